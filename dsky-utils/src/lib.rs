@@ -1,11 +1,26 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
+use core::cmp::Ordering;
 use substrate_fixed::{types::{I10F22, I42F22}, traits::FromFixed};
 // Set of traits, required for Coord struct, used in maps pallet
 
+/// Failure of a checked conversion
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConversionError {
+    /// Result doesn't fit into the target type
+    Overflow,
+    /// Divisor is zero
+    DivisionByZero,
+    /// Value is negative, while the target type is unsigned
+    Negative,
+}
+
 pub trait IntDiv<RHS = Self> {
     fn integer_division_u16(self, rhs: RHS) -> u16;
     fn integer_division_u32(self, rhs: RHS) -> u32;
+    /// Whole part of non-negative division. Fails instead of panicking or wrapping.
+    fn checked_integer_division_u16(self, rhs: RHS) -> Result<u16, ConversionError>;
+    fn checked_integer_division_u32(self, rhs: RHS) -> Result<u32, ConversionError>;
 }
 
 pub trait Signed { 
@@ -17,8 +32,19 @@ pub trait FromRaw {
     fn from_raw(input: i32) -> Self;
 }
 
+/// Reverse of FromRaw, gives bits of the fixed-point number for exact integer calculations
+pub trait ToRaw {
+    fn to_raw(self) -> i32;
+}
+
+pub trait FromU32 {
+    fn from_u32(input: u32) -> Self;
+}
+
 pub trait CastToType {
     fn to_u32_with_frac_part(self, cell_size: u32, max_digits_in_frac_part: u8) -> u32;
+    /// Same as to_u32_with_frac_part, but fails for negative values and overflows
+    fn checked_to_u32_with_frac_part(self, cell_size: u32, max_digits_in_frac_part: u8) -> Result<u32, ConversionError>;
 }
 
 // TODO consider naming of two traits, as they are used in pair
@@ -30,12 +56,27 @@ pub trait ToBigCoord {
 pub trait FromBigCoord {
     type Output;
     fn try_from(self) -> Self::Output;
+    /// Fails, if value doesn't fit into the smaller type, instead of truncating it
+    fn checked_try_from(self) -> Result<Self::Output, ConversionError>;
 }
 
-pub trait GetEpsilon {
-    fn get_epsilon() -> Self;
+/// Conversion between metres and degrees, Earth is considered to be a sphere
+pub trait MetricConversion: Sized {
+    /// Degrees of latitude, which cover given distance
+    fn metres_to_lat_degrees(metres: u32) -> Self;
+    /// Degrees of longitude, which cover given distance at given latitude
+    fn metres_to_lon_degrees(metres: u32, lat: Self) -> Self;
+    /// Metres, covered by given degrees of latitude
+    fn lat_degrees_to_metres(self) -> i64;
+    /// Metres, covered by given degrees of longitude at given latitude
+    fn lon_degrees_to_metres(self, lat: Self) -> i64;
+    /// Whole metres of value, which is already given in metres, like altitude
+    fn altitude_to_metres(self) -> i64;
 }
 
+/// Length of one degree of a meridian, in metres (Earth radius is 6371 km)
+pub const METRES_IN_DEGREE: u32 = 111_195;
+
 // Here comes the implementations 
 // Want to change Coord type => impl trait for it here
 impl IntDiv for I10F22 {
@@ -46,6 +87,25 @@ impl IntDiv for I10F22 {
     fn integer_division_u32(self, rhs: I10F22) -> u32 {
         (self / rhs).to_num::<u32>()
     }
+
+    fn checked_integer_division_u16(self, rhs: I10F22) -> Result<u16, ConversionError> {
+        let quotient = self.checked_integer_division_u32(rhs)?;
+        if quotient > u16::max_value() as u32 {
+            return Err(ConversionError::Overflow);
+        }
+        Ok(quotient as u16)
+    }
+
+    /// Raw bits are divided, so the quotient may exceed the range of I10F22
+    fn checked_integer_division_u32(self, rhs: I10F22) -> Result<u32, ConversionError> {
+        if rhs == I10F22::from_num(0) {
+            return Err(ConversionError::DivisionByZero);
+        }
+        if self < I10F22::from_num(0) || rhs < I10F22::from_num(0) {
+            return Err(ConversionError::Negative);
+        }
+        Ok((self.to_bits() / rhs.to_bits()) as u32)
+    }
 }
 
 impl FromRaw for I10F22 {
@@ -54,6 +114,19 @@ impl FromRaw for I10F22 {
     }
 }
 
+impl ToRaw for I10F22 {
+    fn to_raw(self) -> i32 {
+        self.to_bits()
+    }
+}
+
+impl FromU32 for I10F22 {
+    /// Values, which don't fit into the type, are saturated
+    fn from_u32(input: u32) -> Self {
+        I10F22::saturating_from_num(input)
+    }
+}
+
 impl Signed for I10F22 {
     fn abs(self) -> Self {
         self.abs()
@@ -84,6 +157,23 @@ impl CastToType for I10F22 {
 
         integer_part + frac_part
     }
+
+    fn checked_to_u32_with_frac_part(self, coefficient: u32, max_digits_in_frac_part: u8) -> Result<u32, ConversionError> {
+        if self < I10F22::from_num(0) {
+            return Err(ConversionError::Negative);
+        }
+        let scale = 10u32.checked_pow(max_digits_in_frac_part as u32).ok_or(ConversionError::Overflow)?;
+        let integer = self.to_num::<u32>();
+        let integer_part = integer.checked_mul(scale)
+            .and_then(|value| value.checked_mul(coefficient))
+            .ok_or(ConversionError::Overflow)?;
+        let frac_part = (self - I10F22::from_num(integer))
+            .checked_mul_int(scale as i32)
+            .ok_or(ConversionError::Overflow)?
+            .to_num::<u32>();
+
+        integer_part.checked_add(frac_part).ok_or(ConversionError::Overflow)
+    }
 }
 
 impl ToBigCoord for I10F22 {
@@ -99,10 +189,407 @@ impl FromBigCoord for I42F22 {
     fn try_from(self) -> Self::Output {
         I10F22::from_fixed(self)
     }
+
+    fn checked_try_from(self) -> Result<Self::Output, ConversionError> {
+        I10F22::checked_from_fixed(self).ok_or(ConversionError::Overflow)
+    }
+}
+
+/// Cosine of an angle in degrees. Angles beyond -90..90 are treated as 90,
+/// as latitude can't be out of these bounds.
+fn cos_degrees(angle: I42F22) -> I42F22 {
+    // PI * 2^22
+    let pi = I42F22::from_bits(13_176_795);
+    let right_angle = I42F22::from_num(90);
+    let angle = if angle.abs() > right_angle { right_angle } else { angle.abs() };
+    let x = angle * pi / I42F22::from_num(180);
+    let x_squared = x * x;
+    // Taylor series up to x^10. Its remainder is below 5e-7 for |x| <= PI/2, and together
+    // with rounding of the fixed point the error is less than 3e-6, see cos_degrees_error_bound
+    let mut term = I42F22::from_num(1);
+    let mut sum = term;
+    for n in 1..6 {
+        term = -term * x_squared / I42F22::from_num((2 * n - 1) * (2 * n));
+        sum += term;
+    }
+    if sum < I42F22::from_num(0) { I42F22::from_num(0) } else { sum }
+}
+
+impl MetricConversion for I10F22 {
+    fn metres_to_lat_degrees(metres: u32) -> Self {
+        let degrees = I42F22::from_num(metres) / I42F22::from_num(METRES_IN_DEGREE);
+        I10F22::saturating_from_num(degrees)
+    }
+
+    fn metres_to_lon_degrees(metres: u32, lat: Self) -> Self {
+        let parallel_degree = I42F22::from_num(METRES_IN_DEGREE) * cos_degrees(lat.into());
+        if parallel_degree == I42F22::from_num(0) {
+            return I10F22::max_value();
+        }
+        I10F22::saturating_from_num(I42F22::from_num(metres) / parallel_degree)
+    }
+
+    fn lat_degrees_to_metres(self) -> i64 {
+        (I42F22::from(self) * I42F22::from_num(METRES_IN_DEGREE)).to_num::<i64>()
+    }
+
+    fn lon_degrees_to_metres(self, lat: Self) -> i64 {
+        (I42F22::from(self) * I42F22::from_num(METRES_IN_DEGREE) * cos_degrees(lat.into())).to_num::<i64>()
+    }
+
+    fn altitude_to_metres(self) -> i64 {
+        self.to_num::<i64>()
+    }
+}
+
+// Exact geometric predicates. They work on raw bits of coordinates, see ToRaw, and all
+// calculations are done with i128, which can't overflow for i32 input, so results don't
+// depend on rounding.
+
+/// Point as raw latitude and longitude
+pub type RawPoint = (i32, i32);
+
+/// Fraction with positive denominator. Fractions are compared exactly.
+#[derive(Clone, Copy, Debug)]
+pub struct Fraction {
+    numerator: i128,
+    denominator: i128,
+}
+
+impl Fraction {
+    /// Denominator must not be zero
+    pub fn new(numerator: i128, denominator: i128) -> Self {
+        if denominator < 0 {
+            Fraction { numerator: -numerator, denominator: -denominator }
+        } else {
+            Fraction { numerator, denominator }
+        }
+    }
+
+    pub fn numerator(self) -> i128 {
+        self.numerator
+    }
+
+    pub fn denominator(self) -> i128 {
+        self.denominator
+    }
+}
+
+impl PartialEq for Fraction {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Fraction {}
+
+impl PartialOrd for Fraction {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
 }
 
-impl GetEpsilon for I42F22 {
-    fn get_epsilon() -> I42F22 {
-        I42F22::from_num(0.00001f64)
+impl Ord for Fraction {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.numerator * other.denominator).cmp(&(other.numerator * self.denominator))
+    }
+}
+
+/// Doubled signed area of triangle a, b, c. Positive, if a, b, c turn counterclockwise
+/// (latitude is the first axis), zero, if points are collinear
+pub fn cross_product(a: RawPoint, b: RawPoint, c: RawPoint) -> i128 {
+    (b.0 as i128 - a.0 as i128) * (c.1 as i128 - a.1 as i128) -
+    (b.1 as i128 - a.1 as i128) * (c.0 as i128 - a.0 as i128)
+}
+
+/// Turn of a, b, c: Greater for counterclockwise, Less for clockwise, Equal for collinear points
+pub fn orientation(a: RawPoint, b: RawPoint, c: RawPoint) -> Ordering {
+    cross_product(a, b, c).cmp(&0)
+}
+
+/// True, if point lies in the bounding rect of segment a, b, including edges
+fn is_in_segment_rect(a: RawPoint, b: RawPoint, point: RawPoint) -> bool {
+    a.0.min(b.0) <= point.0 && point.0 <= a.0.max(b.0) &&
+    a.1.min(b.1) <= point.1 && point.1 <= a.1.max(b.1)
+}
+
+/// True, if point lies on segment a, b, including it's ends
+pub fn is_on_segment(a: RawPoint, b: RawPoint, point: RawPoint) -> bool {
+    orientation(a, b, point) == Ordering::Equal && is_in_segment_rect(a, b, point)
+}
+
+/// True, if segments a, b and c, d have at least one common point. Touching segments
+/// and segments of zero length are handled too.
+pub fn segments_intersect(a: RawPoint, b: RawPoint, c: RawPoint, d: RawPoint) -> bool {
+    let (c_side, d_side) = (orientation(a, b, c), orientation(a, b, d));
+    let (a_side, b_side) = (orientation(c, d, a), orientation(c, d, b));
+    let opposite = |first: Ordering, second: Ordering| {
+        first != Ordering::Equal && second != Ordering::Equal && first != second
+    };
+    if opposite(c_side, d_side) && opposite(a_side, b_side) {
+        return true;
+    }
+    // End of one segment lies on the other one
+    is_on_segment(a, b, c) || is_on_segment(a, b, d) ||
+    is_on_segment(c, d, a) || is_on_segment(c, d, b)
+}
+
+/// Checks if point lies inside polygon by even-odd rule. Vertices are listed along the border.
+/// Points on edges are treated either way, but always the same for the same input.
+pub fn is_inside_polygon(point: RawPoint, vertices: &[RawPoint]) -> bool {
+    let edges = vertices.iter().zip(vertices.iter().cycle().skip(1));
+    let mut inside = false;
+    for (a, b) in edges {
+        // Edge goes across the point's parallel, and crosses it east of the point
+        if (a.0 > point.0) != (b.0 > point.0) {
+            let side = orientation(*a, *b, point);
+            let east = if b.0 > a.0 { side == Ordering::Less } else { side == Ordering::Greater };
+            if east { inside = !inside; }
+        }
+    }
+    inside
+}
+
+/// True, if polygon's border doesn't cross or touch itself, except neighbour edges
+/// in their common vertex. Polygons with repeated or collinear overlapping edges are not simple.
+pub fn is_simple_polygon(vertices: &[RawPoint]) -> bool {
+    let amount = vertices.len();
+    if amount < 3 {
+        return false;
+    }
+    let edge = |index: usize| (vertices[index], vertices[(index + 1) % amount]);
+    for first in 0..amount {
+        let (a, b) = edge(first);
+        if a == b {
+            return false;
+        }
+        for second in first + 1..amount {
+            let (c, d) = edge(second);
+            let touching = if second == first + 1 {
+                // Common vertex is b == c, so the edges only overlap, if they go back along each other
+                orientation(a, b, d) == Ordering::Equal && (is_on_segment(a, b, d) || is_on_segment(c, d, a))
+            } else if first == 0 && second == amount - 1 {
+                // Common vertex is a == d
+                orientation(c, a, b) == Ordering::Equal && (is_on_segment(a, b, c) || is_on_segment(c, d, b))
+            } else {
+                segments_intersect(a, b, c, d)
+            };
+            if touching {
+                return false;
+            }
+        }
+    }
+    true
+}
+
+/// True, if polygons have at least one common point, touching included
+pub fn polygons_intersect(first: &[RawPoint], second: &[RawPoint]) -> bool {
+    let edges = |vertices: &[RawPoint]| -> Vec<(RawPoint, RawPoint)> {
+        vertices.iter().copied().zip(vertices.iter().copied().cycle().skip(1)).collect()
+    };
+    let second_edges = edges(second);
+    for (a, b) in edges(first) {
+        if second_edges.iter().any(|(c, d)| segments_intersect(a, b, *c, *d)) {
+            return true;
+        }
+    }
+    // Borders don't meet, so polygons are either apart, or one of them is inside the other
+    first.first().map_or(false, |vertex| is_inside_polygon(*vertex, second)) ||
+    second.first().map_or(false, |vertex| is_inside_polygon(*vertex, first))
+}
+
+/// Returns parameters (0 is a, 1 is b) of points, where segment a, b enters and leaves the rect,
+/// including it's edges. None, if segment doesn't touch the rect.
+// Realisation of Liang–Barsky clipping algorithm
+pub fn segment_rect_crossing(
+    a: RawPoint,
+    b: RawPoint,
+    south_west: RawPoint,
+    north_east: RawPoint,
+) -> Option<(Fraction, Fraction)> {
+    let mut enter = Fraction::new(0, 1);
+    let mut leave = Fraction::new(1, 1);
+    let (d_lat, d_lon) = (b.0 as i128 - a.0 as i128, b.1 as i128 - a.1 as i128);
+    // Pair (p, q) for each rect border: south, north, west, east
+    let borders = [
+        (-d_lat, a.0 as i128 - south_west.0 as i128),
+        (d_lat, north_east.0 as i128 - a.0 as i128),
+        (-d_lon, a.1 as i128 - south_west.1 as i128),
+        (d_lon, north_east.1 as i128 - a.1 as i128),
+    ];
+    for (p, q) in borders.iter() {
+        if *p == 0 {
+            // Segment is parallel to the border and lies outside
+            if *q < 0 { return None; }
+        } else {
+            let r = Fraction::new(*q, *p);
+            if *p < 0 {
+                if r > enter { enter = r; }
+            } else if r < leave {
+                leave = r;
+            }
+        }
+    }
+    if enter > leave { return None; }
+    Some((enter, leave))
+}
+
+/// True, if value, which changes linearly from start to end, is not greater than limit
+/// at given parameter (0 is start, 1 is end)
+pub fn is_interpolated_at_most(start: i32, end: i32, at: Fraction, limit: i32) -> bool {
+    let climb = end as i128 - start as i128;
+    start as i128 * at.denominator + climb * at.numerator <= limit as i128 * at.denominator
+}
+
+#[cfg(test)]
+mod conversion_tests {
+    use super::*;
+
+    #[test]
+    fn division_beyond_coord_range() {
+        // 360 / 0.01 doesn't fit into I10F22, but fits into u16
+        let width = I10F22::from_num(360);
+        let delta = I10F22::from_bits(41_943);
+        assert_eq!(width.checked_integer_division_u16(delta), Ok(36_000));
+        assert_eq!(width.checked_integer_division_u16(I10F22::from_bits(4_194)), Err(ConversionError::Overflow));
+        assert_eq!(width.checked_integer_division_u32(I10F22::from_num(0)), Err(ConversionError::DivisionByZero));
+        assert_eq!(I10F22::from_num(-1).checked_integer_division_u32(delta), Err(ConversionError::Negative));
+    }
+
+    #[test]
+    fn cell_index_conversion() {
+        let value = I10F22::from_num(12.5);
+        assert_eq!(value.checked_to_u32_with_frac_part(1, 2), Ok(value.to_u32_with_frac_part(1, 2)));
+        assert_eq!(I10F22::from_num(-12.5).checked_to_u32_with_frac_part(1, 2), Err(ConversionError::Negative));
+        assert_eq!(value.checked_to_u32_with_frac_part(u32::max_value(), 2), Err(ConversionError::Overflow));
+    }
+
+    #[test]
+    fn cos_degrees_error_bound() {
+        // Reference value is a long Taylor series in f64, which error is far below the bound
+        let reference = |degrees: f64| -> f64 {
+            let x = degrees * core::f64::consts::PI / 180.0;
+            let (mut term, mut sum) = (1.0, 1.0);
+            for n in 1..20 {
+                term = -term * x * x / ((2 * n - 1) * (2 * n)) as f64;
+                sum += term;
+            }
+            sum
+        };
+        // Every 0.01 degree from -90 to 90
+        for step in -9_000..=9_000 {
+            let degrees = step as f64 / 100.0;
+            let error = cos_degrees(I42F22::from_num(degrees)).to_num::<f64>() - reference(degrees);
+            assert!(-3e-6 < error && error < 3e-6, "cos({}) is off by {}", degrees, error);
+        }
+    }
+
+    #[test]
+    fn big_coord_out_of_range() {
+        assert_eq!(I42F22::from_num(511).checked_try_from(), Ok(I10F22::from_num(511)));
+        assert_eq!(I42F22::from_num(512).checked_try_from(), Err(ConversionError::Overflow));
+    }
+}
+
+#[cfg(test)]
+mod predicates_tests {
+    use super::*;
+
+    #[test]
+    fn fractions_are_compared_exactly() {
+        assert_eq!(Fraction::new(1, 3), Fraction::new(-2, -6));
+        assert!(Fraction::new(1, 3) < Fraction::new(1_000_001, 3_000_000));
+        assert!(Fraction::new(-1, 3) < Fraction::new(0, 1));
+    }
+
+    #[test]
+    fn orientation_of_nearly_collinear_points() {
+        // Third point is off the line by one raw unit, which is far below any epsilon
+        let (a, b) = ((0, 0), (1 << 30, (1 << 30) - 2));
+        assert_eq!(orientation(a, b, (1 << 29, (1 << 29) - 1)), Ordering::Equal);
+        assert_eq!(orientation(a, b, (1 << 29, (1 << 29) - 2)), Ordering::Less);
+        assert_eq!(orientation(a, b, (1 << 29, 1 << 29)), Ordering::Greater);
+    }
+
+    #[test]
+    fn segments_touching_and_missing() {
+        // Crossing
+        assert!(segments_intersect((0, 0), (10, 10), (0, 10), (10, 0)));
+        // One end lies on the other segment
+        assert!(segments_intersect((0, 0), (10, 10), (5, 5), (10, 0)));
+        // Collinear and overlapping
+        assert!(segments_intersect((0, 0), (10, 0), (5, 0), (15, 0)));
+        // Collinear, but apart
+        assert!(!segments_intersect((0, 0), (10, 0), (11, 0), (15, 0)));
+        // Passes one raw unit away from the end of the other segment
+        assert!(!segments_intersect((0, 0), (10, 10), (6, 5), (10, 0)));
+        // Zero length segment lies on the other one
+        assert!(segments_intersect((0, 0), (10, 10), (3, 3), (3, 3)));
+        assert!(!segments_intersect((0, 0), (10, 10), (3, 4), (3, 4)));
+    }
+
+    #[test]
+    fn point_inside_polygon_near_edges() {
+        let square = [(0, 0), (0, 10), (10, 10), (10, 0)];
+        assert!(is_inside_polygon((5, 5), &square));
+        assert!(is_inside_polygon((1, 9), &square));
+        assert!(!is_inside_polygon((5, 11), &square));
+        assert!(!is_inside_polygon((-1, 5), &square));
+        // Point in the notch of concave polygon is outside
+        let notched = [(0, 0), (0, 10), (10, 10), (5, 5), (10, 0)];
+        assert!(is_inside_polygon((3, 5), &notched));
+        assert!(!is_inside_polygon((8, 5), &notched));
+    }
+
+    #[test]
+    fn simple_and_self_intersecting_polygons() {
+        assert!(is_simple_polygon(&[(0, 0), (0, 10), (10, 10), (10, 0)]));
+        assert!(is_simple_polygon(&[(0, 0), (0, 10), (10, 10), (5, 5), (10, 0)]));
+        // Bow tie, which edges cross in the middle
+        assert!(!is_simple_polygon(&[(0, 0), (10, 10), (0, 10), (10, 0)]));
+        // Vertex touches the opposite edge
+        assert!(!is_simple_polygon(&[(0, 0), (0, 10), (10, 10), (0, 5), (10, 0)]));
+        // Edge goes back along the previous one
+        assert!(!is_simple_polygon(&[(0, 0), (0, 10), (0, 5), (10, 0)]));
+        // Straight angle at a vertex is allowed
+        assert!(is_simple_polygon(&[(0, 5), (0, 0), (10, 0), (0, 10)]));
+        // Repeated vertex and too few vertices
+        assert!(!is_simple_polygon(&[(0, 0), (0, 10), (0, 10), (10, 0)]));
+        assert!(!is_simple_polygon(&[(0, 0), (0, 10)]));
+    }
+
+    #[test]
+    fn polygons_crossing_touching_and_nested() {
+        let square = [(0, 0), (0, 10), (10, 10), (10, 0)];
+        // Triangle in the square's bounding rect, but beyond it's notch
+        let notched = [(0, 0), (0, 10), (10, 10), (5, 5), (10, 0)];
+        let in_notch = [(8, 4), (8, 6), (7, 5)];
+        assert!(!polygons_intersect(&notched, &in_notch));
+        assert!(polygons_intersect(&square, &in_notch));
+        // Nested polygons have no common border points
+        let inner = [(2, 2), (2, 3), (3, 3)];
+        assert!(polygons_intersect(&square, &inner));
+        assert!(polygons_intersect(&inner, &square));
+        // Common edge
+        assert!(polygons_intersect(&square, &[(0, 10), (0, 20), (10, 20), (10, 10)]));
+        assert!(!polygons_intersect(&square, &[(0, 11), (0, 20), (10, 20), (10, 11)]));
+    }
+
+    #[test]
+    fn segment_crosses_rect() {
+        let (enter, leave) = segment_rect_crossing((0, 15), (30, 15), (10, 10), (20, 20)).unwrap();
+        assert_eq!((enter, leave), (Fraction::new(1, 3), Fraction::new(2, 3)));
+        // Entering through the corner
+        let (enter, leave) = segment_rect_crossing((0, 0), (20, 20), (10, 10), (20, 20)).unwrap();
+        assert_eq!((enter, leave), (Fraction::new(1, 2), Fraction::new(1, 1)));
+        assert_eq!(segment_rect_crossing((0, 21), (30, 21), (10, 10), (20, 20)), None);
+    }
+
+    #[test]
+    fn interpolated_value_is_compared_exactly() {
+        // Value at 1/3 of the way from 0 to 10 is 10/3
+        assert!(is_interpolated_at_most(0, 10, Fraction::new(1, 3), 4));
+        assert!(!is_interpolated_at_most(0, 10, Fraction::new(1, 3), 3));
+        assert!(is_interpolated_at_most(10, 0, Fraction::new(1, 1), 0));
     }
 }
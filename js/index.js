@@ -17,7 +17,63 @@ const types = JSON.parse(readFileSync(process.env.types || "../pallets/types.jso
 // Construct parameters for API instance
 const wsProvider = new WsProvider(ws_url);
 
-const rpc = {}
+// Coordinates are raw, i.e. fixed point bits, zones are decoded using types.json
+const rpc = {
+    dsMaps: {
+        getRootIndex: {
+            description: 'Id of the root, which contains given point',
+            params: [
+                { name: 'raw_point', type: '[RawCoord; 2]' },
+                { name: 'at', type: 'Hash', isOptional: true },
+            ],
+            type: 'RootId',
+        },
+        getAreaZones: {
+            description: 'All zones, stored in the area',
+            params: [
+                { name: 'root_id', type: 'RootId' },
+                { name: 'area_id', type: 'AreaId' },
+                { name: 'at', type: 'Hash', isOptional: true },
+            ],
+            type: 'Vec<ZoneOf>',
+        },
+        getRectZones: {
+            description: 'All zones from areas, which given rect overlaps',
+            params: [
+                { name: 'root_id', type: 'RootId' },
+                { name: 'raw_rect', type: '[RawCoord; 4]' },
+                { name: 'at', type: 'Hash', isOptional: true },
+            ],
+            type: 'Vec<ZoneOf>',
+        },
+        getAreaType: {
+            description: 'Type of the area, if it has any data',
+            params: [
+                { name: 'root_id', type: 'RootId' },
+                { name: 'area_id', type: 'AreaId' },
+                { name: 'at', type: 'Hash', isOptional: true },
+            ],
+            type: 'Option<u8>',
+        },
+        getMaxArea: {
+            description: 'Max area index of the root',
+            params: [
+                { name: 'root_id', type: 'RootId' },
+                { name: 'at', type: 'Hash', isOptional: true },
+            ],
+            type: 'AreaId',
+        },
+        validateRoute: {
+            description: 'Every problem, which prevents the route from being added',
+            params: [
+                { name: 'raw_waypoints', type: 'Vec<([RawCoord; 3], Moment)>' },
+                { name: 'root_id', type: 'RootId' },
+                { name: 'at', type: 'Hash', isOptional: true },
+            ],
+            type: 'RouteReport',
+        },
+    },
+}
 
 async function main() {
     // Construct the actual api
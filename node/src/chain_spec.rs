@@ -1,6 +1,7 @@
 use node_dsky_runtime::{
     AccountId, AuraConfig, BalancesConfig, GenesisConfig, GrandpaConfig, Signature, SudoConfig,
-    SystemConfig, DSAccount, DSAccountsModuleConfig, TransactionPaymentConfig, WASM_BINARY,
+    SystemConfig, DSAccount, DSAccountsModuleConfig, DSMapsModuleConfig, TransactionPaymentConfig,
+    WASM_BINARY,
 };
 use sc_service::ChainType;
 use sp_consensus_aura::sr25519::AuthorityId as AuraId;
@@ -170,6 +171,12 @@ fn testnet_genesis(
                 .into_iter()
                 .collect(),
         }),
+        pallet_ds_maps: Some(DSMapsModuleConfig {
+            // Map is empty by default, staging networks put their roots and zones into chain spec
+            roots: vec![],
+            zones: vec![],
+            area_types: vec![],
+        }),
         pallet_transaction_payment: Some(TransactionPaymentConfig {}),
     }
 }
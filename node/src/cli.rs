@@ -15,6 +15,12 @@ pub enum Subcommand {
     /// Build a chain specification.
     BuildSpec(sc_cli::BuildSpecCmd),
 
+    /// Import flight areas and no-fly zones from GeoJSON into a chain specification.
+    ImportGeojson(crate::geojson::ImportGeoJsonCmd),
+
+    /// Export roots, areas and zones of the ds-maps pallet at given block as GeoJSON.
+    ExportGeojson(crate::geojson::ExportGeoJsonCmd),
+
     /// Validate blocks.
     CheckBlock(sc_cli::CheckBlockCmd),
 
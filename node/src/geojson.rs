@@ -0,0 +1,661 @@
+//! Conversion between GeoJSON airspace and ds-maps storage.
+//!
+//! Import: every feature of the FeatureCollection is a `Polygon`, which `properties.kind` is
+//! either `flight_area` or `no_fly`. Only rectangles along meridians and parallels without holes
+//! are imported, as roots and zones of genesis are rects, and polygons, which cross
+//! the antimeridian, are skipped. Flight area becomes a root with `min_alt` and `max_alt`
+//! altitudes and `delta` area size (0.01 by default). No-fly rectangle becomes a permanent zone
+//! of given `height`, which is rounded up to whole metres. `properties.name` is used in reports,
+//! if present.
+//!
+//! Export: roots, every area of their grids and zones are read from the node's database at given
+//! block, and written as features of `root`, `area` and `zone` kinds. Ids don't fit into
+//! JavaScript numbers, so they are written as strings.
+
+use codec::Decode;
+use node_dsky_runtime::{
+    opaque::Block, DSMapsModule, DSMapsModuleConfig, MaxAreasPerZone, MaxBuildingsInArea, MaxHeight,
+    Runtime,
+    pallet_ds_maps::{
+        Area, AreaId, Box3D, Page, Point2D, Point3D, Rect2D, RootBox, RootBoxOf, RootId, ZoneId,
+        Trait as MapsTrait, ZoneOf, ZoneShape,
+        index::SpatialIndex,
+    },
+};
+use sc_cli::{BlockNumberOrHash, ChainSpec, CliConfiguration, PruningParams, SharedParams};
+use sc_client_api::{Backend, StorageProvider};
+use serde_json::{json, Value};
+use sp_blockchain::HeaderBackend;
+use sp_core::{hashing::twox_128, storage::StorageKey};
+use sp_runtime::generic::BlockId;
+use std::{collections::BTreeMap, fs::File, io::Write, path::PathBuf, sync::Arc};
+use structopt::StructOpt;
+use substrate_fixed::types::I10F22;
+
+/// Default size of root's areas in degrees
+const DEFAULT_DELTA: f64 = 0.01;
+
+/// The `import-geojson` command used to put flight areas and no-fly zones into a chain spec.
+#[derive(Debug, StructOpt)]
+pub struct ImportGeoJsonCmd {
+    /// GeoJSON FeatureCollection with flight areas and no-fly zones.
+    #[structopt(long, parse(from_os_str))]
+    pub input: PathBuf,
+
+    /// Chain spec to put the map into. Accepts the same values, as `--chain` of `build-spec`.
+    /// Map of the chain spec is replaced.
+    #[structopt(long, default_value = "")]
+    pub chain: String,
+
+    /// Where to write the chain spec. It's printed to stdout, if not given.
+    #[structopt(long, parse(from_os_str))]
+    pub output: Option<PathBuf>,
+}
+
+/// The `export-geojson` command used to dump the map, which the chain keeps at given block.
+#[derive(Debug, StructOpt)]
+pub struct ExportGeoJsonCmd {
+    /// Block hash or number to read the map at. Best block is used, if not given.
+    #[structopt(long, value_name = "HASH or NUMBER")]
+    pub block: Option<BlockNumberOrHash>,
+
+    /// Where to write GeoJSON. It's printed to stdout, if not given.
+    #[structopt(long, parse(from_os_str))]
+    pub output: Option<PathBuf>,
+
+    #[allow(missing_docs)]
+    #[structopt(flatten)]
+    pub shared_params: SharedParams,
+
+    #[allow(missing_docs)]
+    #[structopt(flatten)]
+    pub pruning_params: PruningParams,
+}
+
+/// Root, which is accepted into genesis
+struct ImportedRoot {
+    id: RootId,
+    root: RootBox<I10F22>,
+    /// Cells of the root: rows and ranges of columns
+    rows: (u32, u32),
+    columns: Vec<(u32, u32)>,
+}
+
+/// Zone, which is accepted into genesis
+struct ImportedZone {
+    name: String,
+    root_id: RootId,
+    rect: Rect2D<I10F22>,
+    areas: Vec<AreaId>,
+}
+
+impl ImportGeoJsonCmd {
+    /// Run the import command
+    pub fn run(&self, spec: Box<dyn ChainSpec>) -> sc_cli::Result<()> {
+        let collection: Value = serde_json::from_reader(File::open(&self.input)?)
+            .map_err(|e| format!("Can't parse {}: {}", self.input.display(), e))?;
+        if collection["type"] != "FeatureCollection" {
+            return Err("GeoJSON must be a FeatureCollection".into());
+        }
+        let features = collection["features"].as_array().cloned().unwrap_or_default();
+
+        let (map, skipped) = convert_features(&features);
+        for (name, reason) in skipped.iter() {
+            eprintln!("Skipped feature {}: {}", name, reason);
+        }
+        // Genesis is built with the runtime's code, so anything the checks above missed is found here
+        let build = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| map.build_storage::<Runtime>()));
+        match build {
+            Ok(Ok(_)) => {}
+            Ok(Err(e)) => return Err(format!("Map can't be built: {}", e).into()),
+            Err(_) => return Err("Map can't be built, see the panic message above".into()),
+        }
+        eprintln!("Imported {} roots and {} zones, skipped {} features",
+                  map.roots.len(), map.zones.len(), skipped.len());
+
+        let mut json: Value = serde_json::from_str(&spec.as_json(false)?)
+            .map_err(|e| format!("Can't parse chain spec: {}", e))?;
+        let runtime = json.pointer_mut("/genesis/runtime")
+            .ok_or("Chain spec is raw, use the one built without `--raw`")?;
+        runtime["palletDsMaps"] = serde_json::to_value(&map)
+            .map_err(|e| format!("Can't serialize map: {}", e))?;
+        let output = serde_json::to_string_pretty(&json)
+            .map_err(|e| format!("Can't serialize chain spec: {}", e))?;
+
+        match &self.output {
+            Some(path) => File::create(path)?.write_all(output.as_bytes())?,
+            None => println!("{}", output),
+        }
+        Ok(())
+    }
+}
+
+/// Converts features into genesis map. Returns the map with names of skipped features,
+/// and the reasons they were skipped.
+fn convert_features(features: &[Value]) -> (DSMapsModuleConfig, Vec<(String, String)>) {
+    let mut map = DSMapsModuleConfig::default();
+    let mut skipped = Vec::new();
+    let mut roots: Vec<ImportedRoot> = Vec::new();
+    let mut zones: Vec<ImportedZone> = Vec::new();
+
+    // Roots are imported first, so zones can be placed into them whatever the order of features is
+    for (index, feature) in features.iter().enumerate() {
+        if feature["properties"]["kind"] != "flight_area" { continue; }
+        let name = feature_name(index, feature);
+        match convert_root(feature, &roots) {
+            Ok((raw_box, raw_delta, root)) => {
+                map.roots.push((raw_box, raw_delta));
+                roots.push(root);
+            }
+            Err(reason) => skipped.push((name, reason)),
+        }
+    }
+    for (index, feature) in features.iter().enumerate() {
+        let name = feature_name(index, feature);
+        match feature["properties"]["kind"].as_str() {
+            Some("flight_area") => {}
+            Some("no_fly") => match convert_zone(feature, name.clone(), &roots, &zones) {
+                Ok((raw_rect, height, zone)) => {
+                    map.zones.push((raw_rect, height, zone.root_id));
+                    zones.push(zone);
+                }
+                Err(reason) => skipped.push((name, reason)),
+            },
+            _ => skipped.push((name, "kind is neither flight_area, nor no_fly".into())),
+        }
+    }
+    (map, skipped)
+}
+
+fn feature_name(index: usize, feature: &Value) -> String {
+    match feature["properties"]["name"].as_str() {
+        Some(name) => format!("#{} '{}'", index, name),
+        None => format!("#{}", index),
+    }
+}
+
+fn to_raw(value: f64) -> Result<i32, String> {
+    I10F22::checked_from_num(value)
+        .map(|coord| coord.to_bits())
+        .ok_or_else(|| format!("{} doesn't fit into coordinate", value))
+}
+
+fn number_property(feature: &Value, property: &str, default: Option<f64>) -> Result<f64, String> {
+    match &feature["properties"][property] {
+        Value::Null => default.ok_or_else(|| format!("{} is not set", property)),
+        value => value.as_f64().ok_or_else(|| format!("{} is not a number", property)),
+    }
+}
+
+/// Rect of the polygon as raw SW {lat, lon} NE {lat, lon}. Fails, unless the polygon is
+/// a rectangle along meridians and parallels without holes, which doesn't cross the antimeridian.
+fn polygon_rect(feature: &Value) -> Result<[i32; 4], String> {
+    let geometry = &feature["geometry"];
+    if geometry["type"] != "Polygon" {
+        return Err("geometry is not a Polygon".into());
+    }
+    let rings = geometry["coordinates"].as_array().ok_or("polygon has no coordinates")?;
+    if rings.len() != 1 {
+        return Err("polygon must have exterior ring only".into());
+    }
+    let ring = rings[0].as_array().ok_or("polygon has no exterior ring")?;
+    let mut positions: Vec<(f64, f64)> = Vec::with_capacity(ring.len());
+    for position in ring.iter() {
+        // GeoJSON positions are [lon, lat]
+        let lon = position[0].as_f64().ok_or("position has no longitude")?;
+        let lat = position[1].as_f64().ok_or("position has no latitude")?;
+        if !(-90.0..=90.0).contains(&lat) || !(-180.0..=180.0).contains(&lon) {
+            return Err(format!("position [{}, {}] lies outside of the globe", lon, lat));
+        }
+        positions.push((lat, lon));
+    }
+    if positions.len() < 4 || positions.first() != positions.last() {
+        return Err("exterior ring is not closed".into());
+    }
+    // Edge between positions is the shorter way around the globe
+    if positions.windows(2).any(|edge| (edge[1].1 - edge[0].1).abs() > 180.0) {
+        return Err("polygon crosses the antimeridian, split it into two".into());
+    }
+
+    let mut raw_positions: Vec<(i32, i32)> = Vec::with_capacity(positions.len());
+    for (lat, lon) in positions.into_iter() {
+        raw_positions.push((to_raw(lat)?, to_raw(lon)?));
+    }
+    let sw_lat = raw_positions.iter().map(|(lat, _)| *lat).min().unwrap_or_default();
+    let ne_lat = raw_positions.iter().map(|(lat, _)| *lat).max().unwrap_or_default();
+    let sw_lon = raw_positions.iter().map(|(_, lon)| *lon).min().unwrap_or_default();
+    let ne_lon = raw_positions.iter().map(|(_, lon)| *lon).max().unwrap_or_default();
+    // Every position is a corner of the rect, and every corner is present
+    let corners = [(sw_lat, sw_lon), (sw_lat, ne_lon), (ne_lat, ne_lon), (ne_lat, sw_lon)];
+    if !raw_positions.iter().all(|position| corners.contains(position)) ||
+       !corners.iter().all(|corner| raw_positions.contains(corner)) {
+        return Err("polygon is not a rectangle along meridians and parallels".into());
+    }
+    Ok([sw_lat, sw_lon, ne_lat, ne_lon])
+}
+
+fn rect_from_raw(raw_rect: [i32; 4]) -> Rect2D<I10F22> {
+    Rect2D::new(Point2D::new(I10F22::from_bits(raw_rect[0]), I10F22::from_bits(raw_rect[1])),
+                Point2D::new(I10F22::from_bits(raw_rect[2]), I10F22::from_bits(raw_rect[3])))
+}
+
+/// Runs the same checks, as root_add does, and checks the root against already imported ones
+fn convert_root(feature: &Value, roots: &[ImportedRoot]) -> Result<([i32; 6], i32, ImportedRoot), String> {
+    let [sw_lat, sw_lon, ne_lat, ne_lon] = polygon_rect(feature)?;
+    let min_alt = to_raw(number_property(feature, "min_alt", Some(0.0))?)?;
+    let max_alt = to_raw(number_property(feature, "max_alt", Some(MaxHeight::get() as f64))?)?;
+    let raw_delta = to_raw(number_property(feature, "delta", Some(DEFAULT_DELTA))?)?;
+    if min_alt > max_alt {
+        return Err("min_alt is above max_alt".into());
+    }
+    if raw_delta <= 0 {
+        return Err("delta is not positive".into());
+    }
+
+    let south_west = Point3D::new(I10F22::from_bits(sw_lat), I10F22::from_bits(sw_lon), I10F22::from_bits(min_alt));
+    let north_east = Point3D::new(I10F22::from_bits(ne_lat), I10F22::from_bits(ne_lon), I10F22::from_bits(max_alt));
+    if !Page::<I10F22, Runtime>::is_on_globe(south_west) || !Page::<I10F22, Runtime>::is_on_globe(north_east) {
+        return Err("flight area lies outside of the globe".into());
+    }
+    let bounding_box = Box3D::new(south_west, north_east);
+    <<Runtime as MapsTrait>::SpatialIndex as SpatialIndex<Runtime>>::check_bounds(bounding_box)
+        .map_err(|e| format!("flight area doesn't fit into the spatial index: {:?}", e))?;
+    // Area ids are u16, and each root must have at least one area
+    let delta = raw_delta as f64;
+    let total_areas = ((ne_lat - sw_lat) as f64 / delta).floor() * ((ne_lon - sw_lon) as f64 / delta).floor();
+    if total_areas < 1.0 || total_areas > AreaId::MAX as f64 {
+        return Err(format!("flight area has {} areas of given delta, while 1 to {} are allowed",
+                           total_areas, AreaId::MAX));
+    }
+
+    let (sw_row, sw_column) = Page::<I10F22, Runtime>::get_cell_indexes(south_west)
+        .map_err(|e| format!("bitmap cell of flight area can't be found: {:?}", e))?;
+    let (ne_row, ne_column) = Page::<I10F22, Runtime>::get_cell_indexes(north_east)
+        .map_err(|e| format!("bitmap cell of flight area can't be found: {:?}", e))?;
+    let id = RootBox::<I10F22>::get_index(sw_row, sw_column, ne_row, ne_column);
+    let columns = Page::<I10F22, Runtime>::get_column_ranges(sw_column, ne_column);
+    for other in roots.iter() {
+        let rows_overlap = sw_row < other.rows.1 && other.rows.0 < ne_row;
+        let columns_overlap = columns.iter().any(|(first, end)| {
+            other.columns.iter().any(|(other_first, other_end)| first < other_end && other_first < end)
+        });
+        if rows_overlap && columns_overlap {
+            return Err(format!("flight area overlaps root {}", other.id));
+        }
+    }
+
+    let root = ImportedRoot {
+        id,
+        root: RootBox::new(id, bounding_box, I10F22::from_bits(raw_delta)),
+        rows: (sw_row, ne_row),
+        columns,
+    };
+    Ok(([sw_lat, sw_lon, min_alt, ne_lat, ne_lon, max_alt], raw_delta, root))
+}
+
+/// Runs the same checks, as zone_add does, against imported roots and zones
+fn convert_zone(
+    feature: &Value,
+    name: String,
+    roots: &[ImportedRoot],
+    zones: &[ImportedZone],
+) -> Result<([i32; 4], u32, ImportedZone), String> {
+    let raw_rect = polygon_rect(feature)?;
+    let height = number_property(feature, "height", None)?;
+    // Zone is never made lower, than given, so the height is checked after rounding
+    if height < 0.0 || height.ceil() >= MaxHeight::get() as f64 {
+        return Err(format!("height {} is out of bounds, rounded up it must be below {}",
+                           height, MaxHeight::get()));
+    }
+    let height = height.ceil() as u32;
+
+    let rect = rect_from_raw(raw_rect);
+    let (root_id, areas) = roots.iter()
+        .map(|root| (root.id, root.root.get_rect_areas(rect).unwrap_or_default()))
+        .find(|(_, areas)| !areas.is_empty())
+        .ok_or("zone doesn't fit into any flight area")?;
+    if areas.len() > MaxAreasPerZone::get() as usize {
+        return Err(format!("zone overlaps {} areas, while only {} are allowed",
+                           areas.len(), MaxAreasPerZone::get()));
+    }
+
+    let mut zones_in_areas: BTreeMap<AreaId, u16> = BTreeMap::new();
+    for other in zones.iter().filter(|other| other.root_id == root_id) {
+        if other.rect.intersects_rect(rect) {
+            return Err(format!("zone overlaps zone {}", other.name));
+        }
+        for area_id in other.areas.iter().filter(|area_id| areas.contains(area_id)) {
+            *zones_in_areas.entry(*area_id).or_default() += 1;
+        }
+    }
+    if let Some((area_id, _)) = zones_in_areas.iter().find(|(_, amount)| **amount >= MaxBuildingsInArea::get()) {
+        return Err(format!("area {} of root {} is full", area_id, root_id));
+    }
+
+    Ok((raw_rect, height, ImportedZone { name, root_id, rect, areas }))
+}
+
+impl ExportGeoJsonCmd {
+    /// Run the export command
+    pub fn run<C, BE>(&self, client: Arc<C>) -> sc_cli::Result<()>
+    where
+        C: StorageProvider<Block, BE> + HeaderBackend<Block>,
+        BE: Backend<Block>,
+    {
+        let block_id = match &self.block {
+            Some(block) => block.parse::<Block>()?,
+            None => BlockId::Hash(client.info().best_hash),
+        };
+        let pairs: Vec<(Vec<u8>, Vec<u8>)> = client
+            .storage_pairs(&block_id, &StorageKey(twox_128(b"DSMapsModule").to_vec()))?
+            .into_iter()
+            .map(|(key, value)| (key.0, value.0))
+            .collect();
+        let features = export_features(&pairs)?;
+
+        let output = serde_json::to_string_pretty(&json!({
+            "type": "FeatureCollection",
+            "features": features,
+        })).map_err(|e| format!("Can't serialize map: {}", e))?;
+        match &self.output {
+            Some(path) => File::create(path)?.write_all(output.as_bytes())?,
+            None => println!("{}", output),
+        }
+        Ok(())
+    }
+}
+
+impl CliConfiguration for ExportGeoJsonCmd {
+    fn shared_params(&self) -> &SharedParams {
+        &self.shared_params
+    }
+
+    fn pruning_params(&self) -> Option<&PruningParams> {
+        Some(&self.pruning_params)
+    }
+}
+
+/// Converts storage of ds-maps, given as pairs of raw keys and values, into features
+fn export_features(pairs: &[(Vec<u8>, Vec<u8>)]) -> sc_cli::Result<Vec<Value>> {
+    let roots: Vec<(Vec<u8>, RootBoxOf<Runtime>)> = read_map(pairs, b"RootBoxes")?;
+    let areas: Vec<(Vec<u8>, Area)> = read_map(pairs, b"AreaData")?;
+    let slots: Vec<(Vec<u8>, ZoneOf<Runtime>)> = read_map(pairs, b"RedZones")?;
+
+    // Keys are blake2_128_concat hashed, so the key itself follows 16 bytes of hash
+    let mut area_types: BTreeMap<(RootId, AreaId), u8> = BTreeMap::new();
+    for (key, area) in areas.into_iter() {
+        let (root_id, area_id) = decode_key(&key, |input| {
+            let root_id = RootId::decode(input)?;
+            <[u8; 16]>::decode(input)?;
+            Ok((root_id, AreaId::decode(input)?))
+        })?;
+        area_types.insert((root_id, area_id), area.area_type);
+    }
+    // Zone is stored to a slot of every area it overlaps, under the same id
+    let mut zones: BTreeMap<ZoneId, (ZoneOf<Runtime>, Vec<AreaId>)> = BTreeMap::new();
+    for (key, zone) in slots.into_iter() {
+        let slot = decode_key(&key, |input| ZoneId::decode(input))?;
+        let (_, area_id, _) = DSMapsModule::unpack_index(slot);
+        zones.entry(zone.zone_id).or_insert_with(|| (zone, Vec::new())).1.push(area_id);
+    }
+
+    let mut features = Vec::new();
+    for (_, root) in roots.iter() {
+        features.push(root_feature(root));
+        features.extend(area_features(root, &area_types));
+    }
+    features.extend(zones.values().map(|(zone, areas)| zone_feature(zone, areas)));
+    eprintln!("Exported {} roots, {} areas with types and {} zones",
+              roots.len(), area_types.len(), zones.len());
+    Ok(features)
+}
+
+/// Decodes pairs of ds-maps storage map, leaving keys without the prefix of the map
+fn read_map<V: Decode>(pairs: &[(Vec<u8>, Vec<u8>)], storage: &[u8]) -> sc_cli::Result<Vec<(Vec<u8>, V)>> {
+    let prefix = [twox_128(b"DSMapsModule"), twox_128(storage)].concat();
+    pairs.iter()
+        .filter(|(key, _)| key.starts_with(&prefix))
+        .map(|(key, value)| -> sc_cli::Result<(Vec<u8>, V)> {
+            let value = V::decode(&mut &value[..])
+                .map_err(|e| format!("Can't decode value of {}: {}", String::from_utf8_lossy(storage), e))?;
+            Ok((key[prefix.len()..].to_vec(), value))
+        })
+        .collect()
+}
+
+/// Decodes key of the map, skipping the hash of it's first part
+fn decode_key<K>(
+    key: &[u8],
+    decode: impl FnOnce(&mut &[u8]) -> Result<K, codec::Error>,
+) -> sc_cli::Result<K> {
+    if key.len() < 16 {
+        return Err("Storage key is too short".into());
+    }
+    decode(&mut &key[16..]).map_err(|e| format!("Can't decode storage key: {}", e).into())
+}
+
+fn degrees(coord: I10F22) -> f64 {
+    coord.to_num::<f64>()
+}
+
+/// GeoJSON position of the point, i.e. [lon, lat]
+fn position(point: Point2D<I10F22>) -> Value {
+    json!([degrees(point.lon()), degrees(point.lat())])
+}
+
+/// Closed ring of the rect, counterclockwise from south-west corner
+fn rect_ring(rect: Rect2D<I10F22>) -> Value {
+    let (south_west, north_east) = (rect.south_west(), rect.north_east());
+    let south_east = Point2D::new(south_west.lat(), north_east.lon());
+    let north_west = Point2D::new(north_east.lat(), south_west.lon());
+    json!([[position(south_west), position(south_east), position(north_east),
+            position(north_west), position(south_west)]])
+}
+
+fn polygon(ring: Value) -> Value {
+    json!({"type": "Polygon", "coordinates": ring})
+}
+
+fn root_feature(root: &RootBoxOf<Runtime>) -> Value {
+    let (south_west, north_east) = (root.bounding_box.south_west, root.bounding_box.north_east);
+    let (rows, columns) = root.get_grid_size().unwrap_or_default();
+    json!({
+        "type": "Feature",
+        "geometry": polygon(rect_ring(root.bounding_box.projection_on_plane())),
+        "properties": {
+            "kind": "root",
+            "id": root.id.to_string(),
+            "min_alt": degrees(south_west.alt()),
+            "max_alt": degrees(north_east.alt()),
+            "delta": degrees(root.delta),
+            "rows": rows,
+            "columns": columns,
+        },
+    })
+}
+
+/// Every area of the root's grid. Areas are numbered from 1, column by column. Type of an area,
+/// which was never touched, is null.
+fn area_features(root: &RootBoxOf<Runtime>, area_types: &BTreeMap<(RootId, AreaId), u8>) -> Vec<Value> {
+    let south_west = root.bounding_box.south_west.project();
+    let (rows, columns) = root.get_grid_size().unwrap_or_default();
+    let mut features = Vec::with_capacity(rows as usize * columns as usize);
+    for column in 0..columns {
+        for row in 0..rows {
+            let area_id = rows * column + row + 1;
+            let lat = south_west.lat() + root.delta * row as i32;
+            let lon = south_west.lon() + root.delta * column as i32;
+            let rect = Rect2D::new(Point2D::new(lat, lon), Point2D::new(lat + root.delta, lon + root.delta));
+            features.push(json!({
+                "type": "Feature",
+                "geometry": polygon(rect_ring(rect)),
+                "properties": {
+                    "kind": "area",
+                    "root_id": root.id.to_string(),
+                    "area_id": area_id,
+                    "area_type": area_types.get(&(root.id, area_id)),
+                },
+            }));
+        }
+    }
+    features
+}
+
+/// Polygon zones are written with their vertices, rect and circle ones with their bounding rect
+fn zone_feature(zone: &ZoneOf<Runtime>, areas: &[AreaId]) -> Value {
+    let (root_id, area_id, slot) = DSMapsModule::unpack_index(zone.zone_id);
+    let mut properties = json!({
+        "kind": "zone",
+        "id": zone.zone_id.to_string(),
+        "root_id": root_id.to_string(),
+        "area_id": area_id,
+        "slot": slot,
+        "areas": areas,
+        "height": zone.height,
+        "shape": "rect",
+    });
+    let geometry = match &zone.shape {
+        ZoneShape::Rect => polygon(rect_ring(zone.rect)),
+        ZoneShape::Polygon(vertices) => {
+            properties["shape"] = "polygon".into();
+            let mut ring: Vec<Value> = vertices.iter().map(|vertex| position(*vertex)).collect();
+            ring.extend(vertices.first().map(|vertex| position(*vertex)));
+            polygon(json!([ring]))
+        }
+        ZoneShape::Circle(center, radius) => {
+            properties["shape"] = "circle".into();
+            properties["center"] = position(*center);
+            properties["radius"] = (*radius).into();
+            polygon(rect_ring(zone.rect))
+        }
+    };
+    if let Some((start, end)) = zone.time_window {
+        properties["start"] = start.into();
+        properties["end"] = end.into();
+    }
+    json!({"type": "Feature", "geometry": geometry, "properties": properties})
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Feature of given kind, which exterior ring is made of [lon, lat] positions
+    fn feature(kind: &str, ring: &[[f64; 2]], properties: Value) -> Value {
+        let mut feature = json!({
+            "type": "Feature",
+            "geometry": {"type": "Polygon", "coordinates": [ring]},
+            "properties": properties,
+        });
+        feature["properties"]["kind"] = kind.into();
+        feature
+    }
+
+    fn rect(sw_lat: f64, sw_lon: f64, ne_lat: f64, ne_lon: f64) -> Vec<[f64; 2]> {
+        vec![[sw_lon, sw_lat], [ne_lon, sw_lat], [ne_lon, ne_lat], [sw_lon, ne_lat], [sw_lon, sw_lat]]
+    }
+
+    fn flight_area() -> Value {
+        feature("flight_area", &rect(55.0, 37.0, 55.1, 37.1), json!({"min_alt": 1, "max_alt": 300}))
+    }
+
+    #[test]
+    fn zone_height_is_rounded_up_below_max_height() {
+        let max_height = MaxHeight::get() as f64;
+        let features = vec![
+            flight_area(),
+            feature("no_fly", &rect(55.011, 37.011, 55.012, 37.012),
+                    json!({"name": "highest", "height": max_height - 1.5})),
+            feature("no_fly", &rect(55.021, 37.021, 55.022, 37.022),
+                    json!({"name": "too high", "height": max_height - 0.5})),
+        ];
+        let (map, skipped) = convert_features(&features);
+        assert_eq!(map.roots.len(), 1);
+        assert_eq!(map.zones.len(), 1);
+        assert_eq!(map.zones[0].1, MaxHeight::get() - 1);
+        assert_eq!(skipped.len(), 1);
+        assert!(skipped[0].0.contains("too high"));
+        // Runtime accepts the imported map
+        assert!(map.build_storage::<Runtime>().is_ok());
+    }
+
+    #[test]
+    fn antimeridian_crossing_feature_is_skipped() {
+        let crossing = feature("flight_area",
+                               &[[179.9, 10.0], [-179.9, 10.0], [-179.9, 10.1], [179.9, 10.1], [179.9, 10.0]],
+                               json!({}));
+        let (map, skipped) = convert_features(&[crossing]);
+        assert!(map.roots.is_empty());
+        assert_eq!(skipped.len(), 1);
+        assert!(skipped[0].1.contains("antimeridian"));
+    }
+
+    #[test]
+    fn invalid_geometry_is_skipped() {
+        let height = json!({"height": 10});
+        let mut point = feature("no_fly", &[], height.clone());
+        point["geometry"] = json!({"type": "Point", "coordinates": [37.05, 55.05]});
+        let mut with_hole = feature("no_fly", &rect(55.01, 37.01, 55.03, 37.03), height.clone());
+        with_hole["geometry"]["coordinates"].as_array_mut().unwrap()
+            .push(json!(rect(55.015, 37.015, 55.02, 37.02)));
+        let triangle = feature("no_fly", &[[37.04, 55.04], [37.05, 55.04], [37.05, 55.05], [37.04, 55.04]],
+                               height.clone());
+        let open_ring = feature("no_fly", &rect(55.06, 37.06, 55.07, 37.07)[..4], height.clone());
+        let off_globe = feature("no_fly", &rect(55.08, 187.08, 55.09, 187.09), height);
+
+        let (map, skipped) = convert_features(&[flight_area(), point, with_hole, triangle, open_ring, off_globe]);
+        assert_eq!(map.roots.len(), 1);
+        assert!(map.zones.is_empty());
+        let reasons: Vec<&str> = skipped.iter().map(|(_, reason)| reason.as_str()).collect();
+        assert_eq!(reasons.len(), 5);
+        assert!(reasons[0].contains("not a Polygon"));
+        assert!(reasons[1].contains("exterior ring only"));
+        assert!(reasons[2].contains("not a rectangle"));
+        assert!(reasons[3].contains("not closed"));
+        assert!(reasons[4].contains("outside of the globe"));
+    }
+
+    #[test]
+    fn exported_map_is_imported_back() {
+        let features = vec![
+            flight_area(),
+            feature("no_fly", &rect(55.011, 37.011, 55.012, 37.012), json!({"height": 10})),
+            // Zone overlaps two areas
+            feature("no_fly", &rect(55.031, 37.045, 55.032, 37.055), json!({"height": 20.5})),
+        ];
+        let (map, skipped) = convert_features(&features);
+        assert!(skipped.is_empty());
+        let storage = map.build_storage::<Runtime>().unwrap();
+        let pairs: Vec<(Vec<u8>, Vec<u8>)> = storage.top.into_iter().collect();
+        let exported = export_features(&pairs).unwrap();
+        let of_kind = |kind: &str| -> Vec<Value> {
+            exported.iter().filter(|feature| feature["properties"]["kind"] == kind).cloned().collect()
+        };
+
+        // Every area of 10 x 10 grid is exported, and areas with zones have types
+        let areas = of_kind("area");
+        assert_eq!(areas.len(), 100);
+        assert_eq!(areas.iter().filter(|area| !area["properties"]["area_type"].is_null()).count(), 3);
+        let zones = of_kind("zone");
+        let zone_areas: Vec<usize> = zones.iter()
+            .map(|zone| zone["properties"]["areas"].as_array().map_or(0, |areas| areas.len()))
+            .collect();
+        assert_eq!(zone_areas, vec![1, 2]);
+
+        // Exported roots and zones are imported as the same map
+        let mut reimported = Vec::new();
+        for (kind, features) in [("flight_area", of_kind("root")), ("no_fly", zones)].iter() {
+            reimported.extend(features.iter().cloned().map(|mut feature| {
+                feature["properties"]["kind"] = (*kind).into();
+                feature
+            }));
+        }
+        let (exported_map, skipped) = convert_features(&reimported);
+        assert!(skipped.is_empty());
+        assert_eq!(exported_map.roots, map.roots);
+        let (mut exported_zones, mut zones) = (exported_map.zones, map.zones);
+        exported_zones.sort();
+        zones.sort();
+        assert_eq!(exported_zones, zones);
+    }
+}
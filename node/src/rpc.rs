@@ -5,13 +5,21 @@
 
 #![warn(missing_docs)]
 
-use std::sync::Arc;
+use std::{convert::TryFrom, sync::Arc};
 
-use node_dsky_runtime::{opaque::Block, AccountId, Balance, Index};
+use codec::Encode;
+use jsonrpc_core::{Error as RpcError, ErrorCode, Result as RpcResult};
+use jsonrpc_derive::rpc;
+use node_dsky_runtime::{
+    opaque::Block, pallet_ds_maps::{AreaId, RootId}, AccountId, Balance, DsMapsApi, Index,
+};
 pub use sc_rpc_api::DenyUnsafe;
 use sp_api::ProvideRuntimeApi;
 use sp_block_builder::BlockBuilder;
 use sp_blockchain::{Error as BlockChainError, HeaderBackend, HeaderMetadata};
+use sp_core::{Bytes, U256};
+use sp_rpc::number::NumberOrHex;
+use sp_runtime::{generic::BlockId, traits::Block as BlockT};
 use sp_transaction_pool::TransactionPool;
 
 /// Full client dependencies.
@@ -33,6 +41,7 @@ where
     C::Api: substrate_frame_rpc_system::AccountNonceApi<Block, AccountId, Index>,
     C::Api: pallet_transaction_payment_rpc::TransactionPaymentRuntimeApi<Block, Balance>,
     C::Api: BlockBuilder<Block>,
+    C::Api: DsMapsApi<Block>,
     P: TransactionPool + 'static,
 {
     use pallet_transaction_payment_rpc::{TransactionPayment, TransactionPaymentApi};
@@ -52,13 +61,122 @@ where
     )));
 
     io.extend_with(TransactionPaymentApi::to_delegate(TransactionPayment::new(
-        client,
+        client.clone(),
     )));
 
-    // Extend this RPC with a custom API by using the following syntax.
-    // `YourRpcStruct` should have a reference to a client, which is needed
-    // to call into the runtime.
-    // `io.extend_with(YourRpcTrait::to_delegate(YourRpcStruct::new(ReferenceToClient, ...)));`
+    io.extend_with(DsMapsRpc::to_delegate(DsMaps::new(client)));
 
     io
 }
+
+/// Error code for failed runtime api calls.
+const RUNTIME_ERROR: i64 = 1;
+
+/// Queries of the airspace, stored by ds-maps pallet.
+/// Coordinates are raw, as in raw_* calls of the pallet. Root ids may exceed 2^53,
+/// so they are passed as numbers or hex strings. Zones are SCALE-encoded `Vec<ZoneOf>`,
+/// which can be decoded using pallets/types.json.
+#[rpc(server)]
+pub trait DsMapsRpc<BlockHash> {
+    /// Returns id of the root, which contains given point, or 0 if there is none
+    #[rpc(name = "dsMaps_getRootIndex")]
+    fn get_root_index(&self, raw_point: [i32; 2], at: Option<BlockHash>) -> RpcResult<NumberOrHex>;
+
+    /// Returns all zones, stored in the area
+    #[rpc(name = "dsMaps_getAreaZones")]
+    fn get_area_zones(&self, root_id: NumberOrHex, area_id: AreaId, at: Option<BlockHash>) -> RpcResult<Bytes>;
+
+    /// Returns all zones from areas, which given rect overlaps
+    #[rpc(name = "dsMaps_getRectZones")]
+    fn get_rect_zones(&self, root_id: NumberOrHex, raw_rect: [i32; 4], at: Option<BlockHash>) -> RpcResult<Bytes>;
+
+    /// Returns type of the area, or null if area has no data yet
+    #[rpc(name = "dsMaps_getAreaType")]
+    fn get_area_type(&self, root_id: NumberOrHex, area_id: AreaId, at: Option<BlockHash>) -> RpcResult<Option<u8>>;
+
+    /// Returns max area index of the root, or 0 if root doesn't exist
+    #[rpc(name = "dsMaps_getMaxArea")]
+    fn get_max_area(&self, root_id: NumberOrHex, at: Option<BlockHash>) -> RpcResult<AreaId>;
+
+    /// Checks route without adding it, and returns SCALE-encoded `RouteReport` with every problem.
+    /// Each waypoint is given by it's raw location and arrival time.
+    #[rpc(name = "dsMaps_validateRoute")]
+    fn validate_route(&self, raw_waypoints: Vec<([i32; 3], u64)>, root_id: NumberOrHex, at: Option<BlockHash>) -> RpcResult<Bytes>;
+}
+
+/// Implements ds-maps RPC by calling DsMapsApi of the runtime.
+pub struct DsMaps<C> {
+    client: Arc<C>,
+}
+
+impl<C> DsMaps<C> {
+    /// Creates new instance, using given client to call the runtime.
+    pub fn new(client: Arc<C>) -> Self {
+        DsMaps { client }
+    }
+}
+
+impl<C: HeaderBackend<Block>> DsMaps<C> {
+    /// Falls back to the best block, if block is not specified
+    fn block_id(&self, at: Option<<Block as BlockT>::Hash>) -> BlockId<Block> {
+        BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash))
+    }
+}
+
+fn runtime_error(error: impl std::fmt::Debug) -> RpcError {
+    RpcError {
+        code: ErrorCode::ServerError(RUNTIME_ERROR),
+        message: "Unable to query ds-maps.".into(),
+        data: Some(format!("{:?}", error).into()),
+    }
+}
+
+fn to_root_id(root_id: NumberOrHex) -> RpcResult<RootId> {
+    RootId::try_from(root_id).map_err(|_| RpcError::invalid_params("Root id doesn't fit into u64."))
+}
+
+impl<C> DsMapsRpc<<Block as BlockT>::Hash> for DsMaps<C>
+where
+    C: ProvideRuntimeApi<Block> + HeaderBackend<Block> + Send + Sync + 'static,
+    C::Api: DsMapsApi<Block>,
+{
+    fn get_root_index(&self, raw_point: [i32; 2], at: Option<<Block as BlockT>::Hash>) -> RpcResult<NumberOrHex> {
+        let root_id = self.client.runtime_api()
+            .get_root_index(&self.block_id(at), raw_point)
+            .map_err(runtime_error)?;
+        Ok(NumberOrHex::Hex(U256::from(root_id)))
+    }
+
+    fn get_area_zones(&self, root_id: NumberOrHex, area_id: AreaId, at: Option<<Block as BlockT>::Hash>) -> RpcResult<Bytes> {
+        let zones = self.client.runtime_api()
+            .get_area_zones(&self.block_id(at), to_root_id(root_id)?, area_id)
+            .map_err(runtime_error)?;
+        Ok(zones.encode().into())
+    }
+
+    fn get_rect_zones(&self, root_id: NumberOrHex, raw_rect: [i32; 4], at: Option<<Block as BlockT>::Hash>) -> RpcResult<Bytes> {
+        let zones = self.client.runtime_api()
+            .get_rect_zones(&self.block_id(at), to_root_id(root_id)?, raw_rect)
+            .map_err(runtime_error)?;
+        Ok(zones.encode().into())
+    }
+
+    fn get_area_type(&self, root_id: NumberOrHex, area_id: AreaId, at: Option<<Block as BlockT>::Hash>) -> RpcResult<Option<u8>> {
+        self.client.runtime_api()
+            .get_area_type(&self.block_id(at), to_root_id(root_id)?, area_id)
+            .map_err(runtime_error)
+    }
+
+    fn get_max_area(&self, root_id: NumberOrHex, at: Option<<Block as BlockT>::Hash>) -> RpcResult<AreaId> {
+        self.client.runtime_api()
+            .get_max_area(&self.block_id(at), to_root_id(root_id)?)
+            .map_err(runtime_error)
+    }
+
+    fn validate_route(&self, raw_waypoints: Vec<([i32; 3], u64)>, root_id: NumberOrHex, at: Option<<Block as BlockT>::Hash>) -> RpcResult<Bytes> {
+        let report = self.client.runtime_api()
+            .validate_route(&self.block_id(at), raw_waypoints, to_root_id(root_id)?)
+            .map_err(runtime_error)?;
+        Ok(report.encode().into())
+    }
+}
@@ -0,0 +1,66 @@
+//! Benchmarks for pallet_ds_accounts
+
+#![cfg(feature = "runtime-benchmarks")]
+
+use super::*;
+use frame_benchmarking::{account, benchmarks, whitelisted_caller};
+use frame_system::RawOrigin;
+
+fn caller_with_role<T: Trait>(role: u8) -> T::AccountId {
+    let caller: T::AccountId = whitelisted_caller();
+    Module::<T>::set_role(&caller, role.into());
+    caller
+}
+
+benchmarks! {
+    // New account is created, so it's creation time is set
+    account_add {
+        let caller = caller_with_role::<T>(ADMIN_ROLE);
+        let target: T::AccountId = account("target", 0, 0);
+    }: _(RawOrigin::Signed(caller), target.clone(), REGISTRAR_ROLE.into())
+    verify {
+        ensure!(Module::<T>::account_is(&target, REGISTRAR_ROLE.into()), "Account was not added");
+    }
+
+    register_pilot {
+        let caller = caller_with_role::<T>(REGISTRAR_ROLE);
+        let target: T::AccountId = account("target", 0, 0);
+    }: _(RawOrigin::Signed(caller), target.clone(), Default::default())
+    verify {
+        ensure!(Module::<T>::account_is(&target, PILOT_ROLE.into()), "Pilot was not registered");
+    }
+
+    register_uav {
+        let caller = caller_with_role::<T>(PILOT_ROLE);
+        let uav: T::AccountId = account("uav", 0, 0);
+    }: _(RawOrigin::Signed(caller), Default::default(), Default::default(), uav.clone())
+    verify {
+        ensure!(UAVRegistry::<T>::contains_key(&uav), "UAV was not registered");
+    }
+
+    account_disable {
+        let caller = caller_with_role::<T>(ADMIN_ROLE);
+        let target: T::AccountId = account("target", 0, 0);
+        Module::<T>::set_role(&target, REGISTRAR_ROLE.into());
+    }: _(RawOrigin::Signed(caller), target.clone())
+    verify {
+        ensure!(!AccountRegistry::<T>::contains_key(&target), "Account was not disabled");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{new_test_ext, Test};
+    use frame_support::assert_ok;
+
+    #[test]
+    fn test_benchmarks() {
+        new_test_ext().execute_with(|| {
+            assert_ok!(test_benchmark_account_add::<Test>());
+            assert_ok!(test_benchmark_register_pilot::<Test>());
+            assert_ok!(test_benchmark_register_uav::<Test>());
+            assert_ok!(test_benchmark_account_disable::<Test>());
+        });
+    }
+}
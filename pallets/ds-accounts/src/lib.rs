@@ -22,7 +22,9 @@ use frame_support::{
 };
 use frame_system::ensure_signed;
 
+mod benchmarking;
 mod default_weight;
+pub mod weights;
 #[cfg(test)]
 mod mock;
 #[cfg(feature = "payment")]
@@ -334,6 +336,12 @@ impl<T: Trait> Module<T> {
     pub fn account_is(acc: &T::AccountId, role: T::AccountRole) -> bool {
         AccountRegistry::<T>::get(acc).role_is(role)
     }
+
+    /// Gives a role to the account without any checks, to prepare accounts in benchmarks
+    #[cfg(feature = "runtime-benchmarks")]
+    pub fn set_role(acc: &T::AccountId, role: T::AccountRole) {
+        AccountRegistry::<T>::mutate(acc, |account| account.roles = role);
+    }
 }
 
 impl<T: Trait> OnKilledAccount<T::AccountId> for Module<T> {
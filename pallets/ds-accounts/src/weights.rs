@@ -0,0 +1,44 @@
+//! Weights for pallet_ds_accounts
+//!
+//! Storage accesses are counted on the paths of the benchmarks. Execution times are not measured yet,
+//! and the file must be regenerated from the benchmarks on the reference hardware with
+//! scripts/benchmark.sh, which runs `benchmark --pallet pallet_ds_accounts`.
+
+#![allow(unused_parens)]
+#![allow(unused_imports)]
+
+use frame_support::{sp_std::marker::PhantomData, traits::Get, weights::Weight};
+
+/// Weight functions for pallet_ds_accounts.
+pub struct SubstrateWeight<T>(PhantomData<T>);
+impl<T: frame_system::Config> crate::WeightInfo for SubstrateWeight<T> {
+    // Storage: DSAccountsModule AccountRegistry (r:2 w:1)
+    // Storage: DSAccountsModule UAVRegistry (r:1 w:0)
+    // Storage: Timestamp Now (r:1 w:0)
+    fn account_add() -> Weight {
+        (32_000_000 as Weight)
+            .saturating_add(T::DbWeight::get().reads(4 as Weight))
+            .saturating_add(T::DbWeight::get().writes(1 as Weight))
+    }
+    // Storage: DSAccountsModule AccountRegistry (r:2 w:1)
+    // Storage: DSAccountsModule UAVRegistry (r:1 w:0)
+    // Storage: Timestamp Now (r:1 w:0)
+    fn register_pilot() -> Weight {
+        (34_000_000 as Weight)
+            .saturating_add(T::DbWeight::get().reads(4 as Weight))
+            .saturating_add(T::DbWeight::get().writes(1 as Weight))
+    }
+    // Storage: DSAccountsModule AccountRegistry (r:2 w:0)
+    // Storage: DSAccountsModule UAVRegistry (r:0 w:1)
+    fn register_uav() -> Weight {
+        (27_000_000 as Weight)
+            .saturating_add(T::DbWeight::get().reads(2 as Weight))
+            .saturating_add(T::DbWeight::get().writes(1 as Weight))
+    }
+    // Storage: DSAccountsModule AccountRegistry (r:2 w:1)
+    fn account_disable() -> Weight {
+        (25_000_000 as Weight)
+            .saturating_add(T::DbWeight::get().reads(2 as Weight))
+            .saturating_add(T::DbWeight::get().writes(1 as Weight))
+    }
+}
@@ -0,0 +1,262 @@
+//! Benchmarks for pallet_ds_maps. Every extrinsic is measured in its worst case:
+//! areas, which are touched by the call, are already full of zones.
+
+#![cfg(feature = "runtime-benchmarks")]
+
+use super::*;
+use frame_benchmarking::{benchmarks, whitelisted_caller};
+use frame_system::RawOrigin;
+
+// Benchmark root lies between 56.0 and 56.3 lat, 37.0 and 38.0 lon, 30 x 100 areas
+const ROOT_LAT: i64 = 56_000_000;
+const ROOT_LON: i64 = 37_000_000;
+const AREA_SIZE: i64 = 10_000;
+const ROOT_ROWS: i64 = 30;
+const ROOT_COLUMNS: i64 = 100;
+
+/// Converts micro-degrees to raw coordinate
+fn raw(micro_degrees: i64) -> i32 {
+    ((micro_degrees << 22) / 1_000_000) as i32
+}
+
+fn coord<T: Trait>(micro_degrees: i64) -> T::Coord {
+    T::Coord::from_raw(raw(micro_degrees))
+}
+
+fn point<T: Trait>(lat: i64, lon: i64) -> Point2D<T::Coord> {
+    Point2D::new(coord::<T>(lat), coord::<T>(lon))
+}
+
+fn rect<T: Trait>(sw_lat: i64, sw_lon: i64, ne_lat: i64, ne_lon: i64) -> Rect2D<T::Coord> {
+    Rect2D::new(point::<T>(sw_lat, sw_lon), point::<T>(ne_lat, ne_lon))
+}
+
+fn registrar<T: Trait>() -> T::AccountId {
+    let caller: T::AccountId = whitelisted_caller();
+    accounts::Module::<T>::set_role(&caller, REGISTRAR_ROLE.into());
+    caller
+}
+
+/// Adds root, which north-east corner is `height` x `width` micro-degrees from (56.0, 37.0)
+fn add_root<T: Trait>(caller: &T::AccountId, height: i64, width: i64) -> Result<RootId, &'static str> {
+    let bounding_box = Box3D::new(
+        Point3D::new(coord::<T>(ROOT_LAT), coord::<T>(ROOT_LON), coord::<T>(1_000_000)),
+        Point3D::new(coord::<T>(ROOT_LAT + height), coord::<T>(ROOT_LON + width), coord::<T>(3_000_000)));
+    Module::<T>::root_add(RawOrigin::Signed(caller.clone()).into(), bounding_box, coord::<T>(AREA_SIZE))?;
+    Ok(Module::<T>::get_root_index([raw(ROOT_LAT + AREA_SIZE / 2), raw(ROOT_LON + AREA_SIZE / 2)]))
+}
+
+/// Stores given amount of zones with the same rect to each area, bypassing all checks
+fn fill_areas<T: Trait>(root_id: RootId, areas: &[AreaId], zone_rect: Rect2D<T::Coord>, amount: u16) {
+    for area_id in areas.iter() {
+        if !AreaData::contains_key(root_id, area_id) {
+            AreaData::insert(root_id, area_id, Area::new(GREEN_AREA));
+        }
+        for slot in 0..amount {
+            let zone_id = Module::<T>::pack_index(root_id, *area_id, slot);
+            RedZones::<T>::insert(zone_id, ZoneOf::<T>::new(zone_id, zone_rect, 0));
+        }
+        if amount > 0 {
+            ZonesInArea::insert(root_id, area_id, (0..amount).collect::<Vec<u16>>());
+        }
+    }
+}
+
+/// Route along the first row of the benchmark root, which crosses `areas` areas,
+/// above any zone of zero height
+fn row_route<T: Trait>(areas: u32) -> Vec<WaypointOf<T>> {
+    let altitude = coord::<T>(2_000_000);
+    let start = Point3D::new(coord::<T>(ROOT_LAT + 5_000), coord::<T>(ROOT_LON + 500), altitude);
+    let end = Point3D::new(coord::<T>(ROOT_LAT + 5_000),
+                           coord::<T>(ROOT_LON + areas as i64 * AREA_SIZE - 500),
+                           altitude);
+    vec![Waypoint::new(start, 10_u32.into()), Waypoint::new(end, 1_000_u32.into())]
+}
+
+benchmarks! {
+    // Root spans `p` pages, on the default grid each of them is almost filled with root's cells
+    root_add {
+        let p in 1 .. T::MaxPagesAmountToExtract::get();
+        let caller = registrar::<T>();
+        let bounding_box = Box3D::new(
+            Point3D::new(coord::<T>(ROOT_LAT), coord::<T>(ROOT_LON), coord::<T>(1_000_000)),
+            Point3D::new(coord::<T>(ROOT_LAT + 315_000), coord::<T>(ROOT_LON + p as i64 * 500_000),
+                         coord::<T>(3_000_000)));
+    }: _(RawOrigin::Signed(caller), bounding_box, coord::<T>(AREA_SIZE))
+    verify {
+        let root_id = Module::<T>::get_root_index([raw(ROOT_LAT), raw(ROOT_LON)]);
+        ensure!(RootBoxes::<T>::contains_key(root_id), "Root was not added");
+    }
+
+    // Zone overlaps `a` areas, and each of them has `z` zones
+    zone_add {
+        let a in 1 .. T::MaxAreasPerZone::get();
+        let z in 0 .. T::MaxBuildingsInArea::get() as u32 - 1;
+        let caller = registrar::<T>();
+        let root_id = add_root::<T>(&caller, ROOT_ROWS * AREA_SIZE, ROOT_COLUMNS * AREA_SIZE)?;
+        let rows = a as i64;
+        let zone_rect = rect::<T>(ROOT_LAT + 500, ROOT_LON + 100, ROOT_LAT + (rows - 1) * AREA_SIZE + 500, ROOT_LON + 3_000);
+        let filler_rect = rect::<T>(ROOT_LAT + 500, ROOT_LON + 5_000, ROOT_LAT + (rows - 1) * AREA_SIZE + 500, ROOT_LON + 9_000);
+        let areas = RootBoxes::<T>::get(root_id).get_rect_areas(zone_rect).map_err(|_| "Root grid is invalid")?;
+        fill_areas::<T>(root_id, &areas, filler_rect, z as u16);
+    }: _(RawOrigin::Signed(caller), zone_rect, 10, root_id)
+    verify {
+        let last_slot = Module::<T>::pack_index(root_id, areas[0], z as u16);
+        ensure!(RedZones::<T>::get(last_slot).rect == zone_rect, "Zone was not added");
+    }
+
+    // Removal is only queued, the root is cleared in the next blocks
+    root_remove {
+        let caller = registrar::<T>();
+        let root_id = add_root::<T>(&caller, ROOT_ROWS * AREA_SIZE, ROOT_COLUMNS * AREA_SIZE)?;
+    }: _(RawOrigin::Signed(caller), root_id)
+    verify {
+        ensure!(RootsToRemove::get() == vec![root_id], "Root removal was not started");
+    }
+
+    // Temporary zone overlaps `a` areas, and each of them has `z` other zones
+    zone_remove {
+        let a in 1 .. T::MaxAreasPerZone::get();
+        let z in 0 .. T::MaxBuildingsInArea::get() as u32 - 1;
+        let caller = registrar::<T>();
+        let root_id = add_root::<T>(&caller, ROOT_ROWS * AREA_SIZE, ROOT_COLUMNS * AREA_SIZE)?;
+        let rows = a as i64;
+        let zone_rect = rect::<T>(ROOT_LAT + 500, ROOT_LON + 100, ROOT_LAT + (rows - 1) * AREA_SIZE + 500, ROOT_LON + 3_000);
+        let areas = RootBoxes::<T>::get(root_id).get_rect_areas(zone_rect).map_err(|_| "Root grid is invalid")?;
+        fill_areas::<T>(root_id, &areas, zone_rect, z as u16);
+        let end: T::Moment = 1_000_u32.into();
+        let zone = ZoneOf::<T>::new_temporary(0, zone_rect, 10, 0_u32.into(), end);
+        let (_, zone_id) = Module::<T>::insert_zone(root_id, zone)?;
+    }: _(RawOrigin::Signed(caller), zone_id)
+    verify {
+        ensure!(!RedZones::<T>::contains_key(zone_id), "Zone was not removed");
+    }
+
+    change_area_type {
+        let caller = registrar::<T>();
+        let root_id = add_root::<T>(&caller, ROOT_ROWS * AREA_SIZE, ROOT_COLUMNS * AREA_SIZE)?;
+        AreaData::insert(root_id, 1, Area::new(GREEN_AREA));
+    }: _(RawOrigin::Signed(caller), root_id, 1, GREEN_AREA << 1)
+    verify {
+        ensure!(Module::<T>::get_area_type(root_id, 1) == Some(GREEN_AREA << 1), "Area type was not changed");
+    }
+
+    // Route of `w` waypoints crosses `a` areas of the first row, and each of them has `z` zones,
+    // which lie right under the route, and `r` routes along the same line, planned for later
+    route_add {
+        let w in 2 .. T::MaxWaypoints::get();
+        let a in 1 .. ROOT_COLUMNS as u32;
+        let z in 0 .. T::MaxBuildingsInArea::get() as u32;
+        let r in 0 .. T::MaxRoutesInArea::get() - 1;
+        let caller = registrar::<T>();
+        let root_id = add_root::<T>(&caller, ROOT_ROWS * AREA_SIZE, ROOT_COLUMNS * AREA_SIZE)?;
+        let altitude = coord::<T>(2_000_000);
+        let length = a as i64 * AREA_SIZE - 1_000;
+        let waypoints: Vec<WaypointOf<T>> = (0..w as i64).map(|i| {
+            let lat = ROOT_LAT + 2_000 + 6_000 * i / (w as i64 - 1);
+            let lon = ROOT_LON + 500 + length * i / (w as i64 - 1);
+            Waypoint::new(Point3D::new(coord::<T>(lat), coord::<T>(lon), altitude), (10 * (i as u32 + 1)).into())
+        }).collect();
+        let (start, end) = (waypoints[0].location.project(), waypoints[w as usize - 1].location.project());
+        let areas = Line::<T::Coord, T::BigCoord>::new(start, end).get_route_areas(RootBoxes::<T>::get(root_id))
+            .map_err(|_| "Root grid is invalid")?;
+        fill_areas::<T>(root_id, &areas, Rect2D::new(start, end), z as u16);
+        let later = T::TimeSeparation::get().saturating_mul(2_u32.into()).saturating_add(10_000_u32.into());
+        let later_waypoints: Vec<WaypointOf<T>> = waypoints.iter()
+            .map(|waypoint| Waypoint::new(waypoint.location, waypoint.arrival.saturating_add(later)))
+            .collect();
+        for (index, area_id) in areas.iter().enumerate() {
+            let routes: Vec<RouteId> = (0..r).map(|i| (index as u32 * r + i + 1) as RouteId).collect();
+            for route_id in routes.iter() {
+                Routes::<T>::insert(route_id, RouteOf::<T>::new(caller.clone(), later_waypoints.clone(), root_id));
+            }
+            RoutesInArea::insert(root_id, area_id, routes);
+        }
+    }: _(RawOrigin::Signed(caller), waypoints, root_id, (2 * ROOT_ROWS * ROOT_COLUMNS) as u32)
+    verify {
+        ensure!(Routes::<T>::contains_key(0), "Route was not added");
+    }
+
+    // Cancelled route is removed from `a` areas, which it crosses
+    change_route_status {
+        let a in 1 .. ROOT_COLUMNS as u32;
+        let caller = registrar::<T>();
+        let root_id = add_root::<T>(&caller, ROOT_ROWS * AREA_SIZE, ROOT_COLUMNS * AREA_SIZE)?;
+        Module::<T>::route_add(RawOrigin::Signed(caller.clone()).into(), row_route::<T>(a), root_id,
+                               T::MaxAreasPerRoute::get())?;
+    }: route_cancel(RawOrigin::Signed(caller), 0)
+    verify {
+        ensure!(Routes::<T>::get(0).status == RouteStatus::Cancelled, "Route was not cancelled");
+        ensure!(RoutesInArea::iter_prefix(root_id).all(|(_, routes)| routes.is_empty()), "Route was not forgotten");
+    }
+
+    // `e` temporary zones are over, each of them overlaps `a` areas of its own column,
+    // and each of the areas has `z` other zones
+    prune_zones {
+        let e in 0 .. T::MaxZonesPrunedPerBlock::get();
+        let a in 1 .. T::MaxAreasPerZone::get();
+        let z in 0 .. T::MaxBuildingsInArea::get() as u32 - 1;
+        let caller = registrar::<T>();
+        let root_id = add_root::<T>(&caller, ROOT_ROWS * AREA_SIZE, ROOT_COLUMNS * AREA_SIZE)?;
+        for i in 0..e as i64 {
+            let lon = ROOT_LON + i * AREA_SIZE;
+            let zone_rect = rect::<T>(ROOT_LAT + 2_000, lon + 2_000, ROOT_LAT + (a as i64 - 1) * AREA_SIZE + 8_000, lon + 8_000);
+            let areas = RootBoxes::<T>::get(root_id).get_rect_areas(zone_rect).map_err(|_| "Root grid is invalid")?;
+            fill_areas::<T>(root_id, &areas, zone_rect, z as u16);
+            let end: T::Moment = (i as u32 + 1).into();
+            let zone = ZoneOf::<T>::new_temporary(0, zone_rect, 10, 0_u32.into(), end);
+            let (_, zone_id) = Module::<T>::insert_zone(root_id, zone)?;
+            ZonesExpiry::<T>::insert(T::BlockNumber::from(1_u32), zone_id, end);
+        }
+        NextPrunedBlock::<T>::put(T::BlockNumber::from(1_u32));
+        let now: T::Moment = (e + 1).into();
+    }: {
+        Module::<T>::prune_expired_zones(now, 1_u32.into());
+    }
+    verify {
+        ensure!(ZonesExpiry::<T>::iter_prefix(T::BlockNumber::from(1_u32)).next().is_none(), "Zones were not pruned");
+    }
+
+    // Removed root has `a` areas, and each of them has `z` zones
+    clear_root {
+        let a in 1 .. 100;
+        let z in 0 .. T::MaxBuildingsInArea::get() as u32;
+        let caller = registrar::<T>();
+        let root_id = add_root::<T>(&caller, a as i64 * AREA_SIZE + AREA_SIZE / 2, AREA_SIZE + AREA_SIZE / 2)?;
+        let root_rect = rect::<T>(ROOT_LAT + 500, ROOT_LON + 500, ROOT_LAT + a as i64 * AREA_SIZE - 500, ROOT_LON + 9_500);
+        let areas = RootBoxes::<T>::get(root_id).get_rect_areas(root_rect).map_err(|_| "Root grid is invalid")?;
+        fill_areas::<T>(root_id, &areas, root_rect, z as u16);
+        Module::<T>::root_remove(RawOrigin::Signed(caller).into(), root_id)?;
+    }: {
+        Module::<T>::clear_removed_root();
+    }
+    verify {
+        // Zones of the area and the area itself are cleared separately
+        let stored = if z > 0 { 2 * a } else { a };
+        let left = stored.saturating_sub(T::MaxAreasClearedPerBlock::get()) as usize;
+        ensure!(ZonesInArea::iter_prefix(root_id).count() + AreaData::iter_prefix(root_id).count() == left,
+                "Areas were not cleared");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{new_test_ext, Test};
+    use frame_support::assert_ok;
+
+    #[test]
+    fn test_benchmarks() {
+        new_test_ext().execute_with(|| {
+            assert_ok!(test_benchmark_root_add::<Test>());
+            assert_ok!(test_benchmark_zone_add::<Test>());
+            assert_ok!(test_benchmark_root_remove::<Test>());
+            assert_ok!(test_benchmark_zone_remove::<Test>());
+            assert_ok!(test_benchmark_change_area_type::<Test>());
+            assert_ok!(test_benchmark_route_add::<Test>());
+            assert_ok!(test_benchmark_change_route_status::<Test>());
+            assert_ok!(test_benchmark_prune_zones::<Test>());
+            assert_ok!(test_benchmark_clear_root::<Test>());
+        });
+    }
+}
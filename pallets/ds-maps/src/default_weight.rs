@@ -1,22 +1,38 @@
 use frame_support::weights::{constants::RocksDbWeight as DbWeight, Weight};
 
 impl crate::WeightInfo for () {
-    fn root_add() -> Weight {
-        1_000_000_u64.saturating_add(DbWeight::get().writes(1))
+    fn root_add(pages: u32) -> Weight {
+        1_000_000_u64.saturating_add(DbWeight::get().reads_writes(pages as Weight, 1 + pages as Weight))
     }
-    fn zone_add() -> Weight {
-        1_000_000_u64.saturating_add(DbWeight::get().writes(1))
+    fn zone_add(areas: u32, zones: u32) -> Weight {
+        1_000_000_u64.saturating_add(DbWeight::get().reads_writes(areas as Weight * (1 + zones as Weight),
+                                                                  2 * areas as Weight))
     }
     fn root_remove() -> Weight {
-        1_000_000_u64.saturating_add(DbWeight::get().writes(1))
+        1_000_000_u64.saturating_add(DbWeight::get().reads_writes(3, 1))
     }   
-    fn zone_remove() -> Weight {
-        1_000_000_u64.saturating_add(DbWeight::get().writes(1))
+    fn zone_remove(areas: u32, zones: u32) -> Weight {
+        1_000_000_u64.saturating_add(DbWeight::get().reads_writes(areas as Weight * (1 + zones as Weight),
+                                                                  2 * areas as Weight))
     }   
     fn change_area_type() -> Weight {
         100_000_u64.saturating_add(DbWeight::get().writes(1))
     }   
-    fn route_add() -> Weight {
-        100_000_u64.saturating_add(DbWeight::get().writes(1))
+    fn route_add(waypoints: u32, areas: u32, zones: u32, routes: u32) -> Weight {
+        (100_000_u64 * waypoints as Weight)
+            .saturating_add(DbWeight::get().reads_writes(areas as Weight + zones as Weight + routes as Weight,
+                                                         1 + areas as Weight))
+    }
+    fn change_route_status(areas: u32) -> Weight {
+        100_000_u64.saturating_add(DbWeight::get().reads_writes(1 + areas as Weight, 1 + areas as Weight))
+    }
+    fn prune_zones(expired: u32, areas: u32, zones: u32) -> Weight {
+        let removed = expired as Weight * areas as Weight;
+        100_000_u64.saturating_add(DbWeight::get().reads_writes(1 + expired as Weight + removed * (1 + zones as Weight),
+                                                                1 + expired as Weight + 2 * removed))
+    }
+    fn clear_root(areas: u32, zones: u32) -> Weight {
+        1_000_000_u64.saturating_add(DbWeight::get().reads_writes(1 + areas as Weight,
+                                                                  1 + areas as Weight * (1 + zones as Weight)))
     }
 }
@@ -0,0 +1,417 @@
+//! Spatial indexes of roots. Index finds the root, which owns a bitmap cell, and keeps roots
+//! from overlapping. Runtime chooses one of them with `Trait::SpatialIndex`.
+//!
+//! Cells of the root are given as [sw_row, sw_column, ne_row, ne_column] on the current grid.
+//! Root's id describes the same cells, unless the grid has changed since the root was added.
+//!
+//! `BitmapIndex` stores every page of cells, so it's fast for small roots, but can't store
+//! roots, which span more than `Trait::MaxPagesAmountToExtract` pages.
+//! `QuadtreeIndex` stores each root in a few nodes of a quadtree, which are not smaller than
+//! the root, so roots of any size are stored the same way, and lookup of a cell reads
+//! one node per level of the tree.
+//! Kind of the index is stored with it, so the index is rebuilt, when the runtime changes it.
+
+use super::*;
+
+/// Cells of the root as [first_row, first_column, end_row, end_column)
+type CellRect = [u32; 4];
+
+/// Kind of the spatial index, which is stored in `IndexedKind`
+#[derive(Encode, Decode, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IndexKind {
+    Bitmap,
+    Quadtree,
+}
+
+/// Index of roots by the cells, which they occupy
+pub trait SpatialIndex<T: Trait> {
+    /// Kind of the index, which is stored with it
+    fn kind() -> IndexKind;
+    /// Checks, that the index can store roots of such bounds. Doesn't read the storage.
+    fn check_bounds(bounding_box: Box3D<T::Coord>) -> dispatch::DispatchResult;
+    /// Max amount of index entries, which one insertion reads to find overlapping roots
+    fn search_budget() -> u32;
+    /// Stores the root, unless it overlaps some stored root. Nothing is written on error.
+    /// Entries, read to find overlapping roots, are taken from the budget, and insertion fails
+    /// with `IndexLimitExceeded`, if it runs out.
+    fn insert(root_id: RootId, cells: [u32; 4], budget: &mut u32) -> dispatch::DispatchResult;
+    /// Removes the root, stored before. Does nothing, if the root is not stored.
+    fn remove(root_id: RootId, cells: [u32; 4]);
+    /// Returns id of the root, which owns the cell, or 0 if there is none
+    fn find(row: u32, column: u32) -> RootId;
+    /// Weight of insert in the worst case
+    fn insert_weight(bounding_box: Box3D<T::Coord>) -> Weight;
+    /// Weight of remove in the worst case
+    fn remove_weight() -> Weight;
+}
+
+/// Cells of the root as rects. Root, which crosses the antimeridian, has two of them.
+fn root_cell_rects<T: Trait>(cells: [u32; 4]) -> Vec<CellRect> {
+    let [sw_row_index, sw_column_index, ne_row_index, ne_column_index] = cells;
+    Page::<T::Coord, T>::get_column_ranges(sw_column_index, ne_column_index)
+        .into_iter()
+        .filter(|(first_column, end_column)| sw_row_index < ne_row_index && first_column < end_column)
+        .map(|(first_column, end_column)| [sw_row_index, first_column, ne_row_index, end_column])
+        .collect()
+}
+
+fn rects_intersect(first: &CellRect, second: &CellRect) -> bool {
+    first[0] < second[2] && second[0] < first[2] && first[1] < second[3] && second[1] < first[3]
+}
+
+fn rect_contains(rect: &CellRect, row: u32, column: u32) -> bool {
+    rect[0] <= row && row < rect[2] && rect[1] <= column && column < rect[3]
+}
+
+fn rect_covers(rect: &CellRect, inner: &CellRect) -> bool {
+    rect[0] <= inner[0] && inner[2] <= rect[2] && rect[1] <= inner[1] && inner[3] <= rect[3]
+}
+
+/// Index, which marks cells of the root in bitmap pages
+pub struct BitmapIndex<T>(PhantomData<T>);
+
+impl<T: Trait> BitmapIndex<T> {
+    /// Applies given function to every row of the root's cells in each page, which the root
+    /// overlaps. Function gets the page, row and range of columns, counted inside the page.
+    /// Pages are written only if function succeeds for every row, and empty pages are removed.
+    fn update_root_cells(
+        cells: [u32; 4],
+        update: impl Fn(&mut PageOf<T>, u32, u32, u32) -> dispatch::DispatchResult,
+    ) -> dispatch::DispatchResult {
+        let (page_length, page_width) = (T::PageLength::get(), T::PageWidth::get());
+        let mut pages: Vec<(PageId, PageOf<T>)> = Vec::new();
+        for [first_row, first_column, end_row, end_column] in root_cell_rects::<T>(cells) {
+            for row in first_row..end_row {
+                // Columns of the row are split by page borders
+                let mut column = first_column;
+                while column < end_column {
+                    let page_end_column = min(end_column, column - column % page_width + page_width);
+                    let page_index = Page::<T::Coord, T>::get_index(row, column);
+                    let position = match pages.iter().position(|(index, _)| *index == page_index) {
+                        Some(position) => position,
+                        None => {
+                            pages.push((page_index, EarthBitmap::<T>::get(page_index)));
+                            pages.len() - 1
+                        }
+                    };
+                    let page_first_column = column % page_width;
+                    update(&mut pages[position].1, row % page_length,
+                           page_first_column, page_first_column + page_end_column - column)?;
+                    column = page_end_column;
+                }
+            }
+        }
+
+        for (page_index, page) in pages {
+            if page.runs.is_empty() {
+                EarthBitmap::<T>::remove(page_index);
+            } else {
+                EarthBitmap::<T>::insert(page_index, page);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<T: Trait> SpatialIndex<T> for BitmapIndex<T> {
+    fn kind() -> IndexKind {
+        IndexKind::Bitmap
+    }
+
+    fn check_bounds(bounding_box: Box3D<T::Coord>) -> dispatch::DispatchResult {
+        let amount_of_pages_to_extract = Page::<T::Coord, T>::get_amount_of_pages_to_extract_using_box(bounding_box)
+            .map_err(Error::<T>::from)?;
+        ensure!(amount_of_pages_to_extract <= T::MaxPagesAmountToExtract::get(), Error::<T>::PageLimitExceeded);
+        Ok(())
+    }
+
+    fn search_budget() -> u32 {
+        // Only pages of the root are read, and check_bounds limits them
+        u32::MAX
+    }
+
+    fn insert(root_id: RootId, cells: [u32; 4], _budget: &mut u32) -> dispatch::DispatchResult {
+        Self::update_root_cells(cells, |page, row, first_column, end_column| {
+            ensure!(page.is_free(row, first_column, end_column), Error::<T>::OverlappingRoot);
+            page.set_cells(row, first_column, end_column, root_id);
+            Ok(())
+        })
+    }
+
+    fn remove(root_id: RootId, cells: [u32; 4]) {
+        // Cells of another root are kept, if this one was not stored
+        let _ = Self::update_root_cells(cells, |page, row, _, _| {
+            page.runs.retain(|run| run.row as u32 != row || run.root_id != root_id);
+            Ok(())
+        });
+    }
+
+    fn find(row: u32, column: u32) -> RootId {
+        let index = Page::<T::Coord, T>::get_index(row, column);
+        EarthBitmap::<T>::get(index).get_cell(row % T::PageLength::get(), column % T::PageWidth::get())
+    }
+
+    fn insert_weight(bounding_box: Box3D<T::Coord>) -> Weight {
+        let max_pages = T::MaxPagesAmountToExtract::get();
+        <T as Trait>::WeightInfo::root_add(
+            Page::<T::Coord, T>::get_amount_of_pages_to_extract_using_box(bounding_box)
+                .unwrap_or(max_pages)
+                .min(max_pages)
+        )
+    }
+
+    fn remove_weight() -> Weight {
+        let pages = T::MaxPagesAmountToExtract::get() as Weight;
+        <T as frame_system::Config>::DbWeight::get().reads_writes(pages, pages)
+    }
+}
+
+/// Depth of the quadtree. Node of level 0 is 2^16 x 2^16 cells, which covers the whole globe,
+/// and node of the last level is a single cell.
+pub const QUADTREE_DEPTH: u32 = 16;
+
+/// Max amount of nodes, which one root changes: up to 4 nodes for each of two root's rects,
+/// with all their ancestors
+const QUADTREE_MAX_NODES: u64 = 8 * (QUADTREE_DEPTH as u64 + 1);
+
+/// Max amount of nodes, which are read to check, that the root doesn't overlap stored ones.
+/// Root, which needs more, is not stored.
+pub const QUADTREE_MAX_SEARCHED_NODES: u32 = 512;
+
+/// Node of the quadtree as level << 32 | row << 16 | column, where row and column
+/// are counted among nodes of the level
+pub type QuadKey = u64;
+
+#[derive(Encode, Decode, Default, Debug, Clone, PartialEq, Eq)]
+pub struct QuadNode {
+    /// Roots, stored in this node, with their cells
+    pub roots: Vec<(RootId, [u32; 4])>,
+    /// Amount of root's rects, stored in this node and all nodes below it
+    pub subtree_roots: u32,
+}
+
+/// Position of the quadtree node
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct QuadPosition {
+    level: u32,
+    row: u32,
+    column: u32,
+}
+
+impl QuadPosition {
+    /// Node of the given level, which contains the cell
+    fn containing(level: u32, row: u32, column: u32) -> Self {
+        let shift = QUADTREE_DEPTH - level;
+        QuadPosition { level, row: row >> shift, column: column >> shift }
+    }
+
+    fn key(self) -> QuadKey {
+        (self.level as QuadKey) << 32 | (self.row as QuadKey) << 16 | self.column as QuadKey
+    }
+
+    /// Cells, covered by the node
+    fn rect(self) -> CellRect {
+        let size = 1 << (QUADTREE_DEPTH - self.level);
+        [self.row * size, self.column * size, (self.row + 1) * size, (self.column + 1) * size]
+    }
+
+    fn parent(self) -> Option<Self> {
+        if self.level == 0 {
+            return None;
+        }
+        Some(QuadPosition { level: self.level - 1, row: self.row >> 1, column: self.column >> 1 })
+    }
+
+    fn children(self) -> Vec<Self> {
+        if self.level == QUADTREE_DEPTH {
+            return Vec::new();
+        }
+        let (row, column) = (self.row << 1, self.column << 1);
+        vec![
+            QuadPosition { level: self.level + 1, row, column },
+            QuadPosition { level: self.level + 1, row, column: column + 1 },
+            QuadPosition { level: self.level + 1, row: row + 1, column },
+            QuadPosition { level: self.level + 1, row: row + 1, column: column + 1 },
+        ]
+    }
+
+    /// Nodes, which store the rect. These are nodes of the deepest level, which are
+    /// not smaller than the rect, so the rect overlaps at most 2 x 2 of them.
+    fn rect_nodes(rect: &CellRect) -> Vec<Self> {
+        let size = max(rect[2] - rect[0], rect[3] - rect[1]);
+        let mut level = QUADTREE_DEPTH;
+        while level > 0 && (1 << (QUADTREE_DEPTH - level)) < size {
+            level -= 1;
+        }
+        let first = Self::containing(level, rect[0], rect[1]);
+        let last = Self::containing(level, rect[2] - 1, rect[3] - 1);
+        let mut nodes = Vec::new();
+        for row in first.row..=last.row {
+            for column in first.column..=last.column {
+                nodes.push(QuadPosition { level, row, column });
+            }
+        }
+        nodes
+    }
+}
+
+/// Index, which stores roots in a quadtree over the cells. Supports roots of any size.
+pub struct QuadtreeIndex<T>(PhantomData<T>);
+
+impl<T: Trait> QuadtreeIndex<T> {
+    /// True, if any stored root overlaps the cells. Fails, if more, than `budget` nodes,
+    /// are needed to find it out.
+    pub fn cells_overlap(cells: [u32; 4], mut budget: u32) -> Result<bool, dispatch::DispatchError> {
+        Self::search_overlap(cells, &mut budget)
+    }
+
+    /// Same as `cells_overlap`, but read nodes are taken from the given budget
+    fn search_overlap(cells: [u32; 4], budget: &mut u32) -> Result<bool, dispatch::DispatchError> {
+        let top = QuadPosition { level: 0, row: 0, column: 0 };
+        for rect in root_cell_rects::<T>(cells).iter() {
+            if Self::overlaps(rect, top, budget)? {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    /// True, if any root, stored in the node or below it, overlaps the rect.
+    /// Every read node is taken from the budget.
+    fn overlaps(rect: &CellRect, position: QuadPosition, budget: &mut u32) -> Result<bool, dispatch::DispatchError> {
+        ensure!(*budget > 0, Error::<T>::IndexLimitExceeded);
+        *budget -= 1;
+        let node = QuadNodes::get(position.key());
+        if node.subtree_roots == 0 {
+            return Ok(false);
+        }
+        // Roots, stored in the node or below it, overlap the node, so they overlap the rect too
+        if rect_covers(rect, &position.rect()) {
+            return Ok(true);
+        }
+        let stored_here = node.roots.iter().any(|(_, cells)| {
+            root_cell_rects::<T>(*cells).iter().any(|other| rects_intersect(rect, other))
+        });
+        if stored_here {
+            return Ok(true);
+        }
+        for child in position.children() {
+            if rects_intersect(rect, &child.rect()) && Self::overlaps(rect, child, budget)? {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    /// Applies given function to nodes at given positions, and to all their ancestors.
+    /// Function gets the node, and whether it's one of the given ones. Each node is written once,
+    /// and nodes without roots below are removed.
+    fn update_nodes(positions: Vec<QuadPosition>, update: impl Fn(&mut QuadNode, bool)) {
+        let mut nodes: Vec<(QuadKey, QuadNode)> = Vec::new();
+        for position in positions {
+            let mut current = Some(position);
+            while let Some(node_position) = current {
+                let key = node_position.key();
+                let index = match nodes.iter().position(|(node_key, _)| *node_key == key) {
+                    Some(index) => index,
+                    None => {
+                        nodes.push((key, QuadNodes::get(key)));
+                        nodes.len() - 1
+                    }
+                };
+                update(&mut nodes[index].1, node_position == position);
+                current = node_position.parent();
+            }
+        }
+
+        for (key, node) in nodes {
+            if node.subtree_roots == 0 {
+                QuadNodes::remove(key);
+            } else {
+                QuadNodes::insert(key, node);
+            }
+        }
+    }
+
+    /// Nodes, which store the root, without repeats
+    fn root_positions(cells: [u32; 4]) -> Vec<QuadPosition> {
+        let mut positions: Vec<QuadPosition> = root_cell_rects::<T>(cells)
+            .iter()
+            .flat_map(QuadPosition::rect_nodes)
+            .collect();
+        positions.sort_unstable_by_key(|position| position.key());
+        positions.dedup();
+        positions
+    }
+}
+
+impl<T: Trait> SpatialIndex<T> for QuadtreeIndex<T> {
+    fn kind() -> IndexKind {
+        IndexKind::Quadtree
+    }
+
+    fn check_bounds(_bounding_box: Box3D<T::Coord>) -> dispatch::DispatchResult {
+        // Any root on the globe fits into the tree
+        Ok(())
+    }
+
+    fn search_budget() -> u32 {
+        QUADTREE_MAX_SEARCHED_NODES
+    }
+
+    fn insert(root_id: RootId, cells: [u32; 4], budget: &mut u32) -> dispatch::DispatchResult {
+        ensure!(!Self::search_overlap(cells, budget)?, Error::<T>::OverlappingRoot);
+        Self::update_nodes(Self::root_positions(cells), |node, stored_here| {
+            if stored_here {
+                node.roots.push((root_id, cells));
+            }
+            node.subtree_roots += 1;
+        });
+        Ok(())
+    }
+
+    fn remove(root_id: RootId, cells: [u32; 4]) {
+        let positions = Self::root_positions(cells);
+        // Counters of ancestors are kept, if the root was not stored
+        let stored = positions.first().map_or(false, |position| {
+            QuadNodes::get(position.key()).roots.iter().any(|(stored_id, _)| *stored_id == root_id)
+        });
+        if !stored {
+            return;
+        }
+        Self::update_nodes(positions, |node, stored_here| {
+            if stored_here {
+                node.roots.retain(|(stored_id, _)| *stored_id != root_id);
+            }
+            node.subtree_roots = node.subtree_roots.saturating_sub(1);
+        });
+    }
+
+    fn find(row: u32, column: u32) -> RootId {
+        for level in 0..=QUADTREE_DEPTH {
+            let node = QuadNodes::get(QuadPosition::containing(level, row, column).key());
+            if node.subtree_roots == 0 {
+                break;
+            }
+            let owner = node.roots.iter().find(|(_, cells)| {
+                root_cell_rects::<T>(*cells).iter().any(|rect| rect_contains(rect, row, column))
+            });
+            if let Some((root_id, _)) = owner {
+                return *root_id;
+            }
+        }
+        0
+    }
+
+    fn insert_weight(_bounding_box: Box3D<T::Coord>) -> Weight {
+        // Overlap search reads up to its budget, then the root's nodes are read and written
+        <T as Trait>::WeightInfo::root_add(0).saturating_add(
+            <T as frame_system::Config>::DbWeight::get()
+                .reads_writes(QUADTREE_MAX_SEARCHED_NODES as u64 + QUADTREE_MAX_NODES, QUADTREE_MAX_NODES)
+        )
+    }
+
+    fn remove_weight() -> Weight {
+        <T as frame_system::Config>::DbWeight::get().reads_writes(QUADTREE_MAX_NODES, QUADTREE_MAX_NODES)
+    }
+}
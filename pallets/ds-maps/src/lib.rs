@@ -5,11 +5,13 @@ use serde::{Deserialize, Serialize};
 
 use frame_support::{
     codec::{Decode, Encode},
-    storage::StorageDoubleMap,
+    storage::{unhashed, IterableStorageDoubleMap, StorageDoubleMap, StoragePrefixedMap},
     dispatch::fmt::Debug,
     sp_runtime::sp_std::{ops::{Sub, Div, Mul, Add}, vec::Vec},
+    sp_runtime::traits::{SaturatedConversion, Saturating},
     decl_error, decl_event, decl_module, decl_storage, dispatch, ensure,    
     weights::Weight,
+    Blake2_128Concat, ReversibleStorageHasher,
     Parameter,
     traits::Get,
 };
@@ -18,16 +20,24 @@ use sp_std::{
     str::FromStr,
     marker::PhantomData,
     vec, 
-    mem::swap,
     cmp::{max, min}
 };
 
-use dsky_utils::{CastToType, FromRaw, IntDiv, Signed, ToBigCoord, FromBigCoord, GetEpsilon};
+use dsky_utils::{
+    CastToType, FromRaw, ToRaw, FromU32, IntDiv, MetricConversion, Signed, ToBigCoord, FromBigCoord,
+    ConversionError, Fraction, RawPoint, is_inside_polygon, is_interpolated_at_most, is_simple_polygon, polygons_intersect,
+    segment_rect_crossing, segments_intersect,
+};
 use frame_system::ensure_signed;
 use pallet_ds_accounts as accounts;
 use accounts::{REGISTRAR_ROLE, PILOT_ROLE};
+use index::SpatialIndex;
 
+mod benchmarking;
 mod default_weight;
+pub mod index;
+mod migrations;
+pub mod weights;
 #[cfg(test)]
 mod mock;
 #[cfg(test)]
@@ -35,15 +45,90 @@ mod tests;
 
 pub const GREEN_AREA: u8 = 0b00000001;
 
-/// Page parameters
-pub const MAX_PAGES_AMOUNT_TO_EXTRACT: u32 = 4;
-pub const PAGE_LENGTH: u32 = 32;
-pub const PAGE_WIDTH: u32 = 50;
+/// Version of the storage layout, which is written at genesis. Older layouts are migrated
+/// on runtime upgrade, see `migrations`.
+pub const STORAGE_VERSION: u16 = 1;
+
+/// Resolution of the bitmap grid, which splits the globe into cells, and cells into pages.
+/// Every pallet's Trait is a grid, see `Trait::BitmapCellLength` and the next parameters.
+pub trait Grid {
+    /// Cell size along latitude in 10^-cell_size_degree degree
+    fn cell_length() -> u32;
+    /// Cell size along longitude in 10^-cell_size_degree degree
+    fn cell_width() -> u32;
+    /// Digits of the degree's fraction, in which cell sizes are given
+    fn cell_size_degree() -> u8;
+    /// Page size in cells along latitude
+    fn page_length() -> u32;
+    /// Page size in cells along longitude
+    fn page_width() -> u32;
+
+    /// Row indexes are shifted by this padding, after latitude is shifted to be non-negative.
+    /// Padding keeps a page border on the equator, and makes zero index unused.
+    fn lat_cell_padding() -> u32 {
+        let rows_to_equator = 90 * 10u32.pow(Self::cell_size_degree() as u32) / Self::cell_length();
+        Self::page_length() - rows_to_equator % Self::page_length()
+    }
+
+    /// Same as lat_cell_padding for columns and the prime meridian
+    fn lon_cell_padding() -> u32 {
+        let columns_to_meridian = 180 * 10u32.pow(Self::cell_size_degree() as u32) / Self::cell_width();
+        Self::page_width() - columns_to_meridian % Self::page_width()
+    }
+
+    /// First column of the globe, i.e. -180 degrees of longitude
+    fn globe_first_column() -> u32 {
+        Self::lon_cell_padding()
+    }
+
+    /// Next after the last column of the globe, i.e. 180 degrees of longitude
+    fn globe_end_column() -> u32 {
+        let full_angle = 360 * 10u32.pow(Self::cell_size_degree() as u32);
+        (full_angle + Self::cell_width() - 1) / Self::cell_width() + Self::lon_cell_padding()
+    }
+
+    fn resolution() -> GridResolution {
+        GridResolution {
+            cell_length: Self::cell_length(),
+            cell_width: Self::cell_width(),
+            cell_size_degree: Self::cell_size_degree(),
+            page_length: Self::page_length(),
+            page_width: Self::page_width(),
+        }
+    }
+}
+
+impl<T: Trait> Grid for T {
+    fn cell_length() -> u32 {
+        T::BitmapCellLength::get()
+    }
+
+    fn cell_width() -> u32 {
+        T::BitmapCellWidth::get()
+    }
+
+    fn cell_size_degree() -> u8 {
+        T::CellSizeDegree::get()
+    }
+
+    fn page_length() -> u32 {
+        T::PageLength::get()
+    }
+
+    fn page_width() -> u32 {
+        T::PageWidth::get()
+    }
+}
 
-/// Bitmap cell parameters in degree e-2
-const BITMAP_CELL_LENGTH: u32 = 1;
-const BITMAP_CELL_WIDTH: u32 = 1;
-const CELL_SIZE_DEGREE: u8 = 2;
+/// Grid parameters, see `Grid`. Spatial index is rebuilt, when they change.
+#[derive(Encode, Decode, Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GridResolution {
+    pub cell_length: u32,
+    pub cell_width: u32,
+    pub cell_size_degree: u8,
+    pub page_length: u32,
+    pub page_width: u32,
+}
 
 #[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
 #[derive(Encode, Decode, Default, Debug, Clone, Copy, PartialEq, Eq)]
@@ -52,6 +137,25 @@ pub struct Point2D<Coord> {
     lon: Coord,
 }
 
+impl<Coord: Copy> Point2D<Coord> {
+    /// Latitude in degrees
+    pub fn lat(self) -> Coord {
+        self.lat
+    }
+
+    /// Longitude in degrees, up to 360 for points of roots, which cross the antimeridian
+    pub fn lon(self) -> Coord {
+        self.lon
+    }
+}
+
+impl<Coord: ToRaw + Copy> Point2D<Coord> {
+    /// Raw bits of coordinates, used by exact predicates of dsky_utils
+    pub fn to_raw(self) -> RawPoint {
+        (self.lat.to_raw(), self.lon.to_raw())
+    }
+}
+
 impl<
     Coord: PartialOrd + Sub<Output = Coord> + Signed + IntDiv
     > Point2D<Coord> {
@@ -103,10 +207,36 @@ impl<
     }
 }
 
+impl<Coord: PartialOrd + Copy> Rect2D<Coord> {
+    /// Corner with the least latitude and longitude
+    pub fn south_west(self) -> Point2D<Coord> {
+        self.south_west
+    }
+
+    /// Corner with the greatest latitude and longitude
+    pub fn north_east(self) -> Point2D<Coord> {
+        self.north_east
+    }
+
+    /// Smallest rect, containing all given points. None, if there are no points.
+    pub fn bounding(points: &[Point2D<Coord>]) -> Option<Self> {
+        let first = points.first()?;
+        let mut south_west = *first;
+        let mut north_east = *first;
+        for point in points.iter() {
+            if point.lat < south_west.lat { south_west.lat = point.lat; }
+            if point.lon < south_west.lon { south_west.lon = point.lon; }
+            if point.lat > north_east.lat { north_east.lat = point.lat; }
+            if point.lon > north_east.lon { north_east.lon = point.lon; }
+        }
+        Some(Rect2D{south_west, north_east})
+    }
+}
+
 #[cfg(test)]
 mod rect_tests {
     use super::*;
-    use crate::tests::{construct_custom_rect, coord};
+    use crate::tests::{construct_custom_rect, coord, Coord};
     // construct_custom_rect(a, b, c, d)
     //
     //       (c,d)
@@ -163,22 +293,87 @@ mod rect_tests {
         let point = Point2D::new(coord("0"), coord("0"));
         assert!(!rect.is_point_inside(point));
     }
+
+    #[test]
+    fn bounding_rect_of_points() {
+        let points = vec![
+            Point2D::new(coord("2"), coord("1")),
+            Point2D::new(coord("3"), coord("4")),
+            Point2D::new(coord("1"), coord("3")),
+        ];
+        assert_eq!(Rect2D::bounding(&points), Some(construct_custom_rect("1", "1", "3", "4")));
+        assert_eq!(Rect2D::<Coord>::bounding(&[]), None);
+    }
+}
+
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[derive(Encode, Decode, Clone, Debug, PartialEq, Eq)]
+pub enum ZoneShape<Coord> {
+    /// Zone fills all it's bounding rect
+    Rect,
+    /// Zone is a polygon with given vertices, listed one by one along it's border
+    Polygon(Vec<Point2D<Coord>>),
+    /// Zone is a circle with given center and radius in metres
+    Circle(Point2D<Coord>, LightCoord),
+}
+
+impl<Coord> Default for ZoneShape<Coord> {
+    fn default() -> Self {
+        ZoneShape::Rect
+    }
 }
 
 #[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
 #[derive(Encode, Decode, Clone, Default)]
-pub struct Zone<Coord> {
+pub struct Zone<Coord, Moment> {
     pub zone_id: ZoneId,
+    /// Bounding rect of the zone
     pub rect: Rect2D<Coord>,
     pub height: LightCoord,
+    pub shape: ZoneShape<Coord>,
+    /// Moments, when temporary zone starts and stops restricting flights. None for permanent zones.
+    pub time_window: Option<(Moment, Moment)>,
 }
 
-impl<Coord> Zone<Coord> {
+impl<Coord, Moment: PartialOrd + Copy> Zone<Coord, Moment> {
     pub fn new(zone_id: ZoneId, rect: Rect2D<Coord>, height: LightCoord) -> Self {
-        Zone {zone_id, rect, height}
+        Zone {zone_id, rect, height, shape: ZoneShape::Rect, time_window: None}
+    }
+
+    pub fn new_polygon(zone_id: ZoneId, rect: Rect2D<Coord>, height: LightCoord, vertices: Vec<Point2D<Coord>>) -> Self {
+        Zone {zone_id, rect, height, shape: ZoneShape::Polygon(vertices), time_window: None}
+    }
+
+    pub fn new_circle(zone_id: ZoneId, rect: Rect2D<Coord>, height: LightCoord, center: Point2D<Coord>, radius: LightCoord) -> Self {
+        Zone {zone_id, rect, height, shape: ZoneShape::Circle(center, radius), time_window: None}
+    }
+
+    pub fn new_temporary(zone_id: ZoneId, rect: Rect2D<Coord>, height: LightCoord, start: Moment, end: Moment) -> Self {
+        Zone {zone_id, rect, height, shape: ZoneShape::Rect, time_window: Some((start, end))}
+    }
+
+    /// True, if zone restricts flights at any moment between given ones, including edges
+    pub fn is_active_between(&self, from: Moment, to: Moment) -> bool {
+        match self.time_window {
+            Some((start, end)) => start <= to && from <= end,
+            None => true,
+        }
     }
 } 
 
+impl<Coord: ToRaw + Copy, Moment> Zone<Coord, Moment> {
+    /// Border of the zone as raw points. Circle is replaced by it's bounding rect.
+    pub fn outline(&self) -> Vec<RawPoint> {
+        match &self.shape {
+            ZoneShape::Polygon(vertices) => vertices.iter().map(|vertex| vertex.to_raw()).collect(),
+            _ => {
+                let (south_west, north_east) = (self.rect.south_west.to_raw(), self.rect.north_east.to_raw());
+                vec![south_west, (north_east.0, south_west.1), north_east, (south_west.0, north_east.1)]
+            }
+        }
+    }
+}
+
 #[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
 #[derive(Encode, Decode, Default, Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Point3D<Coord> {
@@ -187,6 +382,23 @@ pub struct Point3D<Coord> {
     alt: Coord,
 }
 
+impl<Coord: Copy> Point3D<Coord> {
+    /// Latitude in degrees
+    pub fn lat(self) -> Coord {
+        self.lat
+    }
+
+    /// Longitude in degrees, see `Point2D::lon`
+    pub fn lon(self) -> Coord {
+        self.lon
+    }
+
+    /// Altitude in metres
+    pub fn alt(self) -> Coord {
+        self.alt
+    }
+}
+
 impl<
     Coord: PartialOrd + Sub<Output = Coord> + Signed + IntDiv
     > Point3D<Coord> {
@@ -234,6 +446,321 @@ impl<Coord, Moment> Waypoint<Coord, Moment> {
     }
 }
 
+/// Route lifecycle: Planned -> Active -> Completed, or Cancelled at any moment before completion
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[derive(Encode, Decode, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RouteStatus {
+    Planned,
+    Active,
+    Completed,
+    Cancelled,
+}
+
+impl Default for RouteStatus {
+    fn default() -> Self {
+        RouteStatus::Planned
+    }
+}
+
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[derive(Encode, Decode, Clone, Default, Debug, PartialEq, Eq)]
+pub struct Route<AccountId, Coord, Moment> {
+    pub pilot: AccountId,
+    pub waypoints: Vec<Waypoint<Coord, Moment>>,
+    pub root_id: RootId,
+    pub status: RouteStatus,
+}
+
+impl<AccountId, Coord, Moment> Route<AccountId, Coord, Moment> {
+    pub fn new(pilot: AccountId, waypoints: Vec<Waypoint<Coord, Moment>>, root_id: RootId) -> Self {
+        Route{pilot, waypoints, root_id, status: RouteStatus::Planned}
+    }
+
+    /// True, if route still takes part in airspace usage
+    pub fn is_pending(&self) -> bool {
+        self.status == RouteStatus::Planned || self.status == RouteStatus::Active
+    }
+}
+
+/// Problem, which prevents the route from being added
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[derive(Encode, Decode, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RouteViolation {
+    /// Root doesn't exist, so nothing else is checked
+    RootDoesNotExist,
+    /// Route has less than two waypoints, so nothing else is checked
+    TooFewWaypoints,
+    /// Route has more waypoints, than allowed, so nothing else is checked
+    TooManyWaypoints,
+    /// Route is over by now
+    ArrivalInPast,
+    /// Waypoint with given index is reached not later than the previous one
+    WrongArrivalTime(u32),
+    /// Waypoint with given index lies outside of the root or it's altitude bounds.
+    /// Zones are not checked then.
+    WaypointOutsideRoot(u32),
+    /// Segment with given index crosses the zone, which is active during the flight
+    ZoneCrossed(u32, ZoneId),
+    /// Segment with given index comes too close to the route with given id
+    RouteConflict(u32, RouteId),
+    /// Root is being removed, so nothing else is checked
+    RootIsBeingRemoved,
+    /// Area with given id already has the max amount of pending routes
+    TooManyRoutesInArea(AreaId),
+    /// Coordinates give indexes out of bounds, so checks, which need them, are skipped
+    CoordOverflow,
+    /// Root has zero delta, so checks, which need it's areas, are skipped
+    BadDimensions,
+    /// Coordinates are out of the root, so checks, which need them, are skipped
+    InvalidCoords,
+    /// Checks need to read more areas, than the given limit, so they are stopped
+    AreaLimitExceeded,
+}
+
+impl From<ConversionError> for RouteViolation {
+    fn from(error: ConversionError) -> Self {
+        match error {
+            ConversionError::Overflow => RouteViolation::CoordOverflow,
+            ConversionError::DivisionByZero => RouteViolation::BadDimensions,
+            ConversionError::Negative => RouteViolation::InvalidCoords,
+        }
+    }
+}
+
+/// Result of the route validation, which finds every problem instead of the first one
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[derive(Encode, Decode, Clone, Default, Debug, PartialEq, Eq)]
+pub struct RouteReport {
+    /// Areas, crossed by each segment of the route
+    pub segment_areas: Vec<Vec<AreaId>>,
+    pub violations: Vec<RouteViolation>,
+    /// Amount of areas, read by the checks. route_add needs at least such area limit.
+    pub read_areas: u32,
+}
+
+impl RouteReport {
+    /// True, if the route can be added
+    pub fn is_valid(&self) -> bool {
+        self.violations.is_empty()
+    }
+}
+
+/// Amount of areas, zones and routes, which were read while checking the route.
+/// Actual weight of the route addition depends on them. Areas are read up to the limit,
+/// and each area has a bounded amount of zones and routes, so the limit bounds the weight.
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq)]
+struct RouteScan {
+    area_limit: u32,
+    areas: u32,
+    zones: u32,
+    routes: u32,
+}
+
+impl RouteScan {
+    fn new(area_limit: u32) -> Self {
+        RouteScan { area_limit, ..Default::default() }
+    }
+
+    /// Counts areas, which are about to be read. Returns false, if they exceed the limit.
+    fn read_areas(&mut self, amount: usize) -> bool {
+        self.areas = self.areas.saturating_add(amount.saturated_into::<u32>());
+        self.areas <= self.area_limit
+    }
+}
+
+/// Waypoint's location in metres (north, east, up) from some reference point, and it's arrival time
+pub type MetricWaypoint = ([i128; 3], i128);
+pub type MetricLeg = (MetricWaypoint, MetricWaypoint);
+type MetricSegment = ([i128; 3], [i128; 3]);
+
+/// Minimal distances, which drones on different routes shall keep from each other
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Separation {
+    /// In metres
+    pub horizontal: i128,
+    /// In metres
+    pub vertical: i128,
+    /// In the same units, as waypoints' arrival time
+    pub time: i128,
+}
+
+impl Separation {
+    pub fn new(horizontal: LightCoord, vertical: LightCoord, time: u64) -> Self {
+        Separation{horizontal: horizontal as i128, vertical: vertical as i128, time: time as i128}
+    }
+
+    /// True, if drones flying given legs may come closer, than allowed. Drone is too close,
+    /// if it passes a place, where another one is, was or will be within the time separation.
+    /// Moments (t, s) of both drones, which are not further than the time separation from each
+    /// other, form a convex polygon, and the relative position a(t) - b(s) changes linearly
+    /// over it. So the closest approach is either the crossing of the legs, or lies on the border
+    /// of the polygon. Horizontal and vertical distances are minimized separately, so the check
+    /// is a bit stricter, than the real distance between drones.
+    pub fn legs_conflict(&self, a: MetricLeg, b: MetricLeg) -> bool {
+        let border = self.time_border(a, b);
+        if border.is_empty() { return false; }
+        let relative = |(t, s): (i128, i128)| -> [i128; 3] {
+            let (a_point, b_point) = (Self::position(a, t), Self::position(b, s));
+            [a_point[0] - b_point[0], a_point[1] - b_point[1], a_point[2] - b_point[2]]
+        };
+        let segments: Vec<MetricSegment> = border.iter()
+            .map(|(from, to)| (relative(*from), relative(*to)))
+            .collect();
+
+        // Altitude difference changes linearly too, so it's zero inside the polygon,
+        // if it changes sign between the vertices
+        let (lowest, highest) = segments.iter().fold((i128::MAX, i128::MIN), |(lowest, highest), (from, to)| {
+            (min(lowest, min(from[2], to[2])), max(highest, max(from[2], to[2])))
+        });
+        let vertical_gap = if lowest <= 0 && highest >= 0 { 0 } else { min(lowest.abs(), highest.abs()) };
+        if vertical_gap >= self.vertical { return false; }
+
+        let horizontal = self.horizontal * self.horizontal;
+        self.cross_in_time(a, b) ||
+            segments.into_iter().any(|segment| Self::point_distance_squared([0; 3], segment) < horizontal)
+    }
+
+    /// Border of the polygon of moments (t, s), which drones fly their legs at, and which are not
+    /// further than the time separation from each other. It's made of the sides of legs' time
+    /// windows and of the lines of the max time shift, cut to the polygon.
+    /// Segments may repeat or overlap, and none of them is given, if drones are never close in time.
+    fn time_border(&self, a: MetricLeg, b: MetricLeg) -> Vec<((i128, i128), (i128, i128))> {
+        let ((_, a_start), (_, a_end)) = a;
+        let ((_, b_start), (_, b_end)) = b;
+        let time = self.time;
+        let mut border = Vec::new();
+        for t in [a_start, a_end].iter().copied() {
+            let (from, to) = (max(b_start, t - time), min(b_end, t + time));
+            if from <= to { border.push(((t, from), (t, to))); }
+        }
+        for s in [b_start, b_end].iter().copied() {
+            let (from, to) = (max(a_start, s - time), min(a_end, s + time));
+            if from <= to { border.push(((from, s), (to, s))); }
+        }
+        // Lines s = t + shift
+        for shift in [-time, time].iter().copied() {
+            let (from, to) = (max(a_start, b_start - shift), min(a_end, b_end - shift));
+            if from <= to { border.push(((from, from + shift), (to, to + shift))); }
+        }
+        border
+    }
+
+    /// True, if legs cross each other, and drones pass the crossing within the time separation
+    fn cross_in_time(&self, a: MetricLeg, b: MetricLeg) -> bool {
+        let ((a_start, a_start_time), (a_end, a_end_time)) = a;
+        let ((b_start, b_start_time), (b_end, b_end_time)) = b;
+        if !Self::segments_cross((a_start, a_end), (b_start, b_end)) { return false; }
+        let cross = |u: [i128; 2], v: [i128; 2]| u[0] * v[1] - u[1] * v[0];
+        let a_direction = [a_end[0] - a_start[0], a_end[1] - a_start[1]];
+        let b_direction = [b_end[0] - b_start[0], b_end[1] - b_start[1]];
+        let offset = [b_start[0] - a_start[0], b_start[1] - a_start[1]];
+        // Crossing is at the fraction cross(offset, b) / cross(a, b) of the first leg,
+        // and at the fraction cross(offset, a) / cross(a, b) of the second one.
+        // Legs cross, so they are not parallel, and the denominator is not zero.
+        let denominator = cross(a_direction, b_direction);
+        let a_time = a_start_time * denominator + (a_end_time - a_start_time) * cross(offset, b_direction);
+        let b_time = b_start_time * denominator + (b_end_time - b_start_time) * cross(offset, a_direction);
+        (a_time - b_time).abs() <= self.time * denominator.abs()
+    }
+
+    /// Position of the drone on the leg at given moment inside the leg's time window
+    fn position(leg: MetricLeg, time: i128) -> [i128; 3] {
+        let ((start, start_time), (end, end_time)) = leg;
+        let duration = end_time - start_time;
+        let mut point = start;
+        if duration != 0 {
+            for (coord, (s, e)) in point.iter_mut().zip(start.iter().zip(end.iter())) {
+                *coord = s + (e - s) * (time - start_time) / duration;
+            }
+        }
+        point
+    }
+
+    /// Squared horizontal distance from point to the closest point of the segment
+    fn point_distance_squared(point: [i128; 3], segment: MetricSegment) -> i128 {
+        let (start, end) = segment;
+        let (dx, dy) = (end[0] - start[0], end[1] - start[1]);
+        let (px, py) = (point[0] - start[0], point[1] - start[1]);
+        let length_squared = dx * dx + dy * dy;
+        // Projection of the point to the segment, multiplied by segment's length
+        let projection = px * dx + py * dy;
+        if length_squared == 0 || projection <= 0 {
+            px * px + py * py
+        } else if projection >= length_squared {
+            let (qx, qy) = (point[0] - end[0], point[1] - end[1]);
+            qx * qx + qy * qy
+        } else {
+            px * px + py * py - projection * projection / length_squared
+        }
+    }
+
+    /// True, if segments cross each other. Touching is covered by the distance between ends.
+    fn segments_cross(a: MetricSegment, b: MetricSegment) -> bool {
+        let orientation = |p: [i128; 3], q: [i128; 3], r: [i128; 3]| -> i128 {
+            ((q[0] - p[0]) * (r[1] - p[1]) - (q[1] - p[1]) * (r[0] - p[0])).signum()
+        };
+        orientation(b.0, b.1, a.0) * orientation(b.0, b.1, a.1) < 0 &&
+        orientation(a.0, a.1, b.0) * orientation(a.0, a.1, b.1) < 0
+    }
+}
+
+#[cfg(test)]
+mod separation_tests {
+    use super::*;
+
+    fn separation() -> Separation {
+        Separation::new(50, 10, 60)
+    }
+
+    fn leg(start: [i128; 4], end: [i128; 4]) -> MetricLeg {
+        (([start[0], start[1], start[2]], start[3]), ([end[0], end[1], end[2]], end[3]))
+    }
+
+    #[test]
+    fn crossing_legs_at_the_same_time() {
+        let a = leg([0, -500, 100, 0], [0, 500, 100, 100]);
+        let b = leg([-500, 0, 100, 0], [500, 0, 100, 100]);
+        assert!(separation().legs_conflict(a, b));
+    }
+
+    #[test]
+    fn crossing_legs_at_different_time() {
+        let a = leg([0, -500, 100, 0], [0, 500, 100, 100]);
+        // Starts right after the first one, but passes crossing later than time separation
+        let b = leg([-500, 0, 100, 100], [500, 0, 100, 200]);
+        assert!(!separation().legs_conflict(a, b));
+        // Passes crossing within time separation
+        let b = leg([-500, 0, 100, 60], [500, 0, 100, 160]);
+        assert!(separation().legs_conflict(a, b));
+    }
+
+    #[test]
+    fn crossing_legs_at_different_altitude() {
+        let a = leg([0, -500, 100, 0], [0, 500, 100, 100]);
+        let b = leg([-500, 0, 110, 0], [500, 0, 110, 100]);
+        assert!(!separation().legs_conflict(a, b));
+        let b = leg([-500, 0, 105, 0], [500, 0, 105, 100]);
+        assert!(separation().legs_conflict(a, b));
+    }
+
+    #[test]
+    fn parallel_legs() {
+        let a = leg([0, 0, 100, 0], [0, 1000, 100, 100]);
+        let b = leg([50, 0, 100, 0], [50, 1000, 100, 100]);
+        assert!(!separation().legs_conflict(a, b));
+        let b = leg([49, 0, 100, 0], [49, 1000, 100, 100]);
+        assert!(separation().legs_conflict(a, b));
+    }
+
+    #[test]
+    fn following_leg() {
+        // Same path, but the second drone is far behind
+        let a = leg([0, 0, 100, 0], [0, 1000, 100, 100]);
+        let b = leg([0, 0, 100, 200], [0, 1000, 100, 300]);
+        assert!(!separation().legs_conflict(a, b));
+    }
+}
+
 // Actually, this is line section, so we need limits
 #[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
 #[derive(Encode, Decode, Clone, Copy, Default, Debug, PartialEq, Eq)]
@@ -247,8 +774,8 @@ pub struct Line<Coord, BigCoord> {
 }
 
 impl<
-    Coord: FromStr + Default + ToBigCoord<Output = BigCoord> + Ord + Signed + IntDiv + Mul<Output = Coord> + Sub<Output = Coord> + Add<Output = Coord> + Div<Output = Coord> + Copy,
-    BigCoord: Mul<Output = BigCoord> + Sub<Output = BigCoord> + FromBigCoord<Output = Coord> + Copy + PartialOrd + GetEpsilon
+    Coord: FromStr + Default + ToBigCoord<Output = BigCoord> + ToRaw + Ord + Signed + IntDiv + MetricConversion + Mul<Output = Coord> + Sub<Output = Coord> + Add<Output = Coord> + Div<Output = Coord> + Copy,
+    BigCoord: Mul<Output = BigCoord> + Sub<Output = BigCoord> + Add<Output = BigCoord> + Div<Output = BigCoord> + Default + FromBigCoord<Output = Coord> + Copy + PartialOrd
     > Line<Coord, BigCoord> {
     pub fn new(point_0: Point2D<Coord>, point_1: Point2D<Coord>) -> Self {
         // Form coefficients (y1 - y2)x + (x2 - x1)y + (x1y2 - x2y1) = 0
@@ -268,59 +795,52 @@ impl<
             _phantom: PhantomData
         } 
     }
-    fn coord_from_str (s: &str) -> Coord {
-        match Coord::from_str(s) {
-            Ok(v) => v,
-            Err(_) => Default::default(),
-        }
-    } 
-
-    // Realisation of Bresenham's line algorithm 
-    pub fn get_route_areas(self, root: RootBox<Coord>) -> Vec<AreaId> {
-        let start_area = root.detect_intersected_area(self.start_point);
-        let end_area = root.detect_intersected_area(self.end_point);
-        // In case everything is in one area 
-        if start_area == end_area {return vec![start_area]}
+    /// Returns every area, which the line touches, in the order the line passes them.
+    /// Areas, touched only by an edge or a corner, are included too, so no zone is missed.
+    // Supercover traversal: the line is cut by borders of area columns, and rows, which each
+    // piece touches, are found exactly, using raw bits of coordinates
+    pub fn get_route_areas(self, root: RootBox<Coord>) -> Result<Vec<AreaId>, ConversionError> {
+        let (total_rows, total_columns) = root.get_grid_size()?;
+        root.get_max_area()?;
         let mut output = Vec::new();
-        let delta = root.delta;
-        // Simplification, it's still lat/lon
-        let mut dx = self.end_point.lat - self.start_point.lat;
-        let mut dy = self.end_point.lon - self.start_point.lon;
-        let incx = dx.signum() * delta;
-        let incy = dy.signum() * delta;
-        dx = dx.abs(); 
-        dy = dy.abs();
-        let pdx: Coord;
-        let pdy: Coord;
-        let es: Coord;
-        let el: Coord;
-        let zero: Coord = Line::coord_from_str("0");
-        if dx > dy {
-            pdx = incx; pdy = zero;
-            es = dy;    el = dx;
-        } else {
-            pdx = zero;     pdy = incy;
-            es = dx;     el = dy;
-        }
-
-        let mut current_point = self.start_point;
-        output.push(root.detect_intersected_area(current_point));
-        let mut count: Coord = zero;
-        let mut err = el / Line::coord_from_str("2");
-        while count < el {
-            count = count + delta; 
-            err = err - es;
-            if err < zero {
-                err = err + el;
-                current_point.lat = current_point.lat + incx;
-                current_point.lon = current_point.lon + incy;
+        if total_rows == 0 || total_columns == 0 {
+            return Ok(output);
+        }
+        let origin = root.bounding_box.south_west;
+        let delta = root.delta.to_raw() as i128;
+        // Coordinates are taken relative to the root's south-west corner
+        let lat_0 = self.start_point.lat.to_raw() as i128 - origin.lat.to_raw() as i128;
+        let lon_0 = self.start_point.lon.to_raw() as i128 - origin.lon.to_raw() as i128;
+        let d_lat = self.end_point.lat.to_raw() as i128 - origin.lat.to_raw() as i128 - lat_0;
+        let d_lon = self.end_point.lon.to_raw() as i128 - origin.lon.to_raw() as i128 - lon_0;
+
+        let (min_lon, max_lon) = (min(lon_0, lon_0 + d_lon), max(lon_0, lon_0 + d_lon));
+        let first_column = max(ceil_div(min_lon, delta) - 1, 0);
+        let last_column = min(floor_div(max_lon, delta), total_columns as i128 - 1);
+        let mut columns: Vec<i128> = (first_column..=last_column).collect();
+        if d_lon < 0 { columns.reverse(); }
+        for column in columns {
+            // Piece of the line, which lies in the column, including it's borders
+            let piece_start = max(min_lon, column * delta);
+            let piece_end = min(max_lon, (column + 1) * delta);
+            // Latitudes of the piece are multiplied by |d_lon| to stay integer
+            let (low, high, scale) = if d_lon == 0 {
+                (min(lat_0, lat_0 + d_lat), max(lat_0, lat_0 + d_lat), 1)
             } else {
-                current_point.lat = current_point.lat + pdx;
-                current_point.lon = current_point.lon + pdy;
+                let lat_at = |lon: i128| (lat_0 * d_lon + (lon - lon_0) * d_lat) * d_lon.signum();
+                let (start, end) = (lat_at(piece_start), lat_at(piece_end));
+                (min(start, end), max(start, end), d_lon.abs())
+            };
+            let first_row = max(ceil_div(low, scale * delta) - 1, 0);
+            let last_row = min(floor_div(high, scale * delta), total_rows as i128 - 1);
+            let mut rows: Vec<i128> = (first_row..=last_row).collect();
+            if d_lat < 0 { rows.reverse(); }
+            for row in rows {
+                // Areas are numbered from 1, column by column
+                output.push((total_rows as i128 * column + row + 1) as AreaId);
             }
-            output.push(root.detect_intersected_area(current_point));
         }
-        output
+        Ok(output)
     }
 
     // Basically we split rect to 4 lines(maybe 2 is enough?), and check each one for intersection
@@ -341,40 +861,76 @@ impl<
         }
         false
     }
-    // This method allows to proof intersection, but can't find intersection points
-    pub fn is_lines_cross(&self, line: Line<Coord, BigCoord>) -> bool {
-        // Checking if the segments are collinear
-        Line::projection_intersect(self.start_point.lat, self.end_point.lat, line.start_point.lat, line.end_point.lat) &&
-        Line::projection_intersect(self.start_point.lon, self.end_point.lon, line.start_point.lon, line.end_point.lon) &&
-        // Check, that points of AB lies on different sides of CD, and vice versa
-        // TODO basically we need only signs and zero-condition, consider refactor
-        ((Line::area(self.start_point, self.end_point, line.start_point) * 
-        Line::area(self.start_point, self.end_point, line.end_point)) <= BigCoord::get_epsilon()) &&
-        ((Line::area(line.start_point, line.end_point, self.start_point) * 
-        Line::area(line.start_point, line.end_point, self.end_point)) <= BigCoord::get_epsilon())
-    }
-
-    // Checks for projections not to intersect
-    fn projection_intersect(_a: Coord, _b: Coord, _c: Coord, _d: Coord) -> bool {
-        let (mut a, mut b, mut c, mut d) = (_a, _b, _c, _d);
-        if a > b { swap(&mut a, &mut b) }
-        if c > d { swap(&mut c, &mut d) }
-        
-        max(a,c) <= min(b,d)
+    /// True if the line crosses any of polygon's edges, or lies inside the polygon
+    pub fn intersects_polygon(&self, vertices: &[Point2D<Coord>]) -> bool {
+        let edges = vertices.iter().zip(vertices.iter().cycle().skip(1));
+        for (a, b) in edges {
+            if self.is_lines_cross(Line::new(*a, *b)) { return true; }
+        }
+        // No edges are crossed, so the line is either fully inside, or fully outside
+        Line::<Coord, BigCoord>::is_point_inside_polygon(self.start_point, vertices)
     }
-    
-    // Yea, this fn hurts
-    // Signed area of a triangle, oriented clockwise (same as vector multiplication)
-    fn area(a: Point2D<Coord>, b: Point2D<Coord>, c: Point2D<Coord>) -> BigCoord {
-        (b.lat.try_into() - a.lat.try_into()) * (c.lon.try_into() - a.lon.try_into()) - 
-        (b.lon.try_into() - a.lon.try_into()) * (c.lat.try_into() - a.lat.try_into())
+
+    /// Checks if point lies inside polygon by even-odd rule.
+    /// Points, lying exactly on edges, may be treated either way, but always the same.
+    pub fn is_point_inside_polygon(point: Point2D<Coord>, vertices: &[Point2D<Coord>]) -> bool {
+        let vertices: Vec<RawPoint> = vertices.iter().map(|vertex| vertex.to_raw()).collect();
+        is_inside_polygon(point.to_raw(), &vertices)
+    }
+
+    /// True if any point of the line lies not further than radius (in metres) from the center
+    pub fn intersects_circle(&self, center: Point2D<Coord>, radius: LightCoord) -> bool {
+        // Points are projected to the plane, touching Earth in the center, with metres as units
+        let to_metres = |point: Point2D<Coord>| -> (i128, i128) {
+            ((point.lat - center.lat).lat_degrees_to_metres() as i128,
+             (point.lon - center.lon).lon_degrees_to_metres(center.lat) as i128)
+        };
+        let (start_x, start_y) = to_metres(self.start_point);
+        let (end_x, end_y) = to_metres(self.end_point);
+        let (dx, dy) = (end_x - start_x, end_y - start_y);
+
+        let start_squared = start_x * start_x + start_y * start_y;
+        let length_squared = dx * dx + dy * dy;
+        // Projection of the center to the line, multiplied by line's length squared
+        let projection = -(start_x * dx + start_y * dy);
+        let distance_squared = if length_squared == 0 || projection <= 0 {
+            start_squared
+        } else if projection >= length_squared {
+            end_x * end_x + end_y * end_y
+        } else {
+            start_squared - projection * projection / length_squared
+        };
+        distance_squared <= (radius as i128) * (radius as i128)
+    }
+
+    /// Returns parameters (0 is start point, 1 is end point) of points, where the line
+    /// enters and leaves the rect. None, if line doesn't pass over the rect.
+    pub fn get_rect_crossing(&self, rect: Rect2D<Coord>) -> Option<(Fraction, Fraction)> {
+        segment_rect_crossing(self.start_point.to_raw(), self.end_point.to_raw(),
+                              rect.south_west.to_raw(), rect.north_east.to_raw())
+    }
+
+    /// True, if lines have at least one common point, touching included
+    pub fn is_lines_cross(&self, line: Line<Coord, BigCoord>) -> bool {
+        segments_intersect(self.start_point.to_raw(), self.end_point.to_raw(),
+                           line.start_point.to_raw(), line.end_point.to_raw())
     }
 }
 
+/// Largest integer, which is not greater than a / b. Divisor must be positive.
+fn floor_div(a: i128, b: i128) -> i128 {
+    a.div_euclid(b)
+}
+
+/// Smallest integer, which is not less than a / b. Divisor must be positive.
+fn ceil_div(a: i128, b: i128) -> i128 {
+    -(-a).div_euclid(b)
+}
+
 #[cfg(test)]
 mod line_tests {
     use super::*;
-    use crate::tests::{construct_custom_box, construct_testing_rect, coord, Coord};
+    use crate::tests::{construct_custom_box, construct_custom_rect, construct_testing_rect, coord, Coord, BigCoord};
     // TODO draw rect in ascii as an illustration
     // +---+
     // |   |
@@ -392,8 +948,36 @@ mod line_tests {
             let second_point = Point2D::new(coord("2.5"),
                                             coord("2.5"));
             let line = Line::new(first_point, second_point);
-            let areas = line.get_route_areas(root);
-            assert_eq!(areas, vec![1, 6, 11]);
+            let areas = line.get_route_areas(root).unwrap();
+            // Line passes through corners, so areas, which it touches there, are included
+            assert_eq!(areas, vec![1, 2, 5, 6, 7, 10, 11]);
+        }
+
+        #[test]
+        fn get_route_areas_clipping_corner() {
+            let rect = construct_custom_box("0", "0", "4", "4");
+            let root = RootBox::new(1, rect, coord("1"));
+            // Line passes area 5 near it's corner, before going up to area 6
+            let first_point = Point2D::new(coord("0.5"),
+                coord("0.2"));
+            let second_point = Point2D::new(coord("1.5"),
+                coord("1.9"));
+            let line = Line::new(first_point, second_point);
+            let areas = line.get_route_areas(root).unwrap();
+            assert_eq!(areas, vec![1, 5, 6]);
+        }
+
+        #[test]
+        fn get_route_areas_backwards() {
+            let rect = construct_custom_box("0", "0", "4", "4");
+            let root = RootBox::new(1, rect, coord("1"));
+            let first_point = Point2D::new(coord("1.5"),
+                coord("1.9"));
+            let second_point = Point2D::new(coord("0.5"),
+                coord("0.2"));
+            let line = Line::new(first_point, second_point);
+            let areas = line.get_route_areas(root).unwrap();
+            assert_eq!(areas, vec![6, 5, 1]);
         }
 
         #[test]
@@ -405,7 +989,7 @@ mod line_tests {
             let second_point = Point2D::new(coord("3.1"),
                 coord("0.2"));
             let line = Line::new(first_point, second_point);
-            let areas = line.get_route_areas(root);
+            let areas = line.get_route_areas(root).unwrap();
             assert_eq!(areas, vec![1, 2, 3, 4]);
         }
 
@@ -418,8 +1002,8 @@ mod line_tests {
             let second_point = Point2D::new(coord("0.31"),
                 coord("0.02"));
             let line = Line::new(first_point, second_point);
-            let areas = line.get_route_areas(root);
-            assert_eq!(areas, vec![1, 2, 3, 4, 5]);
+            let areas = line.get_route_areas(root).unwrap();
+            assert_eq!(areas, vec![1, 2, 3, 4]);
         }
 
         #[test]
@@ -431,9 +1015,73 @@ mod line_tests {
             let second_point = Point2D::new(coord("0.3"),
                 coord("0.2"));
             let line = Line::new(first_point, second_point);
-            let areas = line.get_route_areas(root);
+            let areas = line.get_route_areas(root).unwrap();
             assert_eq!(areas, vec![1]);
         }
+
+        /// Areas, which the line touches, found by checking every area of the root
+        fn touched_areas(line: Line<Coord, BigCoord>, root: RootBox<Coord>) -> Vec<AreaId> {
+            let (rows, columns) = root.get_grid_size().unwrap();
+            let mut areas = Vec::new();
+            for column in 0..columns {
+                for row in 0..rows {
+                    let south_west = Point2D::new(root.delta * row as i32, root.delta * column as i32);
+                    let north_east = Point2D::new(root.delta * (row + 1) as i32, root.delta * (column + 1) as i32);
+                    let area = Rect2D::new(south_west, north_east);
+                    if line.intersects_rect(area) || area.is_point_inside(line.start_point) {
+                        areas.push(rows * column + row + 1);
+                    }
+                }
+            }
+            areas
+        }
+
+        /// Coordinates are multiples of 1/8, so lines often pass through corners and along
+        /// borders of areas
+        fn random_point(seed: &mut u64, max: u64) -> Point2D<Coord> {
+            let mut next = || {
+                // xorshift64
+                *seed ^= *seed << 13;
+                *seed ^= *seed >> 7;
+                *seed ^= *seed << 17;
+                Coord::from_bits(((*seed % (max * 8 - 1)) as i32 + 1) << 19)
+            };
+            Point2D::new(next(), next())
+        }
+
+        #[test]
+        fn get_route_areas_matches_brute_force() {
+            let mut seed = 0x2545_f491_4f6c_dd1d;
+            for delta in ["1", "0.5"].iter() {
+                let root = RootBox::new(1, construct_custom_box("0", "0", "8", "8"), coord(delta));
+                for _ in 0..500 {
+                    let line = Line::new(random_point(&mut seed, 8), random_point(&mut seed, 8));
+                    let mut areas = line.get_route_areas(root).unwrap();
+                    areas.sort();
+                    assert_eq!(areas, touched_areas(line, root),
+                               "line {:?} - {:?}", line.start_point, line.end_point);
+                }
+            }
+        }
+
+        #[test]
+        fn get_route_areas_along_borders() {
+            let root = RootBox::new(1, construct_custom_box("0", "0", "4", "4"), coord("1"));
+            let lines = [
+                (("1", "0.5"), ("1", "2.5")),
+                (("0.5", "2"), ("3.5", "2")),
+                (("1", "1"), ("3", "3")),
+                (("3", "1"), ("1", "3")),
+                (("2", "2"), ("2", "2")),
+            ];
+            for ((start_lat, start_lon), (end_lat, end_lon)) in lines.iter() {
+                let line = Line::new(Point2D::new(coord(start_lat), coord(start_lon)),
+                                     Point2D::new(coord(end_lat), coord(end_lon)));
+                let mut areas = line.get_route_areas(root).unwrap();
+                areas.sort();
+                assert_eq!(areas, touched_areas(line, root));
+            }
+        }
     }
 
     // In this section we try crossing different lines
@@ -548,6 +1196,87 @@ mod line_tests {
             assert!(!line.intersects_rect(rect));
         }
 
+        #[test]
+        fn line_crosses_rect_in_the_middle() {
+            let rect = construct_custom_rect("1", "1", "2", "2");
+            let line = Line::new(Point2D::new(coord("0"), coord("1.5")),
+                                 Point2D::new(coord("3"), coord("1.5")));
+            let (enter, leave) = line.get_rect_crossing(rect).unwrap();
+            assert_eq!(enter, Fraction::new(1, 3));
+            assert_eq!(leave, Fraction::new(2, 3));
+        }
+
+        #[test]
+        fn line_starts_inside_rect() {
+            let rect = construct_custom_rect("1", "1", "2", "2");
+            let line = Line::new(Point2D::new(coord("1.5"), coord("1.5")),
+                                 Point2D::new(coord("1.5"), coord("3.5")));
+            let (enter, leave) = line.get_rect_crossing(rect).unwrap();
+            assert_eq!(enter, Fraction::new(0, 1));
+            assert_eq!(leave, Fraction::new(1, 4));
+        }
+
+        #[test]
+        fn line_misses_rect() {
+            let rect = construct_custom_rect("1", "1", "2", "2");
+            let line = Line::<Coord, BigCoord>::new(Point2D::new(coord("0"), coord("3")),
+                                                    Point2D::new(coord("3"), coord("3")));
+            assert_eq!(line.get_rect_crossing(rect), None);
+        }
+
+        #[test]
+        fn point_inside_polygon() {
+            // Triangle (0, 0), (4, 0), (0, 4)
+            let triangle: Vec<Point2D<Coord>> = vec![
+                Point2D::new(coord("0"), coord("0")),
+                Point2D::new(coord("4"), coord("0")),
+                Point2D::new(coord("0"), coord("4")),
+            ];
+            let inside = Point2D::new(coord("1"), coord("1"));
+            let outside = Point2D::new(coord("3"), coord("3"));
+            assert!(Line::<Coord, BigCoord>::is_point_inside_polygon(inside, &triangle));
+            assert!(!Line::<Coord, BigCoord>::is_point_inside_polygon(outside, &triangle));
+        }
+
+        #[test]
+        fn line_crosses_polygon() {
+            let triangle: Vec<Point2D<Coord>> = vec![
+                Point2D::new(coord("0"), coord("0")),
+                Point2D::new(coord("4"), coord("0")),
+                Point2D::new(coord("0"), coord("4")),
+            ];
+            // Goes through the hypotenuse
+            let crossing = Line::new(Point2D::new(coord("1"), coord("1")),
+                                     Point2D::new(coord("5"), coord("5")));
+            assert!(crossing.intersects_polygon(&triangle));
+            // Fully inside
+            let inner = Line::new(Point2D::new(coord("0.5"), coord("0.5")),
+                                  Point2D::new(coord("1"), coord("1.5")));
+            assert!(inner.intersects_polygon(&triangle));
+            // Fully outside, but inside of the bounding rect
+            let outer = Line::new(Point2D::new(coord("3.5"), coord("3")),
+                                  Point2D::new(coord("3"), coord("3.5")));
+            assert!(!outer.intersects_polygon(&triangle));
+        }
+
+        #[test]
+        fn line_crosses_circle() {
+            let center: Point2D<Coord> = Point2D::new(coord("10"), coord("10"));
+            // ~556 metres to the north from the center
+            let close_line = Line::new(Point2D::new(coord("10.005"), coord("9.9")),
+                                       Point2D::new(coord("10.005"), coord("10.1")));
+            assert!(close_line.intersects_circle(center, 1000));
+            assert!(!close_line.intersects_circle(center, 500));
+            // ~1112 metres to the north from the center, and ends before it
+            let far_line = Line::new(Point2D::new(coord("10.01"), coord("9.9")),
+                                     Point2D::new(coord("10.01"), coord("9.999")));
+            assert!(!far_line.intersects_circle(center, 1000));
+            // Line ends inside of the circle
+            let inner_line = Line::new(Point2D::new(coord("9.9"), coord("10")),
+                                       Point2D::new(coord("9.995"), coord("10")));
+            assert!(inner_line.intersects_circle(center, 1000));
+        }
+
         #[test]
         fn line_rect_gps_coords() {
             let first_point = Point2D::new(coord("55.392"),
@@ -603,29 +1332,87 @@ impl<
         indexes
     }
 
-    /// Returns maximum area index of given root. Max is 65536.
-    pub fn get_max_area(self) -> AreaId {
+    /// Returns maximum area index of given root. Fails, if root has more than 65535 areas.
+    pub fn get_max_area(self) -> Result<AreaId, ConversionError> {
+        let (total_rows, total_columns) = self.get_grid_size()?;
+
+        total_rows.checked_mul(total_columns).ok_or(ConversionError::Overflow)
+    }
+
+    /// Returns amount of rows and columns of areas in the root
+    pub fn get_grid_size(self) -> Result<(u16, u16), ConversionError> {
         let root_dimensions = self.bounding_box.projection_on_plane().get_dimensions();
-        let total_rows = root_dimensions.lat.integer_division_u16(self.delta);
-        let total_columns = root_dimensions.lon.integer_division_u16(self.delta);
+        let total_rows = root_dimensions.lat.checked_integer_division_u16(self.delta)?;
+        let total_columns = root_dimensions.lon.checked_integer_division_u16(self.delta)?;
+
+        Ok((total_rows, total_columns))
+    }
+
+    /// True, if given altitude lies between the root's floor and ceiling, including edges
+    pub fn is_altitude_inside(&self, alt: Coord) -> bool {
+        self.bounding_box.south_west.alt <= alt && alt <= self.bounding_box.north_east.alt
+    }
+
+    /// Returns ids of all areas, which the rect overlaps. Empty, if the rect doesn't fit into root.
+    pub fn get_rect_areas(self, rect: Rect2D<Coord>) -> Result<Vec<AreaId>, ConversionError> {
+        let south_west_area = self.detect_intersected_area(rect.south_west)?;
+        let north_east_area = self.detect_intersected_area(rect.north_east)?;
+        if south_west_area == 0 || north_east_area == 0 {
+            return Ok(Vec::new());
+        }
+        let (total_rows, _) = self.get_grid_size()?;
+        // Areas are numbered from 1, column by column
+        let (sw_row, sw_column) = ((south_west_area - 1) % total_rows, (south_west_area - 1) / total_rows);
+        let (ne_row, ne_column) = ((north_east_area - 1) % total_rows, (north_east_area - 1) / total_rows);
+        let mut areas = Vec::new();
+        for column in sw_column..=ne_column {
+            for row in sw_row..=ne_row {
+                areas.push(total_rows * column + row + 1);
+            }
+        }
+        Ok(areas)
+    }
 
-        total_rows * total_columns
+    /// Returns ids of all areas, which are not farther than given amount of rows and columns
+    /// from any of given areas, including these areas. Ids are sorted and don't repeat.
+    pub fn get_surrounding_areas(self, areas: &[AreaId], rows: u16, columns: u16) -> Result<Vec<AreaId>, ConversionError> {
+        let (total_rows, total_columns) = self.get_grid_size()?;
+        let max_area = total_rows.checked_mul(total_columns).ok_or(ConversionError::Overflow)?;
+        let mut surrounding = Vec::new();
+        for area_id in areas.iter().filter(|area_id| **area_id != 0 && **area_id <= max_area) {
+            let (row, column) = ((area_id - 1) % total_rows, (area_id - 1) / total_rows);
+            let last_column = column.saturating_add(columns).min(total_columns - 1);
+            let last_row = row.saturating_add(rows).min(total_rows - 1);
+            for column in column.saturating_sub(columns)..=last_column {
+                for row in row.saturating_sub(rows)..=last_row {
+                    surrounding.push(total_rows * column + row + 1);
+                }
+            }
+        }
+        surrounding.sort_unstable();
+        surrounding.dedup();
+        Ok(surrounding)
     }
 
-    /// Returns id of an area in root, in which supplied point is located
-    fn detect_intersected_area(self, touch: Point2D<Coord>) -> AreaId {
+    /// Returns id of an area in root, in which supplied point is located, or 0 for points outside.
+    /// Margins of the root, which are narrower than delta, belong to no area.
+    fn detect_intersected_area(self, touch: Point2D<Coord>) -> Result<AreaId, ConversionError> {
         let root_projection = self.bounding_box.projection_on_plane();
         if !root_projection.is_point_inside(touch) {
-            return 0;
+            return Ok(0);
         }
-        let root_dimensions = root_projection.get_dimensions();
         let touch_vector = root_projection.south_west.get_distance_vector(touch);
         
-        let row = touch_vector.lat.integer_division_u16(self.delta) + 1;
-        let column = touch_vector.lon.integer_division_u16(self.delta) + 1;
-        let total_rows = root_dimensions.lat.integer_division_u16(self.delta);
+        let row = touch_vector.lat.checked_integer_division_u16(self.delta)?;
+        let column = touch_vector.lon.checked_integer_division_u16(self.delta)?;
+        let (total_rows, total_columns) = self.get_grid_size()?;
+        if row >= total_rows || column >= total_columns {
+            return Ok(0);
+        }
+        // Ids of all areas must fit into AreaId, not only the id of this one
+        total_rows.checked_mul(total_columns).ok_or(ConversionError::Overflow)?;
 
-        (total_rows * (column - 1)) + row
+        Ok(total_rows * column + row + 1)
     }
 
     #[cfg(test)]
@@ -637,27 +1424,27 @@ impl<
 #[cfg(test)]
 mod rootbox_tests {
     use super::*;
-    use crate::tests::{construct_custom_box, ROOT_ID, coord, Coord};
+    use crate::tests::{construct_custom_box, construct_custom_rect, ROOT_ID, coord, Coord};
 
     #[test]
     fn max_area_small_root() {
         let bbox = construct_custom_box("0", "0", "2", "3");
         let root = RootBox::new(ROOT_ID, bbox, coord("1"));
-        assert_eq!(root.get_max_area(), 6);
+        assert_eq!(root.get_max_area().unwrap(), 6);
     }
 
     #[test]
     fn max_area_frac_delta() {
         let bbox = construct_custom_box("-0", "0", "2", "3");
         let root = RootBox::new(ROOT_ID, bbox, coord("0.5"));
-        assert_eq!(root.get_max_area(), 24);
+        assert_eq!(root.get_max_area().unwrap(), 24);
     }
 
     #[test]
     fn max_area_big_root() {
         let bbox = construct_custom_box("-90", "-180", "0", "0");
         let root = RootBox::new(ROOT_ID, bbox, coord("1"));
-        assert_eq!(root.get_max_area(), 16_200);
+        assert_eq!(root.get_max_area().unwrap(), 16_200);
     }
 
     #[test]
@@ -667,35 +1454,71 @@ mod rootbox_tests {
 
         let point = Point2D::new(coord("0.5"),
                                  coord("0.5"));
-        assert_eq!(root.detect_intersected_area(point), 1);
+        assert_eq!(root.detect_intersected_area(point).unwrap(), 1);
 
         let point = Point2D::new(coord("1.5"),
                                  coord("1.5"));
-        assert_eq!(root.detect_intersected_area(point), 4);
+        assert_eq!(root.detect_intersected_area(point).unwrap(), 4);
 
         let edge_point = Point2D::new(coord("2"),
                                       coord("3"));
-        assert_eq!(root.detect_intersected_area(edge_point), 0); 
+        assert_eq!(root.detect_intersected_area(edge_point).unwrap(), 0); 
 
         let inner_mid_point = Point2D::new(coord("1"),
                                             coord("1"));
-        assert_eq!(root.detect_intersected_area(inner_mid_point), 4); 
+        assert_eq!(root.detect_intersected_area(inner_mid_point).unwrap(), 4); 
 
         let inner_edge_point = Point2D::new(coord("1"),
                                             coord("0.5"));
-        assert_eq!(root.detect_intersected_area(inner_edge_point), 2); 
+        assert_eq!(root.detect_intersected_area(inner_edge_point).unwrap(), 2); 
 
         let out_point = Point2D::new(coord("50"),
                                      coord("50"));
-        assert_eq!(root.detect_intersected_area(out_point), 0);
+        assert_eq!(root.detect_intersected_area(out_point).unwrap(), 0);
     }
 
     #[test]
-    fn extract_values_from_rootbox_index() {
-        let rootbox_sw_cell_row: u64 = 0b0000_0000_0000_0101;
-        let rootbox_sw_cell_column: u64 = 0b0000_0000_0000_1010;
-        let rootbox_ne_cell_row: u64 = 0b0000_0000_0000_1111;
-        let rootbox_ne_cell_column: u64 = 0b0000_0000_0000_0010;
+    fn rect_areas_detect_correct() {
+        let bbox = construct_custom_box("0", "0", "2", "3");
+        let root = RootBox::new(100, bbox, coord("1"));
+        // Areas are numbered column by column:
+        // 2 4 6
+        // 1 3 5
+        let rect = construct_custom_rect("0.5", "0.5", "0.7", "0.7");
+        assert_eq!(root.get_rect_areas(rect).unwrap(), vec![1]);
+
+        let rect = construct_custom_rect("0.5", "0.5", "1.5", "1.5");
+        assert_eq!(root.get_rect_areas(rect).unwrap(), vec![1, 2, 3, 4]);
+
+        let rect = construct_custom_rect("1.5", "0.5", "1.7", "2.5");
+        assert_eq!(root.get_rect_areas(rect).unwrap(), vec![2, 4, 6]);
+
+        let rect = construct_custom_rect("0.5", "0.5", "1.5", "3.5");
+        assert!(root.get_rect_areas(rect).unwrap().is_empty());
+    }
+
+    #[test]
+    fn surrounding_areas_detect_correct() {
+        let bbox = construct_custom_box("0", "0", "3", "3");
+        let root = RootBox::new(100, bbox, coord("1"));
+        // 3 6 9
+        // 2 5 8
+        // 1 4 7
+        assert_eq!(root.get_surrounding_areas(&[5], 0, 0).unwrap(), vec![5]);
+        assert_eq!(root.get_surrounding_areas(&[5], 1, 1).unwrap(), vec![1, 2, 3, 4, 5, 6, 7, 8, 9]);
+        assert_eq!(root.get_surrounding_areas(&[1], 1, 0).unwrap(), vec![1, 2]);
+        assert_eq!(root.get_surrounding_areas(&[1, 9], 0, 1).unwrap(), vec![1, 4, 6, 9]);
+        assert_eq!(root.get_surrounding_areas(&[3], 5, 5).unwrap(), vec![1, 2, 3, 4, 5, 6, 7, 8, 9]);
+        // Areas out of the root are ignored
+        assert!(root.get_surrounding_areas(&[0, 10], 1, 1).unwrap().is_empty());
+    }
+
+    #[test]
+    fn extract_values_from_rootbox_index() {
+        let rootbox_sw_cell_row: u64 = 0b0000_0000_0000_0101;
+        let rootbox_sw_cell_column: u64 = 0b0000_0000_0000_1010;
+        let rootbox_ne_cell_row: u64 = 0b0000_0000_0000_1111;
+        let rootbox_ne_cell_column: u64 = 0b0000_0000_0000_0010;
 
         let rootbox_index = rootbox_sw_cell_row << 48 | rootbox_sw_cell_column << 32 |
             rootbox_ne_cell_row << 16 | rootbox_ne_cell_column;
@@ -818,31 +1641,115 @@ impl Area {
     } 
 }
 
+/// Cells of one page row, which belong to the same root. Row and columns are counted
+/// inside the page, end column is excluded.
 #[derive(Encode, Decode, Debug, Clone, Copy, PartialEq, Eq)]
-pub struct Page<Coord> {
-    pub bitmap: [[RootId; PAGE_WIDTH as usize]; PAGE_LENGTH as usize],
-    _phantom: PhantomData<Coord>,
+pub struct CellRun {
+    pub row: u8,
+    pub first_column: u8,
+    pub end_column: u8,
+    pub root_id: RootId,
+}
+
+/// Page of the bitmap, which cells are laid out by the Grid
+#[derive(Encode, Decode, Debug, Clone, PartialEq, Eq)]
+pub struct Page<Coord, G> {
+    /// Cells, occupied by roots, sorted by row and column. Free cells are not stored.
+    pub runs: Vec<CellRun>,
+    #[codec(skip)]
+    _phantom: PhantomData<(Coord, G)>,
+}
+
+impl<Coord, G> Page<Coord, G> {
+    /// Returns root, which owns the cell, or 0 if the cell is free.
+    /// Row and column are counted inside the page.
+    pub fn get_cell(&self, row: u32, column: u32) -> RootId {
+        let (row, column) = (row as u8, column as u8);
+        match self.runs.binary_search_by(|run| (run.row, run.first_column).cmp(&(row, column))) {
+            Ok(index) => self.runs[index].root_id,
+            Err(0) => 0,
+            // Only the last run, which starts before the cell, may contain it
+            Err(index) => {
+                let run = self.runs[index - 1];
+                if run.row == row && column < run.end_column { run.root_id } else { 0 }
+            }
+        }
+    }
+
+    /// True, if no cell of the row from first to end column is occupied
+    pub fn is_free(&self, row: u32, first_column: u32, end_column: u32) -> bool {
+        let (row, first_column, end_column) = (row as u8, first_column as u8, end_column as u8);
+        !self.runs.iter().any(|run| {
+            run.row == row && run.first_column < end_column && first_column < run.end_column
+        })
+    }
+
+    /// Gives cells of the row from first to end column to the root. Zero root frees them.
+    pub fn set_cells(&mut self, row: u32, first_column: u32, end_column: u32, root_id: RootId) {
+        let (row, first_column, end_column) = (row as u8, first_column as u8, end_column as u8);
+        let mut runs: Vec<CellRun> = Vec::with_capacity(self.runs.len() + 2);
+        for run in self.runs.drain(..) {
+            if run.row != row || run.end_column <= first_column || end_column <= run.first_column {
+                runs.push(run);
+                continue;
+            }
+            // Parts of the run outside of the range are kept
+            if run.first_column < first_column {
+                runs.push(CellRun {end_column: first_column, ..run});
+            }
+            if end_column < run.end_column {
+                runs.push(CellRun {first_column: end_column, ..run});
+            }
+        }
+        if root_id != 0 && first_column < end_column {
+            runs.push(CellRun {row, first_column, end_column, root_id});
+        }
+        runs.sort_unstable_by_key(|run| (run.row, run.first_column));
+
+        // Neighbour runs of the same root are joined, so every page has only one layout
+        for run in runs {
+            match self.runs.last_mut() {
+                Some(last) if last.row == run.row && last.end_column == run.first_column &&
+                              last.root_id == run.root_id => last.end_column = run.end_column,
+                _ => self.runs.push(run),
+            }
+        }
+    }
 }
 
 impl<
     Coord: Default
     + FromStr
     + Copy
-    + CastToType
-> Page<Coord> {
-    fn new() -> Self {
-        Page {
-            bitmap: [[0u64; PAGE_WIDTH as usize]; PAGE_LENGTH as usize],
-            _phantom: PhantomData,
-        }
+    + PartialOrd
+    + Add<Output = Coord>
+    + Sub<Output = Coord>
+    + FromU32
+    + CastToType,
+    G: Grid,
+> Page<Coord, G> {
+    /// Checks, if point can be indexed. Longitude may exceed 180 degrees, to describe roots,
+    /// which cross the antimeridian.
+    pub fn is_on_globe(point: Point3D<Coord>) -> bool {
+        let right_angle = Coord::from_u32(90);
+        let straight_angle = Coord::from_u32(180);
+        let full_angle = Coord::from_u32(360);
+        let zero = Coord::default();
+        point.lat >= zero - right_angle && point.lat <= right_angle &&
+            point.lon >= zero - straight_angle && point.lon < full_angle
     }
 
     /// Calculates the number of pages to extract from the storage using the coordinates
-    pub fn get_amount_of_pages_to_extract_using_box(bounding_box: Box3D<Coord>) -> u32 {
-        let (sw_row_index, sw_column_index) = Self::get_cell_indexes(bounding_box.south_west);
-        let (ne_row_index, ne_column_index) = Self::get_cell_indexes(bounding_box.north_east);
-
-        Self::get_amount_of_pages_to_extract(sw_row_index, sw_column_index, ne_row_index, ne_column_index)
+    pub fn get_amount_of_pages_to_extract_using_box(bounding_box: Box3D<Coord>) -> Result<u32, ConversionError> {
+        let (sw_row_index, sw_column_index) = Self::get_cell_indexes(bounding_box.south_west)?;
+        let (ne_row_index, ne_column_index) = Self::get_cell_indexes(bounding_box.north_east)?;
+
+        Ok(Self::get_column_ranges(sw_column_index, ne_column_index)
+            .into_iter()
+            .map(|(first_column, last_column)| Self::get_amount_of_pages_to_extract(
+                sw_row_index, first_column, ne_row_index, last_column
+            ))
+            .sum())
     }
 
     /// Calculates the number of pages to extract from the storage using the indexes
@@ -850,137 +1757,50 @@ impl<
         sw_row_index: u32, sw_column_index: u32,
         ne_row_index: u32, ne_column_index: u32,
     ) -> u32 {
+        if ne_row_index < sw_row_index || ne_column_index < sw_column_index {
+            return 0;
+        }
         let sw_cell_page_index = Self::get_index(sw_row_index, sw_column_index);
         let ne_cell_page_index = Self::get_index(ne_row_index, ne_column_index);
 
-        if sw_cell_page_index == ne_cell_page_index {
-            let amount_of_pages: u32 = 1;
-            return amount_of_pages;
-        }
-
         let (sw_page_row_index, sw_page_column_index) = Self::extract_values_from_page_index(sw_cell_page_index);
         let (ne_page_row_index, ne_page_column_index) = Self::extract_values_from_page_index(ne_cell_page_index);
 
-        if ne_page_row_index < sw_page_row_index || ne_page_column_index < sw_page_column_index {
-            return 0;
-        }
-
-        let mut offset: u32 = 1;
-
-        if ne_page_row_index == sw_page_row_index {
-            if sw_column_index / PAGE_WIDTH == 0 {
-                return ne_page_column_index / PAGE_WIDTH;
-            }
-            return ne_page_column_index / PAGE_WIDTH - sw_page_column_index / PAGE_WIDTH + offset;
-        }
-
-        if sw_page_column_index == ne_page_column_index {
-            if sw_row_index / PAGE_LENGTH == 0 {
-                return ne_page_row_index / PAGE_LENGTH;
-            }
-            return ne_page_row_index / PAGE_LENGTH - sw_page_row_index / PAGE_LENGTH + offset;
-        }
-
-        if sw_row_index / PAGE_LENGTH == 0 && sw_column_index / PAGE_WIDTH == 0 {
-            offset = 2;
-        }
-
-        (ne_page_row_index / PAGE_LENGTH - sw_page_row_index / PAGE_LENGTH) +
-            (ne_page_column_index / PAGE_WIDTH - sw_page_column_index / PAGE_WIDTH)
-            + offset
+        ((ne_page_row_index - sw_page_row_index) / G::page_length() + 1) *
+            ((ne_page_column_index - sw_page_column_index) / G::page_width() + 1)
     }
 
-    /// Gets the indexes of the pages to be extracted
-    pub fn get_pages_indexes_to_be_extracted(
-        amount_of_pages_to_extract: u32,
-        sw_cell_row_index: u32, sw_cell_column_index: u32,
-        sw_page_index: u32, ne_page_index: u32,
-    ) -> Vec<PageId> {
-        let mut page_indexes: Vec<PageId> = vec![sw_page_index];
-
-        // Pages's bypass direction
-        let right = 1;
-        let up = 2;
-        let left = 3;
-        let down = 4;
-        let mut direction = right;
-
-        let mut current_cell_row_index: u32 = sw_cell_row_index;
-        let mut current_cell_column_index: u32 = sw_cell_column_index;
-
-        let first_page_index: PageId = sw_page_index;
-        let (first_cell_row_index, first_cell_column_index) = Self::extract_values_from_page_index(first_page_index);
-        let last_page_index: PageId = ne_page_index;
-        let (last_cell_row_index, last_cell_column_index) = Self::extract_values_from_page_index(last_page_index);
-
-        let mut next_page_index: PageId;
-
-        // Bypass algorithm - counterclockwise, starting from the southwest
-        for _ in 1..amount_of_pages_to_extract {
-            next_page_index = Self::get_index(current_cell_row_index + PAGE_LENGTH, current_cell_column_index);
-            let (next_cell_row_index, _) = Self::extract_values_from_page_index(next_page_index);
-
-            if direction == right {
-                if next_cell_row_index <= last_cell_row_index {
-                    current_cell_row_index += PAGE_LENGTH;
-                    page_indexes.push(next_page_index);
-                    continue;
-                } else {
-                    direction = up;
-                }
-            }
-
-            next_page_index = Self::get_index(current_cell_row_index, current_cell_column_index + PAGE_WIDTH);
-            let (_, next_cell_column_index) = Self::extract_values_from_page_index(next_page_index);
-
-            if direction == up {
-                if next_cell_column_index <= last_cell_column_index {
-                    current_cell_column_index += PAGE_WIDTH;
-                    page_indexes.push(next_page_index);
-                    continue;
-                } else {
-                    direction = left;
-                }
-            }
-
-            next_page_index = Self::get_index(current_cell_row_index - PAGE_LENGTH, current_cell_column_index);
-            let (next_cell_row_index, _) = Self::extract_values_from_page_index(next_page_index);
-
-            if direction == left {
-                if next_cell_row_index >= first_cell_row_index {
-                    current_cell_row_index -= PAGE_LENGTH;
-                    page_indexes.push(next_page_index);
-                    continue;
-                } else {
-                    direction = down;
-                }
-            }
-
-            next_page_index = Self::get_index(current_cell_row_index, current_cell_column_index - PAGE_WIDTH);
-            let (_, next_cell_column_index) = Self::extract_values_from_page_index(next_page_index);
-            if direction == down {
-                if next_cell_column_index <= first_cell_column_index {
-                    current_cell_column_index -= PAGE_WIDTH;
-                    page_indexes.push(next_page_index);
-                    continue;
-                } else {
-                    direction = right;
-                }
-            }
+    /// Splits columns of the root into continuous ranges. Root, which crosses the antimeridian,
+    /// has north-east column less than south-west one, and is split into two ranges.
+    pub fn get_column_ranges(sw_column_index: u32, ne_column_index: u32) -> Vec<(u32, u32)> {
+        if sw_column_index <= ne_column_index {
+            return vec![(sw_column_index, ne_column_index)];
         }
-
-        page_indexes
+        vec![(sw_column_index, G::globe_end_column()), (G::globe_first_column(), ne_column_index)]
     }
 
-    /// Gets the indexes of the cells where the point is located
-    pub fn get_cell_indexes(point: Point3D<Coord>) -> (u32, u32) {
-        let lat: u32 = point.lat.to_u32_with_frac_part(BITMAP_CELL_LENGTH, CELL_SIZE_DEGREE);
-        let lon: u32 = point.lon.to_u32_with_frac_part(BITMAP_CELL_WIDTH, CELL_SIZE_DEGREE);
-
-        let row_index: u32 = lat / BITMAP_CELL_LENGTH;
-        let column_index: u32 = lon / BITMAP_CELL_WIDTH;
+    /// Gets the indexes of the cells where the point is located.
+    /// Point is expected to be on the globe, see is_on_globe. Fails, if the grid is so fine,
+    /// that indexes don't fit into 16 bits of root and page ids.
+    pub fn get_cell_indexes(point: Point3D<Coord>) -> Result<(u32, u32), ConversionError> {
+        let straight_angle = Coord::from_u32(180);
+        let mut lon = point.lon;
+        if lon >= straight_angle {
+            lon = lon - Coord::from_u32(360);
+        }
+        // Shifted coordinates are never negative
+        let lat: u32 = (point.lat + Coord::from_u32(90))
+            .checked_to_u32_with_frac_part(1, G::cell_size_degree())?;
+        let lon: u32 = (lon + straight_angle)
+            .checked_to_u32_with_frac_part(1, G::cell_size_degree())?;
+
+        let row_index: u32 = lat / G::cell_length() + G::lat_cell_padding();
+        let column_index: u32 = lon / G::cell_width() + G::lon_cell_padding();
+        if row_index > u16::MAX as u32 || column_index > u16::MAX as u32 {
+            return Err(ConversionError::Overflow);
+        }
 
-        (row_index, column_index)
+        Ok((row_index, column_index))
     }
 
     /// Gets Page's index
@@ -988,18 +1808,20 @@ impl<
         let row_index: u32;
         let column_index: u32;
 
-        if cell_row_index > 0 && cell_row_index % PAGE_LENGTH != 0 {
-            row_index = PAGE_LENGTH + cell_row_index - cell_row_index % PAGE_LENGTH;
+        let (page_length, page_width) = (G::page_length(), G::page_width());
+
+        if cell_row_index > 0 && cell_row_index % page_length != 0 {
+            row_index = page_length + cell_row_index - cell_row_index % page_length;
         } else if cell_row_index == 0 {
-            row_index = PAGE_LENGTH;
+            row_index = page_length;
         } else {
             row_index = cell_row_index;
         }
 
-        if cell_column_index > 0 && cell_column_index % PAGE_WIDTH != 0 {
-            column_index = PAGE_WIDTH + cell_column_index - cell_column_index % PAGE_WIDTH;
+        if cell_column_index > 0 && cell_column_index % page_width != 0 {
+            column_index = page_width + cell_column_index - cell_column_index % page_width;
         } else if cell_column_index == 0 {
-            column_index = PAGE_WIDTH;
+            column_index = page_width;
         } else {
             column_index = cell_column_index;
         }
@@ -1007,28 +1829,8 @@ impl<
         (row_index << 16) | column_index
     }
 
-    /// Gets boundary cells (southwest and northeast) indexes from Page index.
-    /// Returns the row and column of the southwest cell and the row and column of the northeast
-    /// cell, respectively.
-    pub fn get_boundary_cell_indexes(
-        index: PageId, sw_cell_row_index: u32, ne_cell_column_index: u32,
-    ) -> [u32; 4] {
-        let mask = 0b1111_1111_1111_1111;
-
-        let sw_cell_row_index = sw_cell_row_index - sw_cell_row_index % PAGE_LENGTH;
-        let sw_cell_column_index = index & mask;
-        let ne_cell_column_index = ne_cell_column_index - ne_cell_column_index % PAGE_WIDTH;
-        let ne_cell_row_index = index >> 16;
-
-        let indexes: [u32; 4] = [sw_cell_row_index as u32, sw_cell_column_index as u32,
-            ne_cell_row_index as u32, ne_cell_column_index as u32];
-
-        indexes
-    }
-
     /// Gets cell indexes from Page index.
-    /// Returns the row and column of the southwest cell and the row and column of the northeast
-    /// cell, respectively.
+    /// Returns the row and column of the northeast cell of the page.
     fn extract_values_from_page_index(page_index: PageId) -> (u32, u32) {
         let mask_u16: PageId = 0b1111_1111_1111_1111;
         let row_index: u32 = page_index >> 16;
@@ -1038,14 +1840,14 @@ impl<
 
     #[cfg(test)]
     pub fn is_active(&self) -> bool {
-        self.bitmap.is_empty()
+        !self.runs.is_empty()
     }
 }
 
-impl<Coord> Default for Page<Coord> {
+impl<Coord, G> Default for Page<Coord, G> {
     fn default() -> Self {
         Page{
-            bitmap: [[0u64; PAGE_WIDTH as usize]; PAGE_LENGTH as usize],
+            runs: Vec::new(),
             _phantom: PhantomData,
         }
     }
@@ -1054,252 +1856,359 @@ impl<Coord> Default for Page<Coord> {
 #[cfg(test)]
 mod page_tests {
     use super::*;
+    use crate::mock::Test;
     use crate::tests::{construct_custom_box, coord, Coord};
 
+    type TestPage = Page<Coord, Test>;
+
     // These tests are built taking into account all possible rectangles from 4 Pages
     #[test]
 
     fn get_amount_of_pages_to_extract() {
         // 1 x 1
-        let bounding_box = construct_custom_box( "0.0", "0.491", "0.301", "0.0");
-        let pages_to_extract = Page::get_amount_of_pages_to_extract_using_box(bounding_box);
+        let bounding_box = construct_custom_box("0.011", "0.011", "0.301", "0.491");
+        let pages_to_extract = TestPage::get_amount_of_pages_to_extract_using_box(bounding_box).unwrap();
         assert_eq!(pages_to_extract, 1);
 
         // 1 x 2
-        let bounding_box = construct_custom_box("0.0", "0.0", "0.301", "1.0");
-        let pages_to_extract = Page::get_amount_of_pages_to_extract_using_box(bounding_box);
+        let bounding_box = construct_custom_box("0.011", "0.011", "0.301", "0.511");
+        let pages_to_extract = TestPage::get_amount_of_pages_to_extract_using_box(bounding_box).unwrap();
         assert_eq!(pages_to_extract, 2);
 
         // 2 x 1
-        let bounding_box = construct_custom_box( "0.0", "0.01", "0.331", "0.0");
-        let pages_to_extract = Page::get_amount_of_pages_to_extract_using_box(bounding_box);
+        let bounding_box = construct_custom_box("0.011", "0.011", "0.331", "0.011");
+        let pages_to_extract = TestPage::get_amount_of_pages_to_extract_using_box(bounding_box).unwrap();
         assert_eq!(pages_to_extract, 2);
 
         // 1 x 3
-        let bounding_box = construct_custom_box("0.0", "0.0", "0.301", "1.011");
-        let pages_to_extract = Page::get_amount_of_pages_to_extract_using_box(bounding_box);
+        let bounding_box = construct_custom_box("0.011", "0.011", "0.301", "1.011");
+        let pages_to_extract = TestPage::get_amount_of_pages_to_extract_using_box(bounding_box).unwrap();
         assert_eq!(pages_to_extract, 3);
 
         // 3 x 1
-        let bounding_box = construct_custom_box("0.011", "0.0", "0.651", "0.011");
-        let pages_to_extract = Page::get_amount_of_pages_to_extract_using_box(bounding_box);
+        let bounding_box = construct_custom_box("0.011", "0.011", "0.651", "0.011");
+        let pages_to_extract = TestPage::get_amount_of_pages_to_extract_using_box(bounding_box).unwrap();
         assert_eq!(pages_to_extract, 3);
 
         // 1 x 4
-        let bounding_box = construct_custom_box("0.0", "0.0", "0.301", "1.511");
-        let pages_to_extract = Page::get_amount_of_pages_to_extract_using_box(bounding_box);
+        let bounding_box = construct_custom_box("0.011", "0.011", "0.301", "1.511");
+        let pages_to_extract = TestPage::get_amount_of_pages_to_extract_using_box(bounding_box).unwrap();
         assert_eq!(pages_to_extract, 4);
 
         // 4 x 1
-        let bounding_box = construct_custom_box( "0.011", "0.0", "0.981", "0.011");
-        let pages_to_extract = Page::get_amount_of_pages_to_extract_using_box(bounding_box);
+        let bounding_box = construct_custom_box("0.011", "0.011", "0.981", "0.011");
+        let pages_to_extract = TestPage::get_amount_of_pages_to_extract_using_box(bounding_box).unwrap();
         assert_eq!(pages_to_extract, 4);
 
         // 4 x 1
-        let bounding_box = construct_custom_box( "0.051", "0.0", "1.271", "0.011");
-        let pages_to_extract = Page::get_amount_of_pages_to_extract_using_box(bounding_box);
+        let bounding_box = construct_custom_box("0.051", "0.011", "1.271", "0.021");
+        let pages_to_extract = TestPage::get_amount_of_pages_to_extract_using_box(bounding_box).unwrap();
         assert_eq!(pages_to_extract, 4);
 
         // 2 x 2
         let bounding_box = construct_custom_box("0.211", "0.011", "0.631", "0.991");
-        let pages_to_extract = Page::get_amount_of_pages_to_extract_using_box(bounding_box);
+        let pages_to_extract = TestPage::get_amount_of_pages_to_extract_using_box(bounding_box).unwrap();
         assert_eq!(pages_to_extract, 4);
 
         // 2 x 2
-        let bounding_box = construct_custom_box("0.05", "0.0", "0.5", "0.75");
-        let pages_to_extract = Page::get_amount_of_pages_to_extract_using_box(bounding_box);
+        let bounding_box = construct_custom_box("55.371", "37.371", "55.921", "37.901");
+        let pages_to_extract = TestPage::get_amount_of_pages_to_extract_using_box(bounding_box).unwrap();
         assert_eq!(pages_to_extract, 4);
 
-        // 2 x 2
-        let bounding_box = construct_custom_box( "55.37", "37.37", "55.92", "37.90");
-        let pages_to_extract = Page::get_amount_of_pages_to_extract_using_box(bounding_box);
+        // 2 x 1, southern and western hemispheres
+        let bounding_box = construct_custom_box("-0.301", "-0.491", "-0.011", "-0.011");
+        let pages_to_extract = TestPage::get_amount_of_pages_to_extract_using_box(bounding_box).unwrap();
+        assert_eq!(pages_to_extract, 2);
+
+        // 2 x 2, southern and western hemispheres
+        let bounding_box = construct_custom_box("-23.0", "-46.8", "-22.5", "-46.3");
+        let pages_to_extract = TestPage::get_amount_of_pages_to_extract_using_box(bounding_box).unwrap();
         assert_eq!(pages_to_extract, 4);
+
+        // 3 x 2, crossing both the equator and the prime meridian
+        let bounding_box = construct_custom_box("-0.301", "-0.491", "0.011", "0.011");
+        let pages_to_extract = TestPage::get_amount_of_pages_to_extract_using_box(bounding_box).unwrap();
+        assert_eq!(pages_to_extract, 6);
+
+        // 2 x 2, near the south pole
+        let bounding_box = construct_custom_box("-90.0", "10.0", "-89.7", "10.4");
+        let pages_to_extract = TestPage::get_amount_of_pages_to_extract_using_box(bounding_box).unwrap();
+        assert_eq!(pages_to_extract, 4);
+
+        // 3 x 2, near the north pole
+        let bounding_box = construct_custom_box("89.6", "10.0", "90.0", "10.4");
+        let pages_to_extract = TestPage::get_amount_of_pages_to_extract_using_box(bounding_box).unwrap();
+        assert_eq!(pages_to_extract, 6);
+
+        // 1 x 1 + 1 x 2, crossing the antimeridian
+        let bounding_box = construct_custom_box("65.0", "179.8", "65.2", "180.2");
+        let pages_to_extract = TestPage::get_amount_of_pages_to_extract_using_box(bounding_box).unwrap();
+        assert_eq!(pages_to_extract, 3);
     }
 
     #[test]
 
     fn extract_values_from_page_index() {
-        let page_sw_column_index: u32 = 0b0000_0000_0010_0000;
-        let page_ne_row_index: u32 = 0b0000_0000_0011_0010;
+        let page_sw_column_index: u32 = 0b0010_0011_0110_0000;
+        let page_ne_row_index: u32 = 0b0100_0110_1011_0100;
         let page_index_expected: u32 = page_sw_column_index << 16 | page_ne_row_index;
 
         let point: Point3D<Coord> = Point3D::new(coord("0.011"), coord("0.011"), coord("1"));
-        let (cell_row_index, cell_column_index) = Page::get_cell_indexes(point);
-        assert_eq!(cell_row_index, 1);
-        assert_eq!(cell_column_index, 1);
-        let page_index = Page::<Coord>::get_index(cell_row_index, cell_column_index);
+        let (cell_row_index, cell_column_index) = TestPage::get_cell_indexes(point).unwrap();
+        assert_eq!(cell_row_index, 9025);
+        assert_eq!(cell_column_index, 18051);
+        let page_index = TestPage::get_index(cell_row_index, cell_column_index);
         assert_eq!(page_index, page_index_expected);
-        let (row_index, column_index) = Page::<Coord>::extract_values_from_page_index(page_index);
-        assert_eq!(row_index, PAGE_LENGTH);
-        assert_eq!(column_index, PAGE_WIDTH);
+        let (row_index, column_index) = TestPage::extract_values_from_page_index(page_index);
+        assert_eq!(row_index, 9056);
+        assert_eq!(column_index, 18100);
 
-        let page_sw_column_index: u32 = 0b0000_0100_1110_0000;
-        let page_ne_row_index: u32 = 0b0000_0000_0011_0010;
+        let page_sw_column_index: u32 = 0b0010_1000_0010_0000;
+        let page_ne_row_index: u32 = 0b0100_0110_1011_0100;
         let page_index_expected: u32 = page_sw_column_index << 16 | page_ne_row_index;
 
         let point: Point3D<Coord> = Point3D::new(coord("12.251"), coord("0.011"), coord("1"));
-        let (cell_row_index, cell_column_index) = Page::get_cell_indexes(point);
-        assert_eq!(cell_row_index, 1225);
-        assert_eq!(cell_column_index, 1);
-        let page_index = Page::<Coord>::get_index(cell_row_index, cell_column_index);
+        let (cell_row_index, cell_column_index) = TestPage::get_cell_indexes(point).unwrap();
+        assert_eq!(cell_row_index, 10249);
+        assert_eq!(cell_column_index, 18051);
+        let page_index = TestPage::get_index(cell_row_index, cell_column_index);
+        assert_eq!(page_index, page_index_expected);
+        let (row_index, column_index) = TestPage::extract_values_from_page_index(page_index);
+        assert_eq!(row_index, 10272);
+        assert_eq!(column_index, 18100);
 
+        let page_sw_column_index: u32 = 0b0010_1000_0010_0000;
+        let page_ne_row_index: u32 = 0b0111_1011_0111_0000;
+        let page_index_expected: u32 = page_sw_column_index << 16 | page_ne_row_index;
+
+        let point: Point3D<Coord> = Point3D::new(coord("12.251"), coord("135.211"), coord("1"));
+        let (cell_row_index, cell_column_index) = TestPage::get_cell_indexes(point).unwrap();
+        assert_eq!(cell_row_index, 10249);
+        assert_eq!(cell_column_index, 31571);
+        let page_index = TestPage::get_index(cell_row_index, cell_column_index);
         assert_eq!(page_index, page_index_expected);
-        let (row_index, column_index) = Page::<Coord>::extract_values_from_page_index(page_index);
-        assert_eq!(row_index, 1248);
-        assert_eq!(column_index, PAGE_WIDTH);
+        let (row_index, column_index) = TestPage::extract_values_from_page_index(page_index);
+        assert_eq!(row_index, 10272);
+        assert_eq!(column_index, 31600);
 
-        let page_sw_column_index: u32 = 0b0000_0100_1110_0000;
-        let page_ne_row_index: u32 = 0b0101_1011_1111_1110;
+        let page_sw_column_index: u32 = 0b0001_1110_1000_0000;
+        let page_ne_row_index: u32 = 0b0100_0110_1000_0010;
         let page_index_expected: u32 = page_sw_column_index << 16 | page_ne_row_index;
 
-        let point: Point3D<Coord> = Point3D::new(coord("12.251"), coord("235.211"), coord("1"));
-        let (cell_row_index, cell_column_index) = Page::get_cell_indexes(point);
-        assert_eq!(cell_row_index, 1225);
-        assert_eq!(cell_column_index, 23521);
-        let page_index = Page::<Coord>::get_index(cell_row_index, cell_column_index);
+        let point: Point3D<Coord> = Point3D::new(coord("-12.251"), coord("-0.011"), coord("1"));
+        let (cell_row_index, cell_column_index) = TestPage::get_cell_indexes(point).unwrap();
+        assert_eq!(cell_row_index, 7798);
+        assert_eq!(cell_column_index, 18048);
+        let page_index = TestPage::get_index(cell_row_index, cell_column_index);
         assert_eq!(page_index, page_index_expected);
-        let (row_index, column_index) = Page::<Coord>::extract_values_from_page_index(page_index);
-        assert_eq!(row_index, 1248);
-        assert_eq!(column_index, 23550);
+        let (row_index, column_index) = TestPage::extract_values_from_page_index(page_index);
+        assert_eq!(row_index, 7808);
+        assert_eq!(column_index, 18050);
     }
 
     #[test]
     fn get_page_index() {
         // The formula for getting page index from rows and columns is the same,
         // except for the shift, so only several cases are considered.
-        // The entry 1-1 means 1 digit in the row index and 1 digit in the column index, and so on.
 
-        // case 1-1
-        let page_sw_column_index: u32 = 0b0000_0000_0010_0000;
-        let page_ne_row_index: u32 = 0b0000_0000_0011_0010;
+        // north-eastern hemisphere
+        let page_sw_column_index: u32 = 0b0010_0011_0110_0000;
+        let page_ne_row_index: u32 = 0b0100_0110_1011_0100;
         let page_index_expected: u32 = page_sw_column_index << 16 | page_ne_row_index;
 
         let point: Point3D<Coord> = Point3D::new(coord("0.011"), coord("0.011"), coord("1"));
-        let (cell_row_index, cell_column_index) = Page::get_cell_indexes(point);
-        assert_eq!(cell_row_index, 1);
-        assert_eq!(cell_column_index, 1);
-        let page_index = Page::<Coord>::get_index(cell_row_index, cell_column_index);
-
+        let (cell_row_index, cell_column_index) = TestPage::get_cell_indexes(point).unwrap();
+        assert_eq!(cell_row_index, 9025);
+        assert_eq!(cell_column_index, 18051);
+        let page_index = TestPage::get_index(cell_row_index, cell_column_index);
         assert_eq!(page_index, page_index_expected);
 
-        // case 2-1
-        let page_sw_column_index: u32 = 0b0000_0000_0010_0000;
-        let page_ne_row_index: u32 = 0b0000_0000_0011_0010;
+        // same page
+        let page_sw_column_index: u32 = 0b0010_0011_0110_0000;
+        let page_ne_row_index: u32 = 0b0100_0110_1011_0100;
         let page_index_expected: u32 = page_sw_column_index << 16 | page_ne_row_index;
 
         let point: Point3D<Coord> = Point3D::new(coord("0.251"), coord("0.011"), coord("1"));
-        let (cell_row_index, cell_column_index) = Page::get_cell_indexes(point);
-        assert_eq!(cell_row_index, 25);
-        assert_eq!(cell_column_index, 1);
-        let page_index = Page::<Coord>::get_index(cell_row_index, cell_column_index);
+        let (cell_row_index, cell_column_index) = TestPage::get_cell_indexes(point).unwrap();
+        assert_eq!(cell_row_index, 9049);
+        assert_eq!(cell_column_index, 18051);
+        let page_index = TestPage::get_index(cell_row_index, cell_column_index);
         assert_eq!(page_index, page_index_expected);
 
-        // case 3-1
-        let page_sw_column_index: u32 = 0b0000_0001_0000_0000;
-        let page_ne_row_index: u32 = 0b0000_0000_0011_0010;
+        // next pages
+        let page_sw_column_index: u32 = 0b0010_0100_0100_0000;
+        let page_ne_row_index: u32 = 0b0100_0110_1011_0100;
         let page_index_expected: u32 = page_sw_column_index << 16 | page_ne_row_index;
 
         let point: Point3D<Coord> = Point3D::new(coord("2.251"), coord("0.011"), coord("1"));
-        let (cell_row_index, cell_column_index) = Page::get_cell_indexes(point);
-        assert_eq!(cell_row_index, 225);
-        assert_eq!(cell_column_index, 1);
-        let page_index = Page::<Coord>::get_index(cell_row_index, cell_column_index);
+        let (cell_row_index, cell_column_index) = TestPage::get_cell_indexes(point).unwrap();
+        assert_eq!(cell_row_index, 9249);
+        assert_eq!(cell_column_index, 18051);
+        let page_index = TestPage::get_index(cell_row_index, cell_column_index);
+        assert_eq!(page_index, page_index_expected);
+
+        // far from zero
+        let page_sw_column_index: u32 = 0b0010_1000_0010_0000;
+        let page_ne_row_index: u32 = 0b0111_1011_0111_0000;
+        let page_index_expected: u32 = page_sw_column_index << 16 | page_ne_row_index;
 
+        let point: Point3D<Coord> = Point3D::new(coord("12.251"), coord("135.211"), coord("1"));
+        let (cell_row_index, cell_column_index) = TestPage::get_cell_indexes(point).unwrap();
+        assert_eq!(cell_row_index, 10249);
+        assert_eq!(cell_column_index, 31571);
+        let page_index = TestPage::get_index(cell_row_index, cell_column_index);
         assert_eq!(page_index, page_index_expected);
 
-        // case 4-1
-        let page_sw_column_index: u32 = 0b0000_0100_1110_0000;
-        let page_ne_row_index: u32 = 0b0000_0000_0011_0010;
+        // south-western hemisphere
+        let page_sw_column_index: u32 = 0b0001_0110_0100_0000;
+        let page_ne_row_index: u32 = 0b0010_1011_0010_1010;
         let page_index_expected: u32 = page_sw_column_index << 16 | page_ne_row_index;
 
-        let point: Point3D<Coord> = Point3D::new(coord("12.251"), coord("0.011"), coord("1"));
-        let (cell_row_index, cell_column_index) = Page::get_cell_indexes(point);
-        assert_eq!(cell_row_index, 1225);
-        assert_eq!(cell_column_index, 1);
-        let page_index = Page::<Coord>::get_index(cell_row_index, cell_column_index);
+        let point: Point3D<Coord> = Point3D::new(coord("-33.371"), coord("-70.373"), coord("1"));
+        let (cell_row_index, cell_column_index) = TestPage::get_cell_indexes(point).unwrap();
+        assert_eq!(cell_row_index, 5686);
+        assert_eq!(cell_column_index, 11012);
+        let page_index = TestPage::get_index(cell_row_index, cell_column_index);
         assert_eq!(page_index, page_index_expected);
 
-        // case 5-1
-        let page_sw_column_index: u32 = 0b0011_0100_0010_0000;
+        // south-west corner of the globe
+        let page_sw_column_index: u32 = 0b0000_0000_0010_0000;
         let page_ne_row_index: u32 = 0b0000_0000_0011_0010;
         let page_index_expected: u32 = page_sw_column_index << 16 | page_ne_row_index;
 
-        let point: Point3D<Coord> = Point3D::new(coord("133.251"), coord("0.011"), coord("1"));
-        let (cell_row_index, cell_column_index) = Page::get_cell_indexes(point);
-        assert_eq!(cell_row_index, 13325);
-        assert_eq!(cell_column_index, 1);
-        let page_index = Page::<Coord>::get_index(cell_row_index, cell_column_index);
-
+        let point: Point3D<Coord> = Point3D::new(coord("-89.999"), coord("-179.999"), coord("1"));
+        let (cell_row_index, cell_column_index) = TestPage::get_cell_indexes(point).unwrap();
+        assert_eq!(cell_row_index, 24);
+        assert_eq!(cell_column_index, 50);
+        let page_index = TestPage::get_index(cell_row_index, cell_column_index);
         assert_eq!(page_index, page_index_expected);
 
-        // case 4-5
-        let page_sw_column_index: u32 = 0b0000_0100_1110_0000;
-        let page_ne_row_index: u32 = 0b0101_1011_1111_1110;
+        // north-east corner of the globe
+        let page_sw_column_index: u32 = 0b0100_0110_1000_0000;
+        let page_ne_row_index: u32 = 0b1000_1100_1101_0010;
         let page_index_expected: u32 = page_sw_column_index << 16 | page_ne_row_index;
 
-        let point: Point3D<Coord> = Point3D::new(coord("12.251"), coord("235.211"), coord("1"));
-        let (cell_row_index, cell_column_index) = Page::get_cell_indexes(point);
-        assert_eq!(cell_row_index, 1225);
-        assert_eq!(cell_column_index, 23521);
-        let page_index = Page::<Coord>::get_index(cell_row_index, cell_column_index);
+        let point: Point3D<Coord> = Point3D::new(coord("89.999"), coord("179.999"), coord("1"));
+        let (cell_row_index, cell_column_index) = TestPage::get_cell_indexes(point).unwrap();
+        assert_eq!(cell_row_index, 18023);
+        assert_eq!(cell_column_index, 36049);
+        let page_index = TestPage::get_index(cell_row_index, cell_column_index);
         assert_eq!(page_index, page_index_expected);
     }
 
     #[test]
     fn calculate_cell_indexes() {
         let point: Point3D<Coord> = Point3D::new(coord("1.0"), coord("2.0"), coord("1"));
-        let (cell_row_index, cell_column_index) = Page::<Coord>::get_cell_indexes(point);
-        assert_eq!(cell_row_index, 100);
-        assert_eq!(cell_column_index, 200);
+        let (cell_row_index, cell_column_index) = TestPage::get_cell_indexes(point).unwrap();
+        assert_eq!(cell_row_index, 9124);
+        assert_eq!(cell_column_index, 18250);
 
         let point: Point3D<Coord> = Point3D::new(coord("55.371"), coord("33.371"), coord("1"));
-        let (cell_row_index, cell_column_index) = Page::get_cell_indexes(point);
-        assert_eq!(cell_row_index, 5537);
-        assert_eq!(cell_column_index, 3337);
-
-        let point: Point3D<Coord> = Point3D::new(coord("133.371"), coord("255.373"), coord("1"));
-        let (cell_row_index, cell_column_index) = Page::get_cell_indexes(point);
-        assert_eq!(cell_row_index, 13337);
-        assert_eq!(cell_column_index, 25537);
-
-        let point: Point3D<Coord> = Point3D::new(coord("360.0"), coord("180.0"), coord("1"));
-        let (cell_row_index, cell_column_index) = Page::get_cell_indexes(point);
-        assert_eq!(cell_row_index, 36000);
-        assert_eq!(cell_column_index, 18000);
-
-        let point: Point3D<Coord> = Point3D::new(coord("13.3778"), coord("255.3734"), coord("1"));
-        let (cell_row_index, cell_column_index) = Page::get_cell_indexes(point);
-        assert_eq!(cell_row_index, 1337);
-        assert_eq!(cell_column_index, 25537);
+        let (cell_row_index, cell_column_index) = TestPage::get_cell_indexes(point).unwrap();
+        assert_eq!(cell_row_index, 14561);
+        assert_eq!(cell_column_index, 21387);
+
+        let point: Point3D<Coord> = Point3D::new(coord("-33.371"), coord("-70.373"), coord("1"));
+        let (cell_row_index, cell_column_index) = TestPage::get_cell_indexes(point).unwrap();
+        assert_eq!(cell_row_index, 5686);
+        assert_eq!(cell_column_index, 11012);
+
+        let point: Point3D<Coord> = Point3D::new(coord("90.0"), coord("180.0"), coord("1"));
+        let (cell_row_index, cell_column_index) = TestPage::get_cell_indexes(point).unwrap();
+        // Longitude of 180 degrees is the same as -180
+        assert_eq!(cell_row_index, 18024);
+        assert_eq!(cell_column_index, 50);
+
+        let point: Point3D<Coord> = Point3D::new(coord("-90.0"), coord("-180.0"), coord("1"));
+        let (cell_row_index, cell_column_index) = TestPage::get_cell_indexes(point).unwrap();
+        assert_eq!(cell_row_index, 24);
+        assert_eq!(cell_column_index, 50);
+
+        let point: Point3D<Coord> = Point3D::new(coord("13.3778"), coord("-155.3734"), coord("1"));
+        let (cell_row_index, cell_column_index) = TestPage::get_cell_indexes(point).unwrap();
+        assert_eq!(cell_row_index, 10361);
+        assert_eq!(cell_column_index, 2512);
 
         let point: Point3D<Coord> = Point3D::new(coord("0.452"), coord("0.3003"), coord("1"));
-        let (cell_row_index, cell_column_index) = Page::get_cell_indexes(point);
-        assert_eq!(cell_row_index, 45);
-        assert_eq!(cell_column_index, 30);
+        let (cell_row_index, cell_column_index) = TestPage::get_cell_indexes(point).unwrap();
+        assert_eq!(cell_row_index, 9069);
+        assert_eq!(cell_column_index, 18080);
 
         let point: Point3D<Coord> = Point3D::new(coord("55.37"), coord("33.37"), coord("1"));
-        let (cell_row_index, cell_column_index) = Page::get_cell_indexes(point);
+        let (cell_row_index, cell_column_index) = TestPage::get_cell_indexes(point).unwrap();
         // Because it is required minimum 3 non-zero (simultaneous) digits after the point
-        assert_eq!(cell_row_index, 5536);
-        assert_eq!(cell_column_index, 3336);
+        assert_eq!(cell_row_index, 14560);
+        assert_eq!(cell_column_index, 21386);
+
+        let point: Point3D<Coord> = Point3D::new(coord("-1.3778321"), coord("25.3222734"), coord("1"));
+        let (cell_row_index, cell_column_index) = TestPage::get_cell_indexes(point).unwrap();
+        assert_eq!(cell_row_index, 8886);
+        assert_eq!(cell_column_index, 20582);
+
+        let point: Point3D<Coord> = Point3D::new(coord("1.301"), coord("185.301"), coord("1"));
+        let (cell_row_index, cell_column_index) = TestPage::get_cell_indexes(point).unwrap();
+        // Longitude above 180 degrees is wrapped, as in roots crossing the antimeridian
+        assert_eq!(cell_row_index, 9154);
+        assert_eq!(cell_column_index, 580);
+    }
 
-        let point: Point3D<Coord> = Point3D::new(coord("1.3778321"), coord("25.3222734"), coord("1"));
-        let (cell_row_index, cell_column_index) = Page::get_cell_indexes(point);
-        assert_eq!(cell_row_index, 137);
-        assert_eq!(cell_column_index, 2532);
+    #[test]
+    fn check_point_is_on_globe() {
+        assert_eq!(TestPage::is_on_globe(Point3D::new(coord("90.0"), coord("-180.0"), coord("1"))), true);
+        assert_eq!(TestPage::is_on_globe(Point3D::new(coord("-90.0"), coord("359.99"), coord("1"))), true);
+        assert_eq!(TestPage::is_on_globe(Point3D::new(coord("90.001"), coord("0.0"), coord("1"))), false);
+        assert_eq!(TestPage::is_on_globe(Point3D::new(coord("-90.001"), coord("0.0"), coord("1"))), false);
+        assert_eq!(TestPage::is_on_globe(Point3D::new(coord("0.0"), coord("-180.001"), coord("1"))), false);
+        assert_eq!(TestPage::is_on_globe(Point3D::new(coord("0.0"), coord("360.0"), coord("1"))), false);
+    }
 
-        let point: Point3D<Coord> = Point3D::new(coord("1.301"), coord("25.301"), coord("1"));
-        let (cell_row_index, cell_column_index) = Page::get_cell_indexes(point);
-        assert_eq!(cell_row_index, 130);
-        assert_eq!(cell_column_index, 2530);
+    #[test]
+    fn split_columns_at_antimeridian() {
+        assert_eq!(TestPage::get_column_ranges(18051, 18101), vec![(18051, 18101)]);
+        assert_eq!(TestPage::get_column_ranges(36029, 70), vec![(36029, Test::globe_end_column()), (Test::globe_first_column(), 70)]);
+    }
+
+    #[test]
+    fn set_and_get_cells() {
+        let mut page = TestPage::default();
+        page.set_cells(3, 10, 20, 7);
+        page.set_cells(3, 30, 40, 8);
+        page.set_cells(5, 0, 50, 9);
+        assert_eq!(page.get_cell(3, 9), 0);
+        assert_eq!(page.get_cell(3, 10), 7);
+        assert_eq!(page.get_cell(3, 19), 7);
+        assert_eq!(page.get_cell(3, 20), 0);
+        assert_eq!(page.get_cell(3, 35), 8);
+        assert_eq!(page.get_cell(4, 15), 0);
+        assert_eq!(page.get_cell(5, 49), 9);
+        assert!(page.is_free(3, 20, 30));
+        assert!(!page.is_free(3, 15, 25));
+        assert!(!page.is_free(3, 0, 50));
+
+        // Freeing the middle of the run splits it
+        page.set_cells(3, 12, 15, 0);
+        assert_eq!(page.get_cell(3, 11), 7);
+        assert_eq!(page.get_cell(3, 12), 0);
+        assert_eq!(page.get_cell(3, 15), 7);
+        assert_eq!(page.runs.len(), 4);
+
+        // Refilled cells are joined with the neighbours back
+        page.set_cells(3, 12, 15, 7);
+        assert_eq!(page.runs.len(), 3);
+
+        page.set_cells(3, 0, 50, 0);
+        page.set_cells(5, 0, 50, 0);
+        assert!(!page.is_active());
     }
 }
 
-type AreaId = u16;
+
+pub type AreaId = u16;
 type PageId = u32;
 type LightCoord = u32;
-type RootId = u64;
-type ZoneId = u128;
+pub type RootId = u64;
+pub type ZoneId = u128;
+type RouteId = u64;
 
 /// Configure the pallet by specifying the parameters and types on which it depends.
 pub trait Trait: accounts::Trait {
@@ -1325,6 +2234,9 @@ pub trait Trait: accounts::Trait {
     // Traits from dsky-utils
     + IntDiv
     + FromRaw
+    + ToRaw
+    + FromU32
+    + MetricConversion
     + CastToType
     + ToBigCoord<Output = Self::BigCoord>;
 
@@ -1336,8 +2248,7 @@ pub trait Trait: accounts::Trait {
     + Default
     + PartialOrd
     + Copy
-    + FromBigCoord<Output = Self::Coord>
-    + GetEpsilon;
+    + FromBigCoord<Output = Self::Coord>;
     
     type RawCoord: Default 
     + Parameter 
@@ -1349,15 +2260,80 @@ pub trait Trait: accounts::Trait {
     
     /// Max available height of any building
     type MaxHeight: Get<LightCoord>;
+
+    /// Max amount of vertices in polygon zone
+    type MaxPolygonVertices: Get<u32>;
+
+    /// Max amount of areas, which one zone can overlap
+    type MaxAreasPerZone: Get<u32>;
+
+    /// Max amount of waypoints in one route
+    type MaxWaypoints: Get<u32>;
+
+    /// Minimal horizontal distance in metres between drones on different routes
+    type HorizontalSeparation: Get<LightCoord>;
+
+    /// Minimal vertical distance in metres between drones on different routes
+    type VerticalSeparation: Get<LightCoord>;
+
+    /// Minimal time between drones on different routes, passing the same place
+    type TimeSeparation: Get<<Self as pallet_timestamp::Config>::Moment>;
+
+    /// Max amount of pending routes, which cross one area
+    type MaxRoutesInArea: Get<u32>;
+
+    /// Max amount of areas, which are read to check one route. Route crosses not more of them,
+    /// so it also bounds the areas, which forget the route on its completion or cancellation.
+    type MaxAreasPerRoute: Get<u32>;
+
+    /// Max amount of areas of removed roots, which are cleared in one block. Must be above zero.
+    type MaxAreasClearedPerBlock: Get<u32>;
+
+    /// Max amount of temporary zones, which are pruned in one block. Must be above zero.
+    type MaxZonesPrunedPerBlock: Get<u32>;
+
+    /// Index, which finds roots by their cells, see `index`
+    type SpatialIndex: SpatialIndex<Self>;
+
+    /// Max amount of roots, which are stored in one block, while the spatial index is rebuilt.
+    /// Must be above zero.
+    type MaxRootsIndexedPerBlock: Get<u32>;
+
+    /// Max amount of keys of the old spatial index, which are removed in one block,
+    /// before the index is rebuilt. Must be above zero.
+    type MaxIndexKeysClearedPerBlock: Get<u32>;
+
+    /// Bitmap cell size along latitude in 10^-CellSizeDegree degree.
+    /// When grid parameters change, roots are re-indexed on runtime upgrade.
+    type BitmapCellLength: Get<u32>;
+
+    /// Bitmap cell size along longitude in 10^-CellSizeDegree degree
+    type BitmapCellWidth: Get<u32>;
+
+    /// Digits of the degree's fraction, in which cell sizes are given, up to 6.
+    /// Cell indexes must fit into 16 bits, so cells can't be finer than about 0.006 degree.
+    type CellSizeDegree: Get<u8>;
+
+    /// Page size in cells along latitude, up to 255
+    type PageLength: Get<u32>;
+
+    /// Page size in cells along longitude, up to 255
+    type PageWidth: Get<u32>;
+
+    /// Max amount of pages, which one root may span in BitmapIndex
+    type MaxPagesAmountToExtract: Get<u32>;
 }    
 
 pub trait WeightInfo {
-    fn root_add() -> Weight;
-    fn zone_add() -> Weight;
+    fn root_add(pages: u32) -> Weight;
+    fn zone_add(areas: u32, zones: u32) -> Weight;
     fn root_remove() -> Weight;
-    fn zone_remove() -> Weight;
+    fn zone_remove(areas: u32, zones: u32) -> Weight;
     fn change_area_type() -> Weight;
-    fn route_add() -> Weight;
+    fn route_add(waypoints: u32, areas: u32, zones: u32, routes: u32) -> Weight;
+    fn change_route_status(areas: u32) -> Weight;
+    fn prune_zones(expired: u32, areas: u32, zones: u32) -> Weight;
+    fn clear_root(areas: u32, zones: u32) -> Weight;
 }
 
 decl_storage! {
@@ -1371,18 +2347,113 @@ decl_storage! {
         EarthBitmap get(fn bitmap_cells):
             map hasher(blake2_128_concat) PageId => PageOf<T>;
 
+        /// Grid, which the spatial index is built with
+        IndexedGrid get(fn indexed_grid) build(|_: &GenesisConfig| <T as Grid>::resolution()): GridResolution;
+
+        /// Kind of the spatial index, which roots are stored in. None, if the index was built before it.
+        IndexedKind get(fn indexed_kind)
+            build(|_: &GenesisConfig| Some(<T as Trait>::SpatialIndex::kind())): Option<index::IndexKind>;
+
+        /// Raw key in RootBoxes, after which roots are not stored in the rebuilt spatial index yet.
+        /// None, unless the index is being rebuilt.
+        IndexRebuildKey get(fn index_rebuild_key): Option<Vec<u8>>;
+
+        /// Keys of the old spatial index are being removed, before roots are stored in the rebuilt one
+        IndexClearing get(fn index_clearing): bool;
+
+        /// Nodes of QuadtreeIndex. Node is removed, when no roots are stored in it or below it.
+        QuadNodes get(fn quad_node):
+            map hasher(blake2_128_concat) index::QuadKey => index::QuadNode;
+
         AreaData get(fn area_info):
             double_map hasher(blake2_128_concat) RootId, 
                        hasher(blake2_128_concat) AreaId => Area;    
 
         RedZones get(fn zone_data): 
             map hasher(blake2_128_concat) ZoneId => ZoneOf<T>;
+
+        /// Occupied zone slots of the area, sorted. Area is removed, when it has no zones.
+        ZonesInArea get(fn zones_in_area):
+            double_map hasher(blake2_128_concat) RootId,
+                       hasher(blake2_128_concat) AreaId => Vec<u16>;
+
+        /// Ends of temporary zones by the block, which is expected to come after them.
+        /// Entries of removed zones are left, and skipped, when their block comes.
+        ZonesExpiry get(fn zones_expiry):
+            double_map hasher(blake2_128_concat) <T as frame_system::Config>::BlockNumber,
+                       hasher(blake2_128_concat) ZoneId => <T as pallet_timestamp::Config>::Moment;
+
+        /// First block, which expiring zones may be left in, see `prune_expired_zones`
+        NextPrunedBlock get(fn next_pruned_block): <T as frame_system::Config>::BlockNumber;
+
+        Routes get(fn route_data):
+            map hasher(blake2_128_concat) RouteId => RouteOf<T>;
+
+        NextRouteId get(fn next_route_id): RouteId;
+
+        /// Every route of the root, so they are removed with the root
+        RoutesInRoot get(fn routes_in_root):
+            double_map hasher(blake2_128_concat) RootId,
+                       hasher(blake2_128_concat) RouteId => ();
+
+        /// Routes, crossing the area. Routes, which are over, are removed from the area,
+        /// when another route crosses it.
+        RoutesInArea get(fn routes_in_area):
+            double_map hasher(blake2_128_concat) RootId,
+                       hasher(blake2_128_concat) AreaId => Vec<RouteId>;
+
+        /// Roots, which are being removed, in order of removal. Only the first one is cleared.
+        RootsToRemove get(fn roots_to_remove): Vec<RootId>;
+
+        /// Version of the storage layout, 0 for chains, started before versioning
+        StorageVersion get(fn storage_version) build(|_: &GenesisConfig| STORAGE_VERSION): u16;
+    }
+    add_extra_genesis {
+        /// Roots as raw SW {lat, lon, alt} NE {lat, lon, alt}, and raw delta
+        config(roots): Vec<([i32; 6], i32)>;
+        /// Permanent zones as raw SW {lat, lon} NE {lat, lon}, with their height and root
+        config(zones): Vec<([i32; 4], LightCoord, RootId)>;
+        /// Types of areas [root, area, type]. Zones are added before, so areas may be forbidden.
+        config(area_types): Vec<(RootId, AreaId, u8)>;
+        build(|config: &GenesisConfig| {
+            for (raw_box, raw_delta) in config.roots.iter() {
+                let south_west = Point3D::new(T::Coord::from_raw(raw_box[0]),
+                                              T::Coord::from_raw(raw_box[1]),
+                                              T::Coord::from_raw(raw_box[2]));
+                let north_east = Point3D::new(T::Coord::from_raw(raw_box[3]),
+                                              T::Coord::from_raw(raw_box[4]),
+                                              T::Coord::from_raw(raw_box[5]));
+                let bounding_box = Box3D::new(south_west, north_east);
+                if let Err(e) = Module::<T>::insert_root(bounding_box, T::Coord::from_raw(*raw_delta)) {
+                    panic!("Genesis root {:?} can't be added: {:?}", raw_box, e);
+                }
+            }
+            for (raw_rect, height, root_id) in config.zones.iter() {
+                let rect = Rect2D::new(Point2D::new(T::Coord::from_raw(raw_rect[0]), T::Coord::from_raw(raw_rect[1])),
+                                       Point2D::new(T::Coord::from_raw(raw_rect[2]), T::Coord::from_raw(raw_rect[3])));
+                if let Err(e) = Module::<T>::check_zone_root(*root_id, *height)
+                    .and_then(|_| Module::<T>::insert_zone(*root_id, ZoneOf::<T>::new(0, rect, *height)).map(|_| ())) {
+                    panic!("Genesis zone {:?} can't be added: {:?}", raw_rect, e);
+                }
+            }
+            for (root_id, area_id, area_type) in config.area_types.iter() {
+                assert!(*area_id != 0 && *area_id <= Module::<T>::get_max_area(*root_id),
+                        "Genesis area {} doesn't exist in root {}", area_id, root_id);
+                AreaData::insert(root_id, area_id, Area::new(*area_type));
+            }
+        });
     }
 }
 
-pub type PageOf<T> = Page<<T as Trait>::Coord>;
+pub type PageOf<T> = Page<<T as Trait>::Coord, T>;
 pub type RootBoxOf<T> = RootBox<<T as Trait>::Coord>;
-pub type ZoneOf<T> = Zone<<T as Trait>::Coord>;
+pub type ZoneOf<T> = Zone<<T as Trait>::Coord, <T as pallet_timestamp::Config>::Moment>;
+pub type WaypointOf<T> = Waypoint<<T as Trait>::Coord, <T as pallet_timestamp::Config>::Moment>;
+pub type RouteOf<T> = Route<
+    <T as frame_system::Config>::AccountId,
+    <T as Trait>::Coord,
+    <T as pallet_timestamp::Config>::Moment,
+>;
 
 // Pallets use events to inform users when important changes are made.
 // https://substrate.dev/docs/en/knowledgebase/runtime/events
@@ -1400,12 +2471,28 @@ decl_event!(
         ZoneCreated(RootId, AreaId, ZoneId, AccountId),
         /// Area type changed [role, area, root, who]
         AreaTypeChanged(u8, AreaId, RootId, AccountId),
-        /// Root was removed from storage
-        RootRemoved(RootId, AccountId),
+        /// Root is being removed, it's contents are cleared in the next blocks [root, who]
+        RootRemovalStarted(RootId, AccountId),
+        /// Root and everything inside it was removed from storage [root]
+        RootRemoved(RootId),
         /// Zone was removed from storage
         ZoneRemoved(ZoneId, AccountId),
-        /// New route was submitted [start, destination, start, arrival, rootId, who]
-        RouteAdded(Point3D<Coord>, Point3D<Coord>, Moment, Moment, RootId, AccountId),
+        /// Temporary zone is over, and was removed from storage [zone]
+        ZoneExpired(ZoneId),
+        /// New route was submitted [route, start, destination, start, arrival, rootId, who]
+        RouteAdded(RouteId, Point3D<Coord>, Point3D<Coord>, Moment, Moment, RootId, AccountId),
+        /// Pilot started flying the route [route, who]
+        RouteActivated(RouteId, AccountId),
+        /// Route was flown [route, who]
+        RouteCompleted(RouteId, AccountId),
+        /// Route was cancelled [route, who]
+        RouteCancelled(RouteId, AccountId),
+        /// Root overlaps another one on the new grid, or doesn't fit into the new spatial index,
+        /// so it's removed [root]
+        RootNotIndexed(RootId),
+        /// Root can't be checked for overlapping within one block, so it's kept,
+        /// but left out of the rebuilt index [root]
+        RootLeftUnindexed(RootId),
     }
 );
 
@@ -1439,7 +2526,7 @@ decl_error! {
         PageLimitExceeded,
         /// Root you are trying to access is not in storage
         RootDoesNotExist,
-        /// Zone points lies in different areas
+        /// Zone lies outside of root, or overlaps too many areas
         ZoneDoesntFit,
         /// Zone you are trying to access is not in storage
         ZoneDoesntExist,
@@ -1449,10 +2536,67 @@ decl_error! {
         RouteDoesNotFitToRoot,
         /// Route intersect 1 or more zones
         RouteIntersectRedZone, 
+        /// Polygon has less than 3, or more than allowed vertices
+        WrongVerticesAmount,
+        /// Route has more waypoints, than allowed
+        TooManyWaypoints,
+        /// Route you are trying to access is not in storage
+        RouteDoesNotExist,
+        /// Route's current status doesn't allow this operation
+        WrongRouteStatus,
+        /// Route comes too close to another planned or active route
+        RouteConflict,
+        /// Coordinates give indexes out of bounds, e.g. root has more than 65535 areas
+        CoordOverflow,
+        /// Root is being removed, so it can't be changed or used for routes
+        RootIsBeingRemoved,
+        /// Root with the same id exists, as it was added with another grid
+        RootAlreadyExists,
+        /// Polygon's border crosses or touches itself
+        PolygonNotSimple,
+        /// Route crosses an area, which already has the max amount of pending routes
+        TooManyRoutesInArea,
+        /// Checking the route needs more areas, than the given limit
+        AreaLimitExceeded,
+        /// Spatial index needs to read more nodes, than allowed, to check the root
+        IndexLimitExceeded,
+        /// Spatial index is being rebuilt, so roots can't be added
+        IndexIsBeingRebuilt,
         // Add additional errors below
     }
 }
 
+impl<T: Trait> From<ConversionError> for Error<T> {
+    fn from(error: ConversionError) -> Self {
+        match error {
+            ConversionError::Overflow => Error::<T>::CoordOverflow,
+            // Only delta of the root is used as a divisor
+            ConversionError::DivisionByZero => Error::<T>::BadDimesions,
+            ConversionError::Negative => Error::<T>::InvalidCoords,
+        }
+    }
+}
+
+impl<T: Trait> From<RouteViolation> for Error<T> {
+    fn from(violation: RouteViolation) -> Self {
+        match violation {
+            RouteViolation::RootDoesNotExist => Error::<T>::RootDoesNotExist,
+            RouteViolation::TooFewWaypoints => Error::<T>::InvalidData,
+            RouteViolation::TooManyWaypoints => Error::<T>::TooManyWaypoints,
+            RouteViolation::ArrivalInPast | RouteViolation::WrongArrivalTime(_) => Error::<T>::WrongTimeSupplied,
+            RouteViolation::WaypointOutsideRoot(_) => Error::<T>::RouteDoesNotFitToRoot,
+            RouteViolation::ZoneCrossed(..) => Error::<T>::RouteIntersectRedZone,
+            RouteViolation::RouteConflict(..) => Error::<T>::RouteConflict,
+            RouteViolation::RootIsBeingRemoved => Error::<T>::RootIsBeingRemoved,
+            RouteViolation::TooManyRoutesInArea(_) => Error::<T>::TooManyRoutesInArea,
+            RouteViolation::CoordOverflow => Error::<T>::CoordOverflow,
+            RouteViolation::BadDimensions => Error::<T>::BadDimesions,
+            RouteViolation::InvalidCoords => Error::<T>::InvalidCoords,
+            RouteViolation::AreaLimitExceeded => Error::<T>::AreaLimitExceeded,
+        }
+    }
+}
+
 // Dispatchable functions allows users to interact with the pallet and invoke state changes.
 // These functions materialize as "extrinsics", which are often compared to transactions.
 // Dispatchable functions must be annotated with a weight and must return a DispatchResult.
@@ -1464,89 +2608,67 @@ decl_module! {
         // Events must be initialized if they are used by the pallet.
         fn deposit_event() = default;
 
+        fn on_runtime_upgrade() -> Weight {
+            migrations::migrate::<T>()
+        }
+
+        /// Checks, that runtime parameters fit into stored types: rows and columns of a page
+        /// are kept in u8 by CellRun, and cell indexes of the globe are kept in 16 bits by RootId
+        fn integrity_test() {
+            assert!(T::MaxAreasClearedPerBlock::get() > 0, "MaxAreasClearedPerBlock must be above zero");
+            assert!(T::MaxZonesPrunedPerBlock::get() > 0, "MaxZonesPrunedPerBlock must be above zero");
+            assert!(T::MaxRootsIndexedPerBlock::get() > 0, "MaxRootsIndexedPerBlock must be above zero");
+            assert!(T::MaxIndexKeysClearedPerBlock::get() > 0, "MaxIndexKeysClearedPerBlock must be above zero");
+            assert!(<T as Grid>::cell_length() > 0 && <T as Grid>::cell_width() > 0, "cell size must be above zero");
+            // Full angle in units of cell size fits into u32
+            assert!(<T as Grid>::cell_size_degree() <= 7, "CellSizeDegree must be at most 7");
+            let (page_length, page_width) = (<T as Grid>::page_length(), <T as Grid>::page_width());
+            assert!(page_length > 0 && page_length <= u8::MAX as u32, "PageLength must be in 1..=255");
+            assert!(page_width > 0 && page_width <= u8::MAX as u32, "PageWidth must be in 1..=255");
+            let north_pole_row = 180 * 10u32.pow(<T as Grid>::cell_size_degree() as u32) / <T as Grid>::cell_length()
+                + <T as Grid>::lat_cell_padding();
+            assert!(north_pole_row <= u16::MAX as u32, "rows of the grid don't fit into 16 bits");
+            assert!(<T as Grid>::globe_end_column() <= u16::MAX as u32 + 1,
+                    "columns of the grid don't fit into 16 bits");
+        }
+
+        /// Removes temporary zones, which are over. Timestamp of the previous block is used.
+        /// Then stores roots in the rebuilt spatial index, and clears removed roots,
+        /// as far as block budget allows.
+        fn on_initialize(block_number: T::BlockNumber) -> Weight {
+            let now = <pallet_timestamp::Module<T>>::get();
+            let (expired, blocks) = Self::prune_expired_zones(now, block_number);
+            let indexing = Self::index_next_roots()
+                .unwrap_or_else(|| <T as frame_system::Config>::DbWeight::get().reads(1));
+            let zones_in_area = T::MaxBuildingsInArea::get() as u32;
+            let clearing = match Self::clear_removed_root() {
+                Some(cleared) if cleared < T::MaxAreasClearedPerBlock::get() =>
+                    <T as Trait>::WeightInfo::clear_root(cleared, zones_in_area)
+                        .saturating_add(<T as Trait>::SpatialIndex::remove_weight()),
+                Some(cleared) => <T as Trait>::WeightInfo::clear_root(cleared, zones_in_area),
+                None => <T as frame_system::Config>::DbWeight::get().reads(1),
+            };
+            <T as Trait>::WeightInfo::prune_zones(expired, T::MaxAreasPerZone::get(), zones_in_area)
+                .saturating_add(<T as frame_system::Config>::DbWeight::get().reads_writes(blocks as Weight + 1, 1))
+                .saturating_add(indexing)
+                .saturating_add(clearing)
+        }
+
         /// Adds new RootBox to storage
-        #[weight = <T as Trait>::WeightInfo::root_add()]
+        #[weight = <T as Trait>::SpatialIndex::insert_weight(*bounding_box)]
         pub fn root_add(origin, bounding_box: Box3D<T::Coord>, delta: T::Coord) -> dispatch::DispatchResult {
             let who = ensure_signed(origin)?;
             ensure!(<accounts::Module<T>>::account_is(&who, REGISTRAR_ROLE.into()), Error::<T>::NotAuthorized);
 
-            // Check amount of pages to be extracted
-            let amount_of_pages_to_extract = Page::<T::Coord>::get_amount_of_pages_to_extract_using_box(bounding_box);
-            ensure!(amount_of_pages_to_extract <= MAX_PAGES_AMOUNT_TO_EXTRACT, Error::<T>::PageLimitExceeded);
-
-            // Check given coordinates
-            let (sw_cell_row_index, sw_cell_column_index) = Page::<T::Coord>::get_cell_indexes(bounding_box.south_west);
-            let sw_page_index = Page::<T::Coord>::get_index(sw_cell_row_index, sw_cell_column_index);
-            let (ne_cell_row_index, ne_cell_column_index) = Page::<T::Coord>::get_cell_indexes(bounding_box.north_east);
-            let ne_page_index = Page::<T::Coord>::get_index(ne_cell_row_index, ne_cell_column_index);
-            ensure!(ne_page_index != 0 && sw_page_index != 0, Error::<T>::InvalidCoords);
-            ensure!(sw_cell_column_index <= ne_cell_column_index, Error::<T>::InvalidCoords);
-            ensure!(sw_cell_row_index <= ne_cell_row_index, Error::<T>::InvalidCoords);
-            if ne_page_index == sw_page_index {
-                ensure!(sw_cell_row_index <= ne_cell_row_index, Error::<T>::InvalidCoords);
-            }
-
-            let page_indexes: Vec<u32> = Page::<T::Coord>::get_pages_indexes_to_be_extracted(
-                amount_of_pages_to_extract,
-                sw_cell_row_index, sw_cell_column_index,
-                sw_page_index, ne_page_index,
-            );
-
-            let id = RootBox::<T::Coord>::get_index(sw_cell_row_index, sw_cell_column_index,
-                                                    ne_cell_row_index, ne_cell_column_index);
-            let rootbox_boundary_cell_indexes = RootBox::<T::Coord>::get_boundary_cell_indexes(id);
-
-            let mut updated_pages: Vec<Page<<T as Trait>::Coord>> = Vec::new();
-            for page_index in page_indexes.clone() {
-                let mut current_bitmap = EarthBitmap::<T>::get(page_index).bitmap;
-                let page_boundary_cell_indexes = Page::<T::Coord>::get_boundary_cell_indexes(
-                    page_index, sw_cell_row_index, ne_cell_column_index
-                );
-
-                let mut row_start = 0;
-                if rootbox_boundary_cell_indexes[0] == page_boundary_cell_indexes[0] {
-                    row_start = sw_cell_row_index % PAGE_LENGTH;
-                }
-
-                let mut row_end = PAGE_LENGTH;
-                if rootbox_boundary_cell_indexes[2] == page_boundary_cell_indexes[2] {
-                    row_end = ne_cell_row_index % PAGE_LENGTH;
-                }
-
-                for page_row in current_bitmap.iter_mut().take(row_end as usize).skip(row_start as usize) {
-                    let mut column_start = 0;
-                    if rootbox_boundary_cell_indexes[1] == page_boundary_cell_indexes[1] {
-                        column_start = sw_cell_column_index % PAGE_WIDTH;
-                    }
-
-                    let mut column_end = PAGE_WIDTH;
-                    if rootbox_boundary_cell_indexes[3] == page_boundary_cell_indexes[3] {
-                        column_end = ne_cell_column_index % PAGE_WIDTH;
-                    }
-
-                    for cell in page_row.iter_mut().take(column_end as usize).skip(column_start as usize) {
-                        ensure!(*cell == 0_u64, Error::<T>::OverlappingRoot);
-                        *cell = id;
-                    }
-                }
-                let mut page = Page::new();
-                page.bitmap = current_bitmap;
-                updated_pages.push(page);
-            }
-
-            for (page_number, page_index) in page_indexes.into_iter().enumerate() {
-                EarthBitmap::<T>::insert(page_index, updated_pages[page_number]);
-            }
-
-            let root = RootBoxOf::<T>::new(id, bounding_box, delta);
-            RootBoxes::<T>::insert(id, root);
-
+            let id = Self::insert_root(bounding_box, delta)?;
             Self::deposit_event(RawEvent::RootCreated(id, who));
             Ok(())
         }
         
         /// TODO fix this trouble with types, RawCoord is a one big crutch
-        #[weight = <T as Trait>::WeightInfo::root_add()]
+        /// Bounds are unknown before conversion, so the largest root of the bitmap is assumed
+        #[weight = <T as Trait>::WeightInfo::root_add(T::MaxPagesAmountToExtract::get())
+            .max(<T as Trait>::SpatialIndex::insert_weight(Default::default()))]
         pub fn raw_root_add(origin, 
                             // Coords is SW {lat, lon, alt} NE {lat, lon, alt} 
                             raw_box: [T::RawCoord; 6],
@@ -1567,63 +2689,93 @@ decl_module! {
         }
 
         /// Form index and store input to redzones, creates area struct if it doesnt exist
-        #[weight = <T as Trait>::WeightInfo::zone_add()]
+        #[weight = Module::<T>::zone_add_max_weight()]
         pub fn zone_add(origin, 
                         rect: Rect2D<T::Coord>,
                         height: LightCoord,
                         root_id: RootId) -> dispatch::DispatchResult {
             let who = ensure_signed(origin)?;
             ensure!(<accounts::Module<T>>::account_is(&who, REGISTRAR_ROLE.into()), Error::<T>::NotAuthorized);
-            ensure!(RootBoxes::<T>::contains_key(root_id), Error::<T>::RootDoesNotExist);
-            ensure!(height < T::MaxHeight::get(), Error::<T>::InvalidData);
-            // Check if zone lies in one single area 
-            let area_id = RootBoxes::<T>::get(root_id).detect_intersected_area(rect.south_west);
-            let se_area_id = RootBoxes::<T>::get(root_id).detect_intersected_area(rect.north_east);
-            ensure!(area_id == se_area_id, Error::<T>::ZoneDoesntFit);
-            // Getting area from storage, or creating it
-            let (area, area_existed) = if AreaData::contains_key(root_id, area_id) {
-                (AreaData::get(root_id, area_id), true)
-            } else {
-                AreaData::insert(root_id, area_id, Area::new(GREEN_AREA));
-                (Area::new(GREEN_AREA), false)
-            };
+            Self::check_zone_root(root_id, height)?;
+            let (area_id, zone_id) = Self::insert_zone(root_id, ZoneOf::<T>::new(0, rect, height))?;
+            Self::deposit_event(RawEvent::ZoneCreated(root_id, area_id, zone_id, who));
+            Ok(())
+        }
 
-            let max_zones = T::MaxBuildingsInArea::get();
-            let first_empty_id = Self::pack_index(root_id, area_id, 0);
-            let mut zone_id = first_empty_id;
-            
-            // If area already exists, we check if it's full, and check all zones inside for intersection
-            if area_existed {
-                ensure!(area.area_type == GREEN_AREA, Error::<T>::ForbiddenArea); 
-                let mut current_zone = first_empty_id;
-                let mut empty_id_found = false;
-                // Maybe, this cycle should be splitted in two. One finds first unused Id,
-                // and only if it was found, we should look for intersections. Not sure.
-                while current_zone < first_empty_id + max_zones as ZoneId {
-                    if RedZones::<T>::contains_key(current_zone) || empty_id_found {
-                        // Check if our zone overlaps with another zone in current area
-                        let rect_to_check = RedZones::<T>::get(current_zone).rect;
-                        ensure!(!rect_to_check.intersects_rect(rect), Error::<T>::OverlappingZone);
-                        current_zone += 1;
-                    } else { 
-                        zone_id = current_zone;
-                        empty_id_found = true;
-                    }
-                } 
-                ensure!(empty_id_found, Error::<T>::AreaFull);
-            } else {
-                // This is first zone in area, we don't need to check any intersections and stuff.
-                zone_id = first_empty_id; 
-            }
-            
-            let zone = ZoneOf::<T>::new(zone_id, rect, height);
-            RedZones::<T>::insert(zone_id, zone);
+        /// Adds temporary flight restriction, which is active between given moments, and is
+        /// removed automatically after it's end. Unlike permanent zones, it may overlap other zones.
+        #[weight = Module::<T>::zone_add_max_weight()]
+        pub fn zone_add_temporary(origin,
+                                  rect: Rect2D<T::Coord>,
+                                  height: LightCoord,
+                                  root_id: RootId,
+                                  start: T::Moment,
+                                  end: T::Moment) -> dispatch::DispatchResult {
+            let who = ensure_signed(origin)?;
+            ensure!(<accounts::Module<T>>::account_is(&who, REGISTRAR_ROLE.into()), Error::<T>::NotAuthorized);
+            Self::check_zone_root(root_id, height)?;
+            ensure!(start < end, Error::<T>::WrongTimeSupplied);
+            ensure!(end > <pallet_timestamp::Module<T>>::get(), Error::<T>::WrongTimeSupplied);
+
+            let zone = ZoneOf::<T>::new_temporary(0, rect, height, start, end);
+            let (area_id, zone_id) = Self::insert_zone(root_id, zone)?;
+            let block_number = <frame_system::Module<T>>::block_number();
+            Self::schedule_expiry(zone_id, end, <pallet_timestamp::Module<T>>::get(), block_number);
+            Self::deposit_event(RawEvent::ZoneCreated(root_id, area_id, zone_id, who));
+            Ok(())
+        }
+
+        /// Adds zone of any shape, given by it's vertices. Border of the polygon must not cross itself.
+        /// Zone is stored in areas, which it's bounding rect overlaps, as in zone_add.
+        #[weight = Module::<T>::zone_add_max_weight()]
+        pub fn zone_add_polygon(origin,
+                                vertices: Vec<Point2D<T::Coord>>,
+                                height: LightCoord,
+                                root_id: RootId) -> dispatch::DispatchResult {
+            let who = ensure_signed(origin)?;
+            ensure!(<accounts::Module<T>>::account_is(&who, REGISTRAR_ROLE.into()), Error::<T>::NotAuthorized);
+            Self::check_zone_root(root_id, height)?;
+            ensure!(vertices.len() >= 3 && vertices.len() <= T::MaxPolygonVertices::get() as usize,
+                    Error::<T>::WrongVerticesAmount);
+            let raw_vertices: Vec<RawPoint> = vertices.iter().map(|vertex| vertex.to_raw()).collect();
+            ensure!(is_simple_polygon(&raw_vertices), Error::<T>::PolygonNotSimple);
+
+            let rect = Rect2D::bounding(&vertices).ok_or(Error::<T>::WrongVerticesAmount)?;
+            let zone = ZoneOf::<T>::new_polygon(0, rect, height, vertices);
+            let (area_id, zone_id) = Self::insert_zone(root_id, zone)?;
+            Self::deposit_event(RawEvent::ZoneCreated(root_id, area_id, zone_id, who));
+            Ok(())
+        }
+
+        /// Adds round zone, given by it's center and radius in metres. Same rules as for zone_add
+        /// are applied to circle's bounding rect.
+        #[weight = Module::<T>::zone_add_max_weight()]
+        pub fn zone_add_circle(origin,
+                               center: Point2D<T::Coord>,
+                               radius: LightCoord,
+                               height: LightCoord,
+                               root_id: RootId) -> dispatch::DispatchResult {
+            let who = ensure_signed(origin)?;
+            ensure!(<accounts::Module<T>>::account_is(&who, REGISTRAR_ROLE.into()), Error::<T>::NotAuthorized);
+            Self::check_zone_root(root_id, height)?;
+            ensure!(radius > 0, Error::<T>::InvalidData);
+
+            let lat_radius = T::Coord::metres_to_lat_degrees(radius);
+            let lon_radius = T::Coord::metres_to_lon_degrees(radius, center.lat);
+            // Circle which is bigger than the root can't fit in it anyway
+            let root_dimensions = RootBoxes::<T>::get(root_id).bounding_box.projection_on_plane().get_dimensions();
+            ensure!(lat_radius < root_dimensions.lat && lon_radius < root_dimensions.lon, Error::<T>::ZoneDoesntFit);
+            let rect = Rect2D::new(Point2D::new(center.lat - lat_radius, center.lon - lon_radius),
+                                   Point2D::new(center.lat + lat_radius, center.lon + lon_radius));
+
+            let zone = ZoneOf::<T>::new_circle(0, rect, height, center, radius);
+            let (area_id, zone_id) = Self::insert_zone(root_id, zone)?;
             Self::deposit_event(RawEvent::ZoneCreated(root_id, area_id, zone_id, who));
             Ok(())
         }
 
         /// TODO fix this trouble with types, RawCoord is a one big crutch
-        #[weight = <T as Trait>::WeightInfo::zone_add()]
+        #[weight = Module::<T>::zone_add_max_weight()]
         pub fn raw_zone_add(origin, 
                             raw_rect: [T::RawCoord; 4],
                             height: LightCoord,
@@ -1640,106 +2792,35 @@ decl_module! {
             Module::<T>::zone_add(origin, rect, height, root_id)
         }
 
-        /// Removes root by given id, and zones inside. This means, function might be heavy.
+        /// Starts removal of the root by given id. Root can't be used since then, and it's zones,
+        /// areas and cells in the spatial index are cleared in the next blocks, see `clear_removed_root`.
         #[weight = <T as Trait>::WeightInfo::root_remove()]
         pub fn root_remove(origin, root_id: RootId) -> dispatch::DispatchResult {
             let who = ensure_signed(origin)?;
             ensure!(<accounts::Module<T>>::account_is(&who, REGISTRAR_ROLE.into()), Error::<T>::NotAuthorized);
             ensure!(RootBoxes::<T>::contains_key(root_id), Error::<T>::RootDoesNotExist);
+            ensure!(!Self::is_being_removed(root_id), Error::<T>::RootIsBeingRemoved);
 
-            let max_zones = T::MaxBuildingsInArea::get();
-            let max_areas = RootBoxes::<T>::get(root_id).get_max_area();
-            // Recursively remove all zones inside selected root
-            let mut zone_id = Self::pack_index(root_id, 0, 0);
-            let mut area_id = 0;
-            while area_id <= max_areas {
-                let max_zones_in_area = zone_id + max_zones as ZoneId;
-                while zone_id < max_zones_in_area {
-                    if RedZones::<T>::contains_key(zone_id) {
-                        RedZones::<T>::remove(zone_id); 
-                    }
-                    zone_id += 1;
-                }
-                area_id += 1;
-                zone_id = Self::pack_index(root_id, area_id, 0);
-            }
-
-            // Recursively clear all cells in bitmap
-            let rootbox_boundary_cell_indexes = RootBox::<T::Coord>::get_boundary_cell_indexes(root_id);
-            let sw_cell_row_index = rootbox_boundary_cell_indexes[0];
-            let sw_cell_column_index = rootbox_boundary_cell_indexes[1];
-            let ne_cell_row_index = rootbox_boundary_cell_indexes[2];
-            let ne_cell_column_index = rootbox_boundary_cell_indexes[3];
-
-            let amount_of_pages_to_extract = Page::<T::Coord>::get_amount_of_pages_to_extract(
-                sw_cell_row_index, sw_cell_column_index,
-                ne_cell_row_index, ne_cell_column_index,
-            );
-            let sw_page_index = Page::<T::Coord>::get_index(sw_cell_row_index, sw_cell_column_index);
-            let ne_page_index = Page::<T::Coord>::get_index(ne_cell_row_index, ne_cell_column_index);
-
-            let page_indexes: Vec<u32> = Page::<T::Coord>::get_pages_indexes_to_be_extracted(
-                amount_of_pages_to_extract,
-                sw_cell_row_index, sw_cell_column_index,
-                sw_page_index, ne_page_index,
-            );
-
-            let mut updated_pages: Vec<Page<<T as Trait>::Coord>> = Vec::new();
-            for page_index in page_indexes.clone() {
-                let mut current_bitmap = EarthBitmap::<T>::get(page_index).bitmap;
-                let page_boundary_cell_indexes = Page::<T::Coord>::get_boundary_cell_indexes(
-                    page_index, sw_cell_row_index, ne_cell_column_index
-                );
-
-                let mut row_start = 0;
-                if rootbox_boundary_cell_indexes[0] == page_boundary_cell_indexes[0] {
-                    row_start = sw_cell_row_index % PAGE_LENGTH;
-                }
-
-                let mut row_end = PAGE_LENGTH;
-                if rootbox_boundary_cell_indexes[2] == page_boundary_cell_indexes[2] {
-                    row_end = ne_cell_row_index % PAGE_LENGTH;
-                }
-
-                for page_row in current_bitmap.iter_mut().take(row_end as usize).skip(row_start as usize) {
-                    let mut column_start = 0;
-                    if rootbox_boundary_cell_indexes[1] == page_boundary_cell_indexes[1] {
-                        column_start = sw_cell_column_index % PAGE_WIDTH;
-                    }
-
-                    let mut column_end = PAGE_WIDTH;
-                    if rootbox_boundary_cell_indexes[3] == page_boundary_cell_indexes[3] {
-                        column_end = ne_cell_column_index % PAGE_WIDTH;
-                    }
-
-                    for cell in page_row.iter_mut().take(column_end as usize).skip(column_start as usize) {
-                        ensure!(*cell != 0, Error::<T>::InvalidData);
-                        *cell = 0;
-                    }
-                }
-                let mut page = Page::new();
-                page.bitmap = current_bitmap;
-                updated_pages.push(page);
-            }
-
-            for (page_number, page_index) in page_indexes.into_iter().enumerate() {
-                EarthBitmap::<T>::insert(page_index, updated_pages[page_number]);
-            }
-
-            RootBoxes::<T>::remove(root_id);
-            Self::deposit_event(RawEvent::RootRemoved(root_id, who));
+            RootsToRemove::append(root_id);
+            Self::deposit_event(RawEvent::RootRemovalStarted(root_id, who));
             Ok(())
         }
 
-        /// Removes zone by given id
-        #[weight = <T as Trait>::WeightInfo::zone_remove()]
+        /// Removes zone by given id from all areas, which it overlaps.
+        /// Id of the zone in any of these areas may be used. Expiry of temporary zone is left
+        /// in ZonesExpiry, and skipped, when it's due.
+        #[weight = <T as Trait>::WeightInfo::zone_remove(T::MaxAreasPerZone::get(),
+                                                         T::MaxBuildingsInArea::get() as u32)]
         pub fn zone_remove(origin, zone_id: ZoneId) -> dispatch::DispatchResult {
             let who = ensure_signed(origin)?;
             ensure!(<accounts::Module<T>>::account_is(&who, REGISTRAR_ROLE.into()), Error::<T>::NotAuthorized);
             ensure!(RedZones::<T>::contains_key(zone_id), Error::<T>::ZoneDoesntExist);
+            let (root_id, _, _) = Self::unpack_index(zone_id);
+            ensure!(!Self::is_being_removed(root_id), Error::<T>::RootIsBeingRemoved);
             
-            RedZones::<T>::remove(zone_id);
-            Self::deposit_event(RawEvent::ZoneRemoved(zone_id, who));
+            let zone = RedZones::<T>::get(zone_id);
+            Self::remove_zone(&zone);
+            Self::deposit_event(RawEvent::ZoneRemoved(zone.zone_id, who));
             Ok(())
         }
         
@@ -1751,6 +2832,7 @@ decl_module! {
                                 area_type: u8) -> dispatch::DispatchResult {
             let who = ensure_signed(origin)?;
             ensure!(<accounts::Module<T>>::account_is(&who, REGISTRAR_ROLE.into()), Error::<T>::NotAuthorized);
+            ensure!(!Self::is_being_removed(root_id), Error::<T>::RootIsBeingRemoved);
             ensure!(AreaData::contains_key(root_id, area_id), Error::<T>::NotExists);
             
             AreaData::mutate(root_id, area_id, |ar| {
@@ -1760,73 +2842,123 @@ decl_module! {
             Ok(())
         }
 
-        /// Creates new route for UAV
-        #[weight = <T as Trait>::WeightInfo::route_add()]
+        /// Creates new route for UAV. Checks read at most `area_limit` areas, capped by
+        /// `MaxAreasPerRoute`, and fail, if more are needed, see `RouteReport::read_areas`.
+        /// Declared weight covers the limit, and only weight of actually read areas,
+        /// zones and routes is charged.
+        #[weight = Module::<T>::route_add_max_weight(waypoints.len() as u32, *area_limit)]
         pub fn route_add(origin, 
                         waypoints: Vec<Waypoint<T::Coord, <T as pallet_timestamp::Config>::Moment>>, 
-                        root_id: RootId) -> dispatch::DispatchResult {
+                        root_id: RootId,
+                        area_limit: u32) -> dispatch::DispatchResultWithPostInfo {
             let who = ensure_signed(origin)?;
             // TODO consider role for route addition
             ensure!(<accounts::Module<T>>::account_is(&who, (PILOT_ROLE | REGISTRAR_ROLE).into()), Error::<T>::NotAuthorized);
-            ensure!(RootBoxes::<T>::contains_key(root_id), Error::<T>::RootDoesNotExist);
-            ensure!(waypoints.len() >= 2, Error::<T>::InvalidData);
+            let mut scan = RouteScan::new(area_limit.min(T::MaxAreasPerRoute::get()));
+            let (report, finished) = Self::check_route(&waypoints, root_id, false, &mut scan);
+            if let Some(violation) = report.violations.first() {
+                return Err(Error::<T>::from(*violation).into());
+            }
             let start_waypoint = &waypoints.first().unwrap(); 
             let end_waypoint = &waypoints.last().unwrap(); 
             // Getting all time bounds
             let start_time = start_waypoint.arrival;
             let arrival_time = end_waypoint.arrival;
-            let current_timestamp = <pallet_timestamp::Module<T>>::get();
-            // TODO (n>2) wp[n].arrival < wp[n + 1].arrival
-            ensure!((arrival_time > current_timestamp) && (arrival_time > start_time), Error::<T>::WrongTimeSupplied);
-            
-            let root = RootBoxes::<T>::get(root_id);
-            let start_area = root.detect_intersected_area(start_waypoint.location.project());
-            let end_area = root.detect_intersected_area(end_waypoint.location.project());
-            // TODO (n>2) each wp[n].location shall be inside one Root
-            ensure!((start_area != 0) && (end_area != 0), Error::<T>::RouteDoesNotFitToRoot);
-            // TODO (n>2) vec! in here
-            let route_line = Line::new(start_waypoint.location.project(), end_waypoint.location.project());
-            // We receive all areas, containing list of zones which could be intersected
-            let route_areas: Vec<AreaId> = route_line.get_route_areas(root);
-            // Loop through areas, check each existing zone
-            for area_id in route_areas.iter() {
-                if AreaData::contains_key(root_id, area_id) {
-                    let mut zone_id = Self::pack_index(root_id, *area_id, 0);
-                    // Loop through zones, maybe add constraint to MaxBuildingsInArea
-                    while RedZones::<T>::contains_key(zone_id) {
-                        // TODO ask about ensure!() usage in cycle
-                        ensure!(!route_line.intersects_rect(RedZones::<T>::get(zone_id).rect), Error::<T>::RouteIntersectRedZone);
-                        zone_id += 1;
-                    }
-                }
+
+            let (start_location, end_location) = (start_waypoint.location, end_waypoint.location);
+            let waypoints_amount = waypoints.len() as u32;
+            let route_id = NextRouteId::get();
+            NextRouteId::put(route_id.wrapping_add(1));
+            Routes::<T>::insert(route_id, RouteOf::<T>::new(who.clone(), waypoints, root_id));
+            RoutesInRoot::insert(root_id, route_id, ());
+            // Route is stored in every crossed area, and finished routes are forgotten by them
+            let mut route_areas = report.segment_areas.concat();
+            route_areas.sort_unstable();
+            route_areas.dedup();
+            for area_id in route_areas {
+                let mut routes = RoutesInArea::get(root_id, area_id);
+                routes.retain(|id| !finished.contains(id));
+                routes.push(route_id);
+                RoutesInArea::insert(root_id, area_id, routes);
             }
+
             Self::deposit_event(RawEvent::RouteAdded(
-                start_waypoint.location, end_waypoint.location, 
+                route_id, start_location, end_location, 
                 start_time, arrival_time, root_id, who
             ));
+            Ok(Some(<T as Trait>::WeightInfo::route_add(waypoints_amount, scan.areas, scan.zones, scan.routes)).into())
+        }
+
+        /// Marks route as being flown. Only pilot, who added the route, can do it.
+        #[weight = <T as Trait>::WeightInfo::change_route_status(0)]
+        pub fn route_activate(origin, route_id: RouteId) -> dispatch::DispatchResult {
+            let who = ensure_signed(origin)?;
+            ensure!(Routes::<T>::contains_key(route_id), Error::<T>::RouteDoesNotExist);
+            let route = Routes::<T>::get(route_id);
+            ensure!(route.pilot == who, Error::<T>::NotAuthorized);
+            ensure!(!Self::is_being_removed(route.root_id), Error::<T>::RootIsBeingRemoved);
+            ensure!(route.status == RouteStatus::Planned, Error::<T>::WrongRouteStatus);
+
+            Routes::<T>::mutate(route_id, |r| r.status = RouteStatus::Active);
+            Self::deposit_event(RawEvent::RouteActivated(route_id, who));
             Ok(())
         }
 
-        #[weight = <T as Trait>::WeightInfo::route_add()]
+        /// Marks route as flown. Only pilot, who added the route, can do it.
+        #[weight = <T as Trait>::WeightInfo::change_route_status(T::MaxAreasPerRoute::get())]
+        pub fn route_complete(origin, route_id: RouteId) -> dispatch::DispatchResultWithPostInfo {
+            let who = ensure_signed(origin)?;
+            ensure!(Routes::<T>::contains_key(route_id), Error::<T>::RouteDoesNotExist);
+            let route = Routes::<T>::get(route_id);
+            ensure!(route.pilot == who, Error::<T>::NotAuthorized);
+            ensure!(!Self::is_being_removed(route.root_id), Error::<T>::RootIsBeingRemoved);
+            ensure!(route.status == RouteStatus::Active, Error::<T>::WrongRouteStatus);
+
+            Routes::<T>::mutate(route_id, |r| r.status = RouteStatus::Completed);
+            let areas = Self::forget_route_areas(route_id, &route);
+            Self::deposit_event(RawEvent::RouteCompleted(route_id, who));
+            Ok(Some(<T as Trait>::WeightInfo::change_route_status(areas)).into())
+        }
+
+        /// Cancels route, which is not completed yet. Can be done by route's pilot, or by registrar.
+        #[weight = <T as Trait>::WeightInfo::change_route_status(T::MaxAreasPerRoute::get())]
+        pub fn route_cancel(origin, route_id: RouteId) -> dispatch::DispatchResultWithPostInfo {
+            let who = ensure_signed(origin)?;
+            ensure!(Routes::<T>::contains_key(route_id), Error::<T>::RouteDoesNotExist);
+            let route = Routes::<T>::get(route_id);
+            ensure!(route.pilot == who || <accounts::Module<T>>::account_is(&who, REGISTRAR_ROLE.into()),
+                    Error::<T>::NotAuthorized);
+            ensure!(!Self::is_being_removed(route.root_id), Error::<T>::RootIsBeingRemoved);
+            ensure!(route.is_pending(), Error::<T>::WrongRouteStatus);
+
+            Routes::<T>::mutate(route_id, |r| r.status = RouteStatus::Cancelled);
+            let areas = Self::forget_route_areas(route_id, &route);
+            Self::deposit_event(RawEvent::RouteCancelled(route_id, who));
+            Ok(Some(<T as Trait>::WeightInfo::change_route_status(areas)).into())
+        }
+
+        #[weight = Module::<T>::route_add_max_weight(2, *area_limit)]
         pub fn raw_route_add(origin, 
-                            raw_waypoints: [T::RawCoord; 4],
+                            // Coords are start {lat, lon, alt} arrival {lat, lon, alt}
+                            raw_waypoints: [T::RawCoord; 6],
                             start_time: T::Moment,
                             arrival_time: T::Moment,
-                            root_id: RootId) -> dispatch::DispatchResult {
+                            root_id: RootId,
+                            area_limit: u32) -> dispatch::DispatchResultWithPostInfo {
             let start_location = Point3D::new(
                 T::Coord::from_raw(raw_waypoints[0].into()), 
                 T::Coord::from_raw(raw_waypoints[1].into()),
-                Self::coord_from_str("1"));
+                T::Coord::from_raw(raw_waypoints[2].into()));
 
             let start_waypoint = Waypoint::new(start_location, start_time);
 
             let arrival_location = Point3D::new(
-                T::Coord::from_raw(raw_waypoints[2].into()), 
-                T::Coord::from_raw(raw_waypoints[3].into()),
-                Self::coord_from_str("1"));
+                T::Coord::from_raw(raw_waypoints[3].into()), 
+                T::Coord::from_raw(raw_waypoints[4].into()),
+                T::Coord::from_raw(raw_waypoints[5].into()));
 
             let arrival_waypoint = Waypoint::new(arrival_location, arrival_time);
-            Module::<T>::route_add(origin, vec![start_waypoint, arrival_waypoint], root_id)
+            Module::<T>::route_add(origin, vec![start_waypoint, arrival_waypoint], root_id, area_limit)
         }
     }
 }
@@ -1845,18 +2977,683 @@ impl<T: Trait> Module<T> {
         }
     } 
     
-    #[allow(dead_code)]
-    fn get_root_index(raw_point: [i32; 2]) -> RootId {
+    /// Returns id of the root, which contains given point, or 0 if there is none
+    pub fn get_root_index(raw_point: [i32; 2]) -> RootId {
         let lat = T::Coord::from_raw(raw_point[0]);
         let lon = T::Coord::from_raw(raw_point[1]);
         let alt = T::Coord::from_raw(0);
 
         let point = Point3D::<T::Coord>::new(lat, lon, alt);
-        let (row, column) = Page::<T::Coord>::get_cell_indexes(point);
-        let index = Page::<T::Coord>::get_index(row, column);
-        let bitmap = EarthBitmap::<T>::get(index).bitmap;
+        if !Page::<T::Coord, T>::is_on_globe(point) {
+            return 0;
+        }
+        let (row, column) = match Page::<T::Coord, T>::get_cell_indexes(point) {
+            Ok(indexes) => indexes,
+            Err(_) => return 0,
+        };
+
+        T::SpatialIndex::find(row, column)
+    }
+
+    /// Returns all zones, stored in the area
+    pub fn get_area_zones(root_id: RootId, area_id: AreaId) -> Vec<ZoneOf<T>> {
+        Self::area_zone_slots(root_id, area_id)
+            .into_iter()
+            .map(RedZones::<T>::get)
+            .collect()
+    }
+
+    /// Returns zones from all areas, which given rect overlaps. Every zone is returned once,
+    /// even if it is stored in several of these areas.
+    pub fn get_rect_zones(root_id: RootId, raw_rect: [i32; 4]) -> Vec<ZoneOf<T>> {
+        if !RootBoxes::<T>::contains_key(root_id) {
+            return Vec::new();
+        }
+        let rect = Rect2D::new(Point2D::new(T::Coord::from_raw(raw_rect[0]), T::Coord::from_raw(raw_rect[1])),
+                               Point2D::new(T::Coord::from_raw(raw_rect[2]), T::Coord::from_raw(raw_rect[3])));
+        let mut zones: Vec<ZoneOf<T>> = Vec::new();
+        for area_id in RootBoxes::<T>::get(root_id).get_rect_areas(rect).unwrap_or_default() {
+            for zone in Self::get_area_zones(root_id, area_id) {
+                if !zones.iter().any(|known| known.zone_id == zone.zone_id) {
+                    zones.push(zone);
+                }
+            }
+        }
+        zones
+    }
+
+    /// Returns type of the area, or None if area has no data yet
+    pub fn get_area_type(root_id: RootId, area_id: AreaId) -> Option<u8> {
+        if !AreaData::contains_key(root_id, area_id) {
+            return None;
+        }
+        Some(AreaData::get(root_id, area_id).area_type)
+    }
 
-        bitmap[(row % PAGE_LENGTH) as usize][(column % PAGE_WIDTH) as usize]
+    /// Returns max area index of the root, or 0 if root doesn't exist
+    pub fn get_max_area(root_id: RootId) -> AreaId {
+        if !RootBoxes::<T>::contains_key(root_id) {
+            return 0;
+        }
+        RootBoxes::<T>::get(root_id).get_max_area().unwrap_or_default()
+    }
+
+    /// Runs the same checks, as route_add does, without changing the storage.
+    /// Unlike route_add, doesn't stop on the first problem, and reports all of them.
+    /// Areas are not limited, and the report tells, how many of them were read.
+    pub fn validate_route(waypoints: &[WaypointOf<T>], root_id: RootId) -> RouteReport {
+        let mut scan = RouteScan::new(u32::MAX);
+        let (mut report, _) = Self::check_route(waypoints, root_id, true, &mut scan);
+        report.read_areas = scan.areas;
+        report
+    }
+
+    /// Checks the route against it's root, zones and other routes. Stops on the first problem,
+    /// unless `find_all` is set, and always stops, when areas exceed the limit of `scan`.
+    /// Read areas, zones and routes are counted in `scan`. Returns the report,
+    /// and routes in the searched areas, which are over, see `conflicting_routes`.
+    fn check_route(waypoints: &[WaypointOf<T>],
+                   root_id: RootId,
+                   find_all: bool,
+                   scan: &mut RouteScan) -> (RouteReport, Vec<RouteId>) {
+        let mut report = RouteReport::default();
+        // Nothing else can be checked after these problems
+        let fatal = if !RootBoxes::<T>::contains_key(root_id) {
+            Some(RouteViolation::RootDoesNotExist)
+        } else if Self::is_being_removed(root_id) {
+            Some(RouteViolation::RootIsBeingRemoved)
+        } else if waypoints.len() < 2 {
+            Some(RouteViolation::TooFewWaypoints)
+        } else if waypoints.len() > T::MaxWaypoints::get() as usize {
+            Some(RouteViolation::TooManyWaypoints)
+        } else {
+            None
+        };
+        if let Some(violation) = fatal {
+            report.violations.push(violation);
+            return (report, Vec::new());
+        }
+        let stop = |report: &RouteReport| !find_all && !report.is_valid();
+
+        let now = <pallet_timestamp::Module<T>>::get();
+        if waypoints[waypoints.len() - 1].arrival <= now {
+            report.violations.push(RouteViolation::ArrivalInPast);
+        }
+        // Each waypoint shall be reached strictly after the previous one
+        for (index, segment) in waypoints.windows(2).enumerate() {
+            if segment[1].arrival <= segment[0].arrival {
+                report.violations.push(RouteViolation::WrongArrivalTime(index as u32 + 1));
+            }
+        }
+        if stop(&report) {
+            return (report, Vec::new());
+        }
+
+        // Each waypoint shall be inside one root, including it's altitude bounds.
+        // Legs are straight, so they stay inside the root too.
+        let root = RootBoxes::<T>::get(root_id);
+        let mut route_fits = true;
+        for (index, waypoint) in waypoints.iter().enumerate() {
+            match root.detect_intersected_area(waypoint.location.project()) {
+                Ok(area) if area != 0 && root.is_altitude_inside(waypoint.location.alt) => (),
+                Ok(_) => {
+                    report.violations.push(RouteViolation::WaypointOutsideRoot(index as u32));
+                    route_fits = false;
+                },
+                Err(error) => {
+                    report.violations.push(error.into());
+                    route_fits = false;
+                },
+            }
+        }
+        // Areas of the segment, which leaves the root, are meaningless
+        if !route_fits || stop(&report) {
+            return (report, Vec::new());
+        }
+
+        // Every leg of the route is checked separately, as it's a separate line.
+        // Segment, which areas can't be found, has no areas in the report.
+        let mut areas_found = true;
+        for (index, segment) in waypoints.windows(2).enumerate() {
+            let line = Line::<T::Coord, T::BigCoord>::new(segment[0].location.project(),
+                                                          segment[1].location.project());
+            let areas = match line.get_route_areas(root) {
+                Ok(areas) => areas,
+                Err(error) => {
+                    report.segment_areas.push(Vec::new());
+                    report.violations.push(error.into());
+                    areas_found = false;
+                    continue;
+                },
+            };
+            if !scan.read_areas(areas.len()) {
+                report.violations.push(RouteViolation::AreaLimitExceeded);
+                return (report, Vec::new());
+            }
+            for zone_id in Self::segment_crossed_zones(root, &areas, &segment[0], &segment[1], scan) {
+                report.violations.push(RouteViolation::ZoneCrossed(index as u32, zone_id));
+            }
+            report.segment_areas.push(areas);
+            if stop(&report) {
+                return (report, Vec::new());
+            }
+        }
+        // Routes nearby can't be found without areas of every segment
+        if !areas_found {
+            return (report, Vec::new());
+        }
+
+        // Other routes nearby are stored in the crossed areas, or in areas next to them
+        let mut route_areas = report.segment_areas.concat();
+        route_areas.sort_unstable();
+        route_areas.dedup();
+        let searched_areas = match Self::separation_areas(root, &route_areas) {
+            Ok(areas) => areas,
+            Err(error) => {
+                report.violations.push(error.into());
+                return (report, Vec::new());
+            },
+        };
+        if !scan.read_areas(searched_areas.len()) {
+            report.violations.push(RouteViolation::AreaLimitExceeded);
+            return (report, Vec::new());
+        }
+        let area_routes: Vec<(AreaId, Vec<RouteId>)> = searched_areas.into_iter()
+            .map(|area_id| (area_id, RoutesInArea::get(root_id, area_id)))
+            .collect();
+        let (conflicts, finished) = Self::conflicting_routes(&area_routes, waypoints, now, scan);
+        for (index, route_id) in conflicts {
+            report.violations.push(RouteViolation::RouteConflict(index, route_id));
+        }
+        if stop(&report) {
+            return (report, finished);
+        }
+        // Finished routes are forgotten, so only pending ones count to the limit
+        for (area_id, routes) in area_routes.iter().filter(|(area_id, _)| route_areas.binary_search(area_id).is_ok()) {
+            let pending = routes.iter().filter(|id| !finished.contains(id)).count();
+            if pending >= T::MaxRoutesInArea::get() as usize {
+                report.violations.push(RouteViolation::TooManyRoutesInArea(*area_id));
+                if stop(&report) {
+                    break;
+                }
+            }
+        }
+        (report, finished)
+    }
+
+    /// Weight of the zone addition in the worst case: zone overlaps max amount of areas,
+    /// and every one of them is full of zones
+    fn zone_add_max_weight() -> Weight {
+        <T as Trait>::WeightInfo::zone_add(T::MaxAreasPerZone::get(), T::MaxBuildingsInArea::get() as u32)
+    }
+
+    /// Weight of the route addition, which reads up to `area_limit` areas, in the worst case:
+    /// every area is full of zones and routes
+    fn route_add_max_weight(waypoints: u32, area_limit: u32) -> Weight {
+        let waypoints = waypoints.min(T::MaxWaypoints::get());
+        let area_limit = area_limit.min(T::MaxAreasPerRoute::get());
+        let zones = area_limit.saturating_mul(T::MaxBuildingsInArea::get() as u32);
+        let routes = area_limit.saturating_mul(T::MaxRoutesInArea::get());
+        <T as Trait>::WeightInfo::route_add(waypoints, area_limit, zones, routes)
+    }
+
+    /// Cells of the box on the current grid as [sw_row, sw_column, ne_row, ne_column].
+    /// North-east cells are excluded.
+    fn root_cells(bounding_box: Box3D<T::Coord>) -> Result<[u32; 4], ConversionError> {
+        let (sw_row_index, sw_column_index) = Page::<T::Coord, T>::get_cell_indexes(bounding_box.south_west)?;
+        let (ne_row_index, ne_column_index) = Page::<T::Coord, T>::get_cell_indexes(bounding_box.north_east)?;
+        Ok([sw_row_index, sw_column_index, ne_row_index, ne_column_index])
+    }
+
+    /// Checks root's bounds and stores it in the spatial index. Returns id of the root.
+    fn insert_root(bounding_box: Box3D<T::Coord>, delta: T::Coord) -> Result<RootId, dispatch::DispatchError> {
+        // Check given coordinates. North-east longitude exceeds 180, if root crosses the antimeridian
+        let (south_west, north_east) = (bounding_box.south_west, bounding_box.north_east);
+        ensure!(Page::<T::Coord, T>::is_on_globe(south_west) && Page::<T::Coord, T>::is_on_globe(north_east),
+                Error::<T>::InvalidCoords);
+        ensure!(south_west.lat <= north_east.lat && south_west.lon <= north_east.lon, Error::<T>::InvalidCoords);
+
+        T::SpatialIndex::check_bounds(bounding_box)?;
+        ensure!(IndexRebuildKey::get().is_none(), Error::<T>::IndexIsBeingRebuilt);
+
+        let cells = Self::root_cells(bounding_box).map_err(Error::<T>::from)?;
+        let id = RootBox::<T::Coord>::get_index(cells[0], cells[1], cells[2], cells[3]);
+        // Area grid of stored roots is always valid, so read-only methods don't fail on it
+        let root = RootBoxOf::<T>::new(id, bounding_box, delta);
+        root.get_max_area().map_err(Error::<T>::from)?;
+
+        // Ids of roots, added before the grid changed, describe cells of the old grid
+        ensure!(!RootBoxes::<T>::contains_key(id), Error::<T>::RootAlreadyExists);
+        T::SpatialIndex::insert(id, cells, &mut T::SpatialIndex::search_budget())?;
+
+        RootBoxes::<T>::insert(id, root);
+        Ok(id)
+    }
+
+    /// Checks, that root of the zone exists, and zone is not too high
+    fn check_zone_root(root_id: RootId, height: LightCoord) -> dispatch::DispatchResult {
+        ensure!(RootBoxes::<T>::contains_key(root_id), Error::<T>::RootDoesNotExist);
+        ensure!(!Self::is_being_removed(root_id), Error::<T>::RootIsBeingRemoved);
+        ensure!(height < T::MaxHeight::get(), Error::<T>::InvalidData);
+        Ok(())
+    }
+
+    /// Stores zone to every area, which it's bounding rect overlaps, creating areas if they
+    /// don't exist. Zone gets id of it's slot in the first area, and keeps it in other areas too.
+    /// Returns first area and zone's id.
+    fn insert_zone(root_id: RootId, mut zone: ZoneOf<T>) -> Result<(AreaId, ZoneId), dispatch::DispatchError> {
+        let areas = RootBoxes::<T>::get(root_id).get_rect_areas(zone.rect).map_err(Error::<T>::from)?;
+        ensure!(!areas.is_empty(), Error::<T>::ZoneDoesntFit);
+        ensure!(areas.len() <= T::MaxAreasPerZone::get() as usize, Error::<T>::ZoneDoesntFit);
+
+        // Nothing is written, until every area is checked
+        let mut slots: Vec<ZoneId> = Vec::new();
+        for area_id in areas.iter() {
+            slots.push(Self::find_zone_slot(root_id, *area_id, &zone)?);
+        }
+        zone.zone_id = slots[0];
+        for (area_id, slot) in areas.iter().zip(slots.into_iter()) {
+            if !AreaData::contains_key(root_id, area_id) {
+                AreaData::insert(root_id, area_id, Area::new(GREEN_AREA));
+            }
+            RedZones::<T>::insert(slot, zone.clone());
+            let (_, _, child) = Self::unpack_index(slot);
+            ZonesInArea::mutate(root_id, area_id, |children| {
+                if let Err(position) = children.binary_search(&child) {
+                    children.insert(position, child);
+                }
+            });
+        }
+        Ok((areas[0], zone.zone_id))
+    }
+
+    /// Finds first free slot for the zone in the area, and checks overlapping with other zones in it
+    fn find_zone_slot(root_id: RootId, area_id: AreaId, zone: &ZoneOf<T>) -> Result<ZoneId, dispatch::DispatchError> {
+        let first_slot = Self::pack_index(root_id, area_id, 0);
+        // This is first zone in area, we don't need to check any intersections and stuff.
+        if !AreaData::contains_key(root_id, area_id) {
+            return Ok(first_slot);
+        }
+        ensure!(AreaData::get(root_id, area_id).area_type == GREEN_AREA, Error::<T>::ForbiddenArea);
+
+        let children = ZonesInArea::get(root_id, area_id);
+        for child in children.iter() {
+            // Check if our zone overlaps with another zone in current area.
+            // Temporary restrictions are laid over the map, so they may overlap.
+            let zone_to_check = RedZones::<T>::get(first_slot + *child as ZoneId);
+            let temporary = zone.time_window.is_some() || zone_to_check.time_window.is_some();
+            ensure!(temporary || !Self::zones_overlap(&zone_to_check, zone), Error::<T>::OverlappingZone);
+        }
+        // Occupied slots are sorted, so the first gap among them is the first free slot
+        let free_child = children.iter()
+            .enumerate()
+            .find(|(index, child)| **child as usize != *index)
+            .map_or(children.len(), |(index, _)| index);
+        ensure!(free_child < T::MaxBuildingsInArea::get() as usize, Error::<T>::AreaFull);
+        Ok(first_slot + free_child as ZoneId)
+    }
+
+    /// True, if zones have common inner points. Rects may touch each other, while polygons,
+    /// touching other zones inside of their bounding rects, are treated as overlapping.
+    /// Circles are checked by their bounding rects.
+    fn zones_overlap(first: &ZoneOf<T>, second: &ZoneOf<T>) -> bool {
+        if !first.rect.intersects_rect(second.rect) {
+            return false;
+        }
+        match (&first.shape, &second.shape) {
+            (ZoneShape::Polygon(_), _) | (_, ZoneShape::Polygon(_)) =>
+                polygons_intersect(&first.outline(), &second.outline()),
+            _ => true,
+        }
+    }
+
+    /// Removes zone from all areas, which it overlaps
+    fn remove_zone(zone: &ZoneOf<T>) {
+        let (root_id, _, _) = Self::unpack_index(zone.zone_id);
+        for area_id in RootBoxes::<T>::get(root_id).get_rect_areas(zone.rect).unwrap_or_default() {
+            let first_slot = Self::pack_index(root_id, area_id, 0);
+            let mut children = ZonesInArea::get(root_id, area_id);
+            children.retain(|child| {
+                let slot = first_slot + *child as ZoneId;
+                let removed = RedZones::<T>::get(slot).zone_id == zone.zone_id;
+                if removed {
+                    RedZones::<T>::remove(slot);
+                }
+                !removed
+            });
+            if children.is_empty() {
+                ZonesInArea::remove(root_id, area_id);
+            } else {
+                ZonesInArea::insert(root_id, area_id, children);
+            }
+        }
+    }
+
+    /// Ids of all zones, stored in the area, i.e. it's occupied slots
+    fn area_zone_slots(root_id: RootId, area_id: AreaId) -> Vec<ZoneId> {
+        let first_slot = Self::pack_index(root_id, area_id, 0);
+        ZonesInArea::get(root_id, area_id)
+            .into_iter()
+            .map(|child| first_slot + child as ZoneId)
+            .collect()
+    }
+
+    /// Checks all zones in given areas, crossed by the segment, for intersection with it.
+    /// Zone is intersected, if segment passes over it not higher, than zone's height,
+    /// while the zone is active. Returns ids of all intersected zones.
+    fn segment_crossed_zones(root: RootBoxOf<T>,
+                             route_areas: &[AreaId],
+                             start_waypoint: &WaypointOf<T>,
+                             end_waypoint: &WaypointOf<T>,
+                             scan: &mut RouteScan) -> Vec<ZoneId> {
+        let (start, end) = (start_waypoint.location, end_waypoint.location);
+        let line = Line::<T::Coord, T::BigCoord>::new(start.project(), end.project());
+        let (start_alt, end_alt) = (start.alt.to_raw(), end.alt.to_raw());
+        // Zones, overlapping several areas, are checked once
+        let mut checked_zones: Vec<ZoneId> = Vec::new();
+        let mut crossed_zones: Vec<ZoneId> = Vec::new();
+        // Loop through areas, check each existing zone
+        for area_id in route_areas.iter() {
+            for zone_id in Self::area_zone_slots(root.id, *area_id) {
+                let zone = RedZones::<T>::get(zone_id);
+                scan.zones += 1;
+                if checked_zones.contains(&zone.zone_id) {
+                    continue;
+                }
+                checked_zones.push(zone.zone_id);
+                let active = zone.is_active_between(start_waypoint.arrival, end_waypoint.arrival);
+                let crossed = active && match &zone.shape {
+                    // Segment may lie inside the rect, not crossing any of its edges
+                    ZoneShape::Rect => line.get_rect_crossing(zone.rect).is_some(),
+                    ZoneShape::Polygon(vertices) => line.intersects_polygon(vertices),
+                    ZoneShape::Circle(center, radius) => line.intersects_circle(*center, *radius),
+                };
+                if crossed {
+                    // Altitude changes linearly, so the lowest point above the zone
+                    // is where the segment enters or leaves it. For polygons and circles,
+                    // bounding rect is used, which may only make this point lower.
+                    // Crossed zone always touches it's bounding rect, so the whole segment
+                    // is only a fallback. Altitudes are compared exactly on raw bits.
+                    let (enter, leave) = line.get_rect_crossing(zone.rect)
+                        .unwrap_or((Fraction::new(0, 1), Fraction::new(1, 1)));
+                    let height = T::Coord::from_u32(zone.height).to_raw();
+                    if is_interpolated_at_most(start_alt, end_alt, enter, height) ||
+                       is_interpolated_at_most(start_alt, end_alt, leave, height) {
+                        crossed_zones.push(zone.zone_id);
+                    }
+                }
+            }
+        }
+        crossed_zones
+    }
+
+    /// Stores end of the temporary zone under the block, which is expected to come after it.
+    /// Blocks are expected every 2 * MinimumPeriod, the zone is scheduled again, if they are slower.
+    fn schedule_expiry(zone_id: ZoneId, end: T::Moment, now: T::Moment, block_number: T::BlockNumber) {
+        let block_time = <T as pallet_timestamp::Config>::MinimumPeriod::get()
+            .saturating_mul(2_u32.into())
+            .max(1_u32.into());
+        let blocks = (end.saturating_sub(now) / block_time).saturated_into::<u32>().max(1);
+        ZonesExpiry::<T>::insert(block_number.saturating_add(blocks.into()), zone_id, end);
+    }
+
+    /// Removes temporary zones, which are over by given moment, up to MaxZonesPrunedPerBlock
+    /// of them. Zones are taken from blocks up to the given one, starting with the zones,
+    /// which were left by the limit before. Zones, which are not over yet, are scheduled again.
+    /// Returns amount of pruned zones and amount of blocks, which zones were read for.
+    fn prune_expired_zones(now: T::Moment, block_number: T::BlockNumber) -> (u32, u32) {
+        let limit = T::MaxZonesPrunedPerBlock::get();
+        let mut block = NextPrunedBlock::<T>::get();
+        let (mut pruned, mut blocks): (u32, u32) = (0, 0);
+        while block <= block_number && pruned < limit && blocks < limit {
+            // One more zone is read to know, if some zones of the block are left by the limit
+            let mut due: Vec<(ZoneId, T::Moment)> = ZonesExpiry::<T>::iter_prefix(block)
+                .take((limit - pruned + 1) as usize)
+                .collect();
+            blocks += 1;
+            let block_is_pruned = due.len() as u32 <= limit - pruned;
+            due.truncate((limit - pruned) as usize);
+            pruned += due.len() as u32;
+            for (zone_id, end) in due {
+                ZonesExpiry::<T>::remove(block, zone_id);
+                // Zone might be removed, and its id given to another zone
+                let zone = RedZones::<T>::get(zone_id);
+                if !RedZones::<T>::contains_key(zone_id) || zone.zone_id != zone_id ||
+                   zone.time_window.map(|(_, zone_end)| zone_end) != Some(end) {
+                    continue;
+                }
+                if end <= now {
+                    Self::remove_zone(&zone);
+                    Self::deposit_event(RawEvent::ZoneExpired(zone_id));
+                } else {
+                    Self::schedule_expiry(zone_id, end, now, block_number);
+                }
+            }
+            if block_is_pruned {
+                block = block.saturating_add(1_u32.into());
+            }
+        }
+        NextPrunedBlock::<T>::put(block);
+        (pruned, blocks)
+    }
+
+    /// True, if removal of the root was started, but it's contents are not cleared yet
+    fn is_being_removed(root_id: RootId) -> bool {
+        RootsToRemove::get().contains(&root_id)
+    }
+
+    /// Clears areas of the first removed root, up to MaxAreasClearedPerBlock of them.
+    /// Zones and routes' index are cleared with the area, then routes of the root are removed,
+    /// and each of them is counted as an area. When nothing is left, the root is removed
+    /// from the spatial index and the storage.
+    /// Returns amount of cleared areas and routes, or None if no root is being removed.
+    fn clear_removed_root() -> Option<u32> {
+        let mut queue = RootsToRemove::get();
+        let root_id = *queue.first()?;
+        let limit = T::MaxAreasClearedPerBlock::get();
+        let mut cleared: u32 = 0;
+        for (area_id, children) in ZonesInArea::drain_prefix(root_id).take(limit as usize) {
+            for child in children {
+                RedZones::<T>::remove(Self::pack_index(root_id, area_id, child));
+            }
+            cleared += 1;
+        }
+        cleared += AreaData::drain_prefix(root_id).take((limit - cleared) as usize).count() as u32;
+        cleared += RoutesInArea::drain_prefix(root_id).take((limit - cleared) as usize).count() as u32;
+        for (route_id, _) in RoutesInRoot::drain_prefix(root_id).take((limit - cleared) as usize) {
+            Routes::<T>::remove(route_id);
+            cleared += 1;
+        }
+        if cleared == limit {
+            // Some areas may be left, they are cleared in the next block
+            return Some(cleared);
+        }
+
+        // Cells of the root can't change during removal, as root_add checks them.
+        // Root, which doesn't fit into the index, was never stored in it.
+        let bounding_box = RootBoxes::<T>::get(root_id).bounding_box;
+        if T::SpatialIndex::check_bounds(bounding_box).is_ok() {
+            let cells = Self::root_cells(bounding_box).unwrap_or_default();
+            T::SpatialIndex::remove(root_id, cells);
+        }
+        RootBoxes::<T>::remove(root_id);
+        queue.remove(0);
+        RootsToRemove::put(queue);
+        Self::deposit_event(RawEvent::RootRemoved(root_id));
+        Some(cleared)
+    }
+
+    /// Removes up to MaxIndexKeysClearedPerBlock keys of the old spatial index, then, when it's empty,
+    /// stores up to MaxRootsIndexedPerBlock roots in the spatial index, which is being rebuilt,
+    /// in order of their keys in RootBoxes, see `migrations::rebuild_index`.
+    /// Roots of the block share the search budget of all of them. Root, which runs out of it,
+    /// is stored on the next block, unless it was the first one, and even the whole budget
+    /// doesn't suffice: then it's kept, but left out of the index. Root, which overlaps another one
+    /// or doesn't fit the current grid and index, is removed.
+    /// Returns weight of stored roots, or None if the index is not being rebuilt.
+    fn index_next_roots() -> Option<Weight> {
+        let mut key = IndexRebuildKey::get()?;
+        let prefix = RootBoxes::<T>::final_prefix();
+        let db_weight = <T as frame_system::Config>::DbWeight::get();
+        let mut weight = db_weight.reads_writes(2, 1);
+        if IndexClearing::get() {
+            // Pages of older layouts can't be decoded, so keys are removed without reading values
+            let limit = T::MaxIndexKeysClearedPerBlock::get();
+            let mut removed = Self::remove_keys(&EarthBitmap::<T>::final_prefix(), limit);
+            removed += Self::remove_keys(&QuadNodes::final_prefix(), limit - removed);
+            weight = weight.saturating_add(db_weight.reads_writes(removed as Weight + 2, removed as Weight));
+            if removed == limit {
+                return Some(weight);
+            }
+            IndexClearing::kill();
+        }
+        let limit = T::MaxRootsIndexedPerBlock::get();
+        let mut budget = T::SpatialIndex::search_budget().saturating_mul(limit);
+        for attempt in 0..limit {
+            let next = match sp_io::storage::next_key(&key) {
+                Some(next) if next.starts_with(&prefix) => next,
+                _ => {
+                    IndexRebuildKey::kill();
+                    return Some(weight);
+                }
+            };
+            let root_id = RootId::decode(&mut Blake2_128Concat::reverse(&next[prefix.len()..]));
+            let root = unhashed::get::<RootBoxOf<T>>(&next);
+            weight = weight.saturating_add(db_weight.reads(2));
+            if let (Ok(root_id), Some(root)) = (root_id, root) {
+                // Roots were checked for overlapping on cells of the old grid and index,
+                // so on the new ones they may overlap, not fit into 16 bit indexes or into the index
+                let indexed = T::SpatialIndex::check_bounds(root.bounding_box)
+                    .and_then(|_| Self::root_cells(root.bounding_box).map_err(|error| Error::<T>::from(error).into()))
+                    .and_then(|cells| T::SpatialIndex::insert(root_id, cells, &mut budget));
+                weight = weight.saturating_add(T::SpatialIndex::insert_weight(root.bounding_box));
+                match indexed {
+                    Err(error) if error == Error::<T>::IndexLimitExceeded.into() => {
+                        if attempt > 0 {
+                            break;
+                        }
+                        Self::deposit_event(RawEvent::RootLeftUnindexed(root_id));
+                    }
+                    Err(_) if !Self::is_being_removed(root_id) => {
+                        RootsToRemove::append(root_id);
+                        Self::deposit_event(RawEvent::RootNotIndexed(root_id));
+                    }
+                    _ => {}
+                }
+            }
+            key = next;
+        }
+        IndexRebuildKey::put(key);
+        Some(weight)
+    }
+
+    /// Removes up to `limit` keys, which start with the prefix. Returns amount of removed keys.
+    fn remove_keys(prefix: &[u8], limit: u32) -> u32 {
+        let mut removed = 0;
+        while removed < limit {
+            match sp_io::storage::next_key(prefix) {
+                Some(key) if key.starts_with(prefix) => unhashed::kill(&key),
+                _ => break,
+            }
+            removed += 1;
+        }
+        removed
+    }
+
+    /// All areas, crossed by any leg of the route, without repeats
+    fn route_areas(root: RootBoxOf<T>, waypoints: &[WaypointOf<T>]) -> Result<Vec<AreaId>, ConversionError> {
+        let mut areas: Vec<AreaId> = Vec::new();
+        for segment in waypoints.windows(2) {
+            let line = Line::<T::Coord, T::BigCoord>::new(segment[0].location.project(),
+                                                          segment[1].location.project());
+            areas.extend(line.get_route_areas(root)?);
+        }
+        areas.sort_unstable();
+        areas.dedup();
+        Ok(areas)
+    }
+
+    /// Areas, which are closer to any of given areas, than the horizontal separation, including them
+    fn separation_areas(root: RootBoxOf<T>, areas: &[AreaId]) -> Result<Vec<AreaId>, ConversionError> {
+        let separation = T::HorizontalSeparation::get();
+        // Degree of longitude is the shortest on the root's border, which is farther from the equator
+        let (south, north) = (root.bounding_box.south_west.lat, root.bounding_box.north_east.lat);
+        let lat = south.abs().max(north.abs());
+        // Separation may end anywhere inside the next area, so one more is taken
+        let rows = T::Coord::metres_to_lat_degrees(separation)
+            .checked_integer_division_u16(root.delta)
+            .unwrap_or(u16::MAX)
+            .saturating_add(1);
+        let columns = T::Coord::metres_to_lon_degrees(separation, lat)
+            .checked_integer_division_u16(root.delta)
+            .unwrap_or(u16::MAX)
+            .saturating_add(1);
+        root.get_surrounding_areas(areas, rows, columns)
+    }
+
+    /// Checks route against all planned and active routes, which are stored in given areas.
+    /// Each of the routes is read once, and counted in `scan`.
+    /// Returns index of each segment, which is too close to some route, with that route's id.
+    /// Also returns routes, which are over or ended before `now` by more than the time separation.
+    /// They can't conflict with any route anymore, so they are not checked.
+    fn conflicting_routes(area_routes: &[(AreaId, Vec<RouteId>)],
+                          waypoints: &[WaypointOf<T>],
+                          now: T::Moment,
+                          scan: &mut RouteScan) -> (Vec<(u32, RouteId)>, Vec<RouteId>) {
+        let mut neighbours: Vec<RouteId> = Vec::new();
+        for (_, routes) in area_routes.iter() {
+            neighbours.extend(routes.iter());
+        }
+        neighbours.sort_unstable();
+        neighbours.dedup();
+        scan.routes = scan.routes.saturating_add(neighbours.len().saturated_into::<u32>());
+
+        let separation = Separation::new(T::HorizontalSeparation::get(),
+                                         T::VerticalSeparation::get(),
+                                         T::TimeSeparation::get().saturated_into::<u64>());
+        // All distances are measured from the start of the new route
+        let reference = waypoints[0].location.project();
+        let legs = Self::to_metric_legs(waypoints, reference);
+        let mut conflicts: Vec<(u32, RouteId)> = Vec::new();
+        let mut finished: Vec<RouteId> = Vec::new();
+        for route_id in neighbours.iter() {
+            let route = Routes::<T>::get(route_id);
+            let end = route.waypoints.last().map(|waypoint| waypoint.arrival).unwrap_or_default();
+            if !route.is_pending() || end.saturating_add(T::TimeSeparation::get()) < now {
+                finished.push(*route_id);
+                continue;
+            }
+            let other_legs = Self::to_metric_legs(&route.waypoints, reference);
+            for (index, leg) in legs.iter().enumerate() {
+                if other_legs.iter().any(|other_leg| separation.legs_conflict(*leg, *other_leg)) {
+                    conflicts.push((index as u32, *route_id));
+                }
+            }
+        }
+        (conflicts, finished)
+    }
+
+    /// Converts route's legs to metres from the reference point
+    fn to_metric_legs(waypoints: &[WaypointOf<T>], reference: Point2D<T::Coord>) -> Vec<MetricLeg> {
+        let metric_waypoints: Vec<MetricWaypoint> = waypoints.iter().map(|waypoint| {
+            let location = waypoint.location;
+            ([(location.lat - reference.lat).lat_degrees_to_metres() as i128,
+              (location.lon - reference.lon).lon_degrees_to_metres(reference.lat) as i128,
+              location.alt.altitude_to_metres() as i128],
+             waypoint.arrival.saturated_into::<u64>() as i128)
+        }).collect();
+        metric_waypoints.windows(2).map(|leg| (leg[0], leg[1])).collect()
+    }
+
+    /// Removes route from the areas' lists, so it's not checked for separation anymore.
+    /// Returns amount of the areas.
+    fn forget_route_areas(route_id: RouteId, route: &RouteOf<T>) -> u32 {
+        let root = RootBoxes::<T>::get(route.root_id);
+        let areas = Self::route_areas(root, &route.waypoints).unwrap_or_default();
+        for area_id in areas.iter() {
+            RoutesInArea::mutate(route.root_id, area_id, |routes| routes.retain(|id| *id != route_id));
+        }
+        areas.len() as u32
     }
 
     /// Form index for storing zones, wrapped in u128............limited by const in runtime
@@ -1868,9 +3665,10 @@ impl<T: Trait> Module<T> {
         children as ZoneId
     }
 
-    /// Reverse function for pack_index()
-    #[allow(dead_code)]
-    fn unpack_index(index: ZoneId) -> (RootId, AreaId, u16) {
+    /// Reverse function for pack_index(). Splits id of the zone's slot into root's id,
+    /// id of the area and the slot's index in the area. Zone, which overlaps several areas,
+    /// has the id of it's slot in the first of them.
+    pub fn unpack_index(index: ZoneId) -> (RootId, AreaId, u16) {
         let mask_u16: u128 = 0x0000_0000_0000_0000_0000_0000_ffff_ffff;
         let root: RootId = (index >> 64) as RootId;
         let area: AreaId = ((index >> 16) & mask_u16) as AreaId;
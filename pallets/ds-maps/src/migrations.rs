@@ -0,0 +1,79 @@
+//! Storage migrations of pallet_ds_maps. Chains, started before versioning, have the baseline
+//! layout, which is brought to the current one by a single migration.
+//! Grid parameters and the kind of the spatial index are set by the runtime,
+//! so the spatial index is rebuilt, whenever they change.
+
+use super::*;
+use frame_support::storage::{IterableStorageMap, StoragePrefixedMap};
+
+/// Migrates the baseline layout, if it's stored, then rebuilds the spatial index,
+/// if the layout was migrated, or the grid or the kind of the index has changed.
+/// Index is rebuilt only once.
+pub fn migrate<T: Trait>() -> Weight {
+    let db_weight = <T as frame_system::Config>::DbWeight::get();
+    let mut weight = db_weight.reads(3);
+    let mut rebuild = IndexedGrid::get() != <T as Grid>::resolution() ||
+                      IndexedKind::get() != Some(T::SpatialIndex::kind());
+    if StorageVersion::get() < STORAGE_VERSION {
+        weight = weight.saturating_add(translate_baseline_zones::<T>());
+        weight = weight.saturating_add(index_area_zones::<T>());
+        // Baseline has no temporary zones, and pruning starts from the current block, not from the genesis
+        NextPrunedBlock::<T>::put(<frame_system::Module<T>>::block_number());
+        // Bitmap pages of the baseline store every cell, and roots' ids are made of legacy cells
+        rebuild = true;
+        StorageVersion::put(STORAGE_VERSION);
+        weight = weight.saturating_add(db_weight.reads_writes(1, 2));
+    }
+    if rebuild {
+        weight = weight.saturating_add(rebuild_index::<T>());
+    }
+    weight
+}
+
+/// Zone of the baseline layout, which has only id, bounding rect and height
+#[derive(Decode)]
+struct BaselineZone<Coord> {
+    zone_id: ZoneId,
+    rect: Rect2D<Coord>,
+    height: LightCoord,
+}
+
+/// Zones of the baseline are stored again as rects without time window,
+/// otherwise they can't be decoded, and are skipped by iteration
+fn translate_baseline_zones<T: Trait>() -> Weight {
+    let mut zones: u64 = 0;
+    RedZones::<T>::translate::<BaselineZone<T::Coord>, _>(|_, zone| {
+        zones += 1;
+        Some(ZoneOf::<T>::new(zone.zone_id, zone.rect, zone.height))
+    });
+    <T as frame_system::Config>::DbWeight::get().reads_writes(zones, zones)
+}
+
+/// Zones of the baseline were looked up by probing area slots, now each area lists occupied ones
+fn index_area_zones<T: Trait>() -> Weight {
+    let mut zones: u64 = 0;
+    for (slot, _) in RedZones::<T>::iter() {
+        let (root_id, area_id, child) = Module::<T>::unpack_index(slot);
+        ZonesInArea::mutate(root_id, area_id, |children| {
+            if let Err(position) = children.binary_search(&child) {
+                children.insert(position, child);
+            }
+        });
+        zones += 1;
+    }
+    <T as frame_system::Config>::DbWeight::get().reads_writes(zones.saturating_mul(2), zones)
+}
+
+/// Old index is removed, and every root is stored again with the current grid and index
+/// in the next blocks, keeping its id, see `Module::index_next_roots`. Roots can't be added until then.
+/// Roots of the baseline have ids of cells, which were counted from the equator and
+/// the prime meridian, not from the south pole and the antimeridian. Their ids are kept too,
+/// as zones and areas are stored by them, so id of a root is only a key, and it doesn't always
+/// give root's cells on the current grid.
+fn rebuild_index<T: Trait>() -> Weight {
+    IndexClearing::put(true);
+    IndexRebuildKey::put(RootBoxes::<T>::final_prefix().to_vec());
+    IndexedGrid::put(<T as Grid>::resolution());
+    IndexedKind::put(T::SpatialIndex::kind());
+    <T as frame_system::Config>::DbWeight::get().writes(4)
+}
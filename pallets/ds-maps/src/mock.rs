@@ -1,11 +1,16 @@
 #![allow(clippy::from_over_into)]
 
 use crate as pallet_ds_maps;
-use crate::Trait;
+use crate::{
+    index::{BitmapIndex, IndexKind, QuadtreeIndex, SpatialIndex},
+    Box3D, GridResolution, RootId, Trait,
+};
 use frame_support::{
-    construct_runtime, parameter_types,
+    construct_runtime, dispatch, parameter_types,
+    traits::Get,
     weights::Weight,
 };
+use std::cell::RefCell;
 use frame_system as system;
 use sp_core::H256;
 use sp_runtime::{
@@ -29,7 +34,7 @@ construct_runtime!(
         Timestamp: pallet_timestamp::{Module, Call, Storage},
         Balances: pallet_balances::{Module, Call, Storage, Config<T>, Event<T>},
         DSAccountsModule: pallet_ds_accounts::{Module, Call, Storage, Event<T>},
-        DSMapsModule: pallet_ds_maps::{Module, Call, Storage, Event<T>},
+        DSMapsModule: pallet_ds_maps::{Module, Call, Storage, Config, Event<T>},
     }
 );
 
@@ -80,30 +85,130 @@ impl pallet_timestamp::Config for Test {
 
 struct WeightInfo;
 impl crate::WeightInfo for WeightInfo {
-    fn root_add() -> Weight {
-        <() as crate::WeightInfo>::root_add()
+    fn root_add(pages: u32) -> Weight {
+        <() as crate::WeightInfo>::root_add(pages)
     }
-    fn zone_add() -> Weight {
-        <() as crate::WeightInfo>::zone_add()
+    fn zone_add(areas: u32, zones: u32) -> Weight {
+        <() as crate::WeightInfo>::zone_add(areas, zones)
     }
     fn root_remove() -> Weight {
         <() as crate::WeightInfo>::root_remove()
     }
-    fn zone_remove() -> Weight {
-        <() as crate::WeightInfo>::zone_remove()
+    fn zone_remove(areas: u32, zones: u32) -> Weight {
+        <() as crate::WeightInfo>::zone_remove(areas, zones)
     }
     fn change_area_type() -> Weight {
         <() as crate::WeightInfo>::change_area_type()
     }
-    fn route_add() -> Weight {
-        <() as crate::WeightInfo>::route_add()
+    fn route_add(waypoints: u32, areas: u32, zones: u32, routes: u32) -> Weight {
+        <() as crate::WeightInfo>::route_add(waypoints, areas, zones, routes)
+    }
+    fn change_route_status(areas: u32) -> Weight {
+        <() as crate::WeightInfo>::change_route_status(areas)
+    }
+    fn prune_zones(expired: u32, areas: u32, zones: u32) -> Weight {
+        <() as crate::WeightInfo>::prune_zones(expired, areas, zones)
+    }
+    fn clear_root(areas: u32, zones: u32) -> Weight {
+        <() as crate::WeightInfo>::clear_root(areas, zones)
+    }
+}
+
+/// Cells of 0.01 degree, in pages of 0.32 x 0.5 degree
+pub const DEFAULT_GRID: GridResolution = GridResolution {
+    cell_length: 1,
+    cell_width: 1,
+    cell_size_degree: 2,
+    page_length: 32,
+    page_width: 50,
+};
+
+thread_local! {
+    static INDEX_KIND: RefCell<IndexKind> = RefCell::new(IndexKind::Bitmap);
+    static GRID: RefCell<GridResolution> = RefCell::new(DEFAULT_GRID);
+}
+
+/// Spatial index of the mock runtime. Tests choose it with new_test_ext_with_index(),
+/// so the pallet is tested with every index, and the runtime may use any of them.
+pub struct TestIndex;
+
+macro_rules! with_index {
+    ($function:ident($($argument:expr),*)) => {
+        match Self::kind() {
+            IndexKind::Bitmap => BitmapIndex::<Test>::$function($($argument),*),
+            IndexKind::Quadtree => QuadtreeIndex::<Test>::$function($($argument),*),
+        }
+    };
+}
+
+impl SpatialIndex<Test> for TestIndex {
+    fn kind() -> IndexKind {
+        INDEX_KIND.with(|kind| *kind.borrow())
+    }
+    fn check_bounds(bounding_box: Box3D<I10F22>) -> dispatch::DispatchResult {
+        with_index!(check_bounds(bounding_box))
+    }
+    fn search_budget() -> u32 {
+        with_index!(search_budget())
+    }
+    fn insert(root_id: RootId, cells: [u32; 4], budget: &mut u32) -> dispatch::DispatchResult {
+        with_index!(insert(root_id, cells, budget))
     }
+    fn remove(root_id: RootId, cells: [u32; 4]) {
+        with_index!(remove(root_id, cells))
+    }
+    fn find(row: u32, column: u32) -> RootId {
+        with_index!(find(row, column))
+    }
+    fn insert_weight(bounding_box: Box3D<I10F22>) -> Weight {
+        with_index!(insert_weight(bounding_box))
+    }
+    fn remove_weight() -> Weight {
+        with_index!(remove_weight())
+    }
+}
+
+/// Makes the mock runtime use another spatial index, as a runtime upgrade would do
+pub fn set_index_kind(kind: IndexKind) {
+    INDEX_KIND.with(|index_kind| *index_kind.borrow_mut() = kind);
+}
+
+/// Grid parameter of the mock runtime. Tests choose the grid with new_test_ext_with_grid().
+macro_rules! grid_parameter {
+    ($name:ident: $type:ty = $field:ident) => {
+        pub struct $name;
+
+        impl Get<$type> for $name {
+            fn get() -> $type {
+                GRID.with(|grid| grid.borrow().$field)
+            }
+        }
+    };
 }
 
+grid_parameter!(BitmapCellLength: u32 = cell_length);
+grid_parameter!(BitmapCellWidth: u32 = cell_width);
+grid_parameter!(CellSizeDegree: u8 = cell_size_degree);
+grid_parameter!(PageLength: u32 = page_length);
+grid_parameter!(PageWidth: u32 = page_width);
+
 // After researches, consider placing here max grid sizes
 parameter_types! {
     pub const MaxHeight: u16 = 400;
     pub const MaxBuildingsInArea: u16 = 2;
+    pub const MaxAreasPerZone: u32 = 4;
+    pub const MaxWaypoints: u32 = 8;
+    pub const MaxPolygonVertices: u32 = 8;
+    pub const HorizontalSeparation: u32 = 50;
+    pub const VerticalSeparation: u32 = 10;
+    pub const TimeSeparation: u64 = 60;
+    pub const MaxRoutesInArea: u32 = 3;
+    pub const MaxAreasPerRoute: u32 = 1_000;
+    pub const MaxAreasClearedPerBlock: u32 = 2;
+    pub const MaxZonesPrunedPerBlock: u32 = 2;
+    pub const MaxRootsIndexedPerBlock: u32 = 2;
+    pub const MaxIndexKeysClearedPerBlock: u32 = 16;
+    pub const MaxPagesAmountToExtract: u32 = 4;
 }
 
 impl Trait for Test {
@@ -114,6 +219,25 @@ impl Trait for Test {
     type RawCoord = i32;
     type MaxBuildingsInArea = MaxBuildingsInArea;
     type MaxHeight = MaxHeight;
+    type MaxPolygonVertices = MaxPolygonVertices;
+    type MaxAreasPerZone = MaxAreasPerZone;
+    type MaxWaypoints = MaxWaypoints;
+    type HorizontalSeparation = HorizontalSeparation;
+    type VerticalSeparation = VerticalSeparation;
+    type TimeSeparation = TimeSeparation;
+    type MaxRoutesInArea = MaxRoutesInArea;
+    type MaxAreasPerRoute = MaxAreasPerRoute;
+    type MaxAreasClearedPerBlock = MaxAreasClearedPerBlock;
+    type MaxZonesPrunedPerBlock = MaxZonesPrunedPerBlock;
+    type MaxRootsIndexedPerBlock = MaxRootsIndexedPerBlock;
+    type MaxIndexKeysClearedPerBlock = MaxIndexKeysClearedPerBlock;
+    type SpatialIndex = TestIndex;
+    type BitmapCellLength = BitmapCellLength;
+    type BitmapCellWidth = BitmapCellWidth;
+    type CellSizeDegree = CellSizeDegree;
+    type PageLength = PageLength;
+    type PageWidth = PageWidth;
+    type MaxPagesAmountToExtract = MaxPagesAmountToExtract;
 }
 
 parameter_types! {
@@ -151,6 +275,23 @@ static INITIAL: [(
 ); 1] = [(1, ADMIN_ROLE)];
 
 pub fn new_test_ext() -> sp_io::TestExternalities {
+    new_test_ext_with_map(Default::default())
+}
+
+/// Same as new_test_ext(), with roots kept in the given spatial index
+pub fn new_test_ext_with_index(kind: IndexKind) -> sp_io::TestExternalities {
+    set_index_kind(kind);
+    new_test_ext()
+}
+
+/// Same as new_test_ext(), with another grid of the bitmap
+pub fn new_test_ext_with_grid(grid: GridResolution) -> sp_io::TestExternalities {
+    GRID.with(|test_grid| *test_grid.borrow_mut() = grid);
+    new_test_ext()
+}
+
+/// Same as new_test_ext(), with roots, zones and area types, added at genesis
+pub fn new_test_ext_with_map(map: pallet_ds_maps::GenesisConfig) -> sp_io::TestExternalities {
     let mut storage = system::GenesisConfig::default()
         .build_storage::<Test>()
         .unwrap();
@@ -172,6 +313,7 @@ pub fn new_test_ext() -> sp_io::TestExternalities {
         }
         .assimilate_storage(&mut storage)
         .unwrap();
-    
+    map.assimilate_storage::<Test>(&mut storage).unwrap();
+
     storage.into()
 }
\ No newline at end of file
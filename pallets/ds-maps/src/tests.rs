@@ -3,12 +3,19 @@ use crate::{
             Page,
             Point3D, Box3D,
             Point2D, Rect2D,
-            Waypoint,
+            Waypoint, ZoneShape,
+            RouteStatus, RouteViolation,
+            index::{IndexKind, SpatialIndex},
 };
 use frame_support::{
     assert_noop, assert_ok,
+    codec::Encode,
+    dispatch::DispatchResult,
+    storage::{unhashed, IterableStorageDoubleMap, IterableStorageMap, StorageDoubleMap, StorageMap,
+              StoragePrefixedMap, StorageValue},
+    traits::{IntegrityTest, OnInitialize, OnRuntimeUpgrade},
 };
-use substrate_fixed::types::I10F22;
+use substrate_fixed::types::{I10F22, I42F22};
 use sp_std::str::FromStr;
 
 // Explanation for all hardcoded values down here
@@ -55,18 +62,33 @@ use sp_std::str::FromStr;
 type Error = super::Error<Test>;
 // TODO find out how to connect this types w mock
 pub type Coord = I10F22;
+pub type BigCoord = I42F22;
 type Moment = u64;
 
 // Constants to make tests more readable
 const ADMIN_ACCOUNT_ID: u64 = 1;
 const REGISTRAR_1_ACCOUNT_ID: u64 = 2;
-pub const ROOT_ID: u64 = 0b0001_0101_1010_0001_0000_1110_1001_1001_0001_0101_1101_1000_0000_1110_1100_1110;
+const REGISTRAR_2_ACCOUNT_ID: u64 = 3;
+const PILOT_1_ACCOUNT_ID: u64 = 4;
+pub const ROOT_ID: u64 = 0b0011_1000_1110_0001_0101_0101_0001_1011_0011_1001_0001_1000_0101_0101_0101_0000;
 // Values in construct_testing_..() pre-calculated
 // construct_custom_..() same functionality, but custom numbers
 // These consts also pre-calculated
 
 const AREA_ID: u16 = 58;
 const DEFAULT_HEIGHT: u32 = 30;
+// Area limit of route_add, which is enough for any route in the testing root
+const ROUTE_AREAS: u32 = 10_000;
+// Runtime may store roots in any of them, so tests of roots are run with each one
+const INDEX_KINDS: [IndexKind; 2] = [IndexKind::Bitmap, IndexKind::Quadtree];
+// Cells of 0.02 x 0.05 degree, in pages of 0.64 x 1 degree
+const COARSE_GRID: crate::GridResolution = crate::GridResolution {
+    cell_length: 2,
+    cell_width: 5,
+    cell_size_degree: 2,
+    page_length: 32,
+    page_width: 20,
+};
 
 pub const DELTA: &str = "0.01";
 
@@ -141,6 +163,34 @@ pub fn construct_custom_waypoints(start_lat: &str, start_lon: &str,
     vec![start_wp, end_wp]
 }
 
+// Same as above, but for any amount of waypoints: [lat, lon, alt] and arrival time
+pub fn construct_route(points: &[([&str; 3], u64)]) -> Vec<Waypoint<Coord, Moment>> {
+    points.iter()
+        .map(|([lat, lon, alt], arrival)| {
+            let location = Point3D::new(coord(lat), coord(lon), coord(alt));
+            Waypoint::new(location, *arrival)
+        })
+        .collect()
+}
+
+// Runs blocks, until all removed roots are cleared
+pub fn clear_removed_roots() {
+    let mut block = 1;
+    while !DSMapsModule::roots_to_remove().is_empty() {
+        DSMapsModule::on_initialize(block);
+        block += 1;
+    }
+}
+
+/// Runs blocks, until all roots are stored in the rebuilt spatial index
+pub fn rebuild_index() {
+    let mut block = 1;
+    while DSMapsModule::index_rebuild_key().is_some() {
+        DSMapsModule::on_initialize(block);
+        block += 1;
+    }
+}
+
 #[test]
 fn it_try_to_add_root_unauthorized() {
     new_test_ext().execute_with(|| {
@@ -196,11 +246,11 @@ fn it_try_to_add_raw_root_with_exceeded_page_limit() {
                 super::REGISTRAR_ROLE
           ));
           let raw_coords: [i32; 6] = [
-              465587600,
-              312529919,
+              46556774,
+              312475648,
               8388608,
-              469815744,
-              318558719,
+              50751078,
+              318557389,
               16777216
           ];
           let delta: i32 = 838860;
@@ -230,7 +280,7 @@ fn it_try_to_add_too_big_root() {
         assert_noop!(
             DSMapsModule::root_add(
                 Origin::signed(REGISTRAR_1_ACCOUNT_ID),
-                construct_custom_box("0.0", "0.0", "250.0", "250.0",),
+                construct_custom_box("-45.0", "-50.0", "45.0", "50.0"),
                 coord(DELTA),
             ),
             Error::PageLimitExceeded
@@ -271,6 +321,59 @@ fn it_try_to_add_root_with_incorrect_coordinates() {
             ),
             Error::InvalidCoords
         );
+        assert_noop!(
+            DSMapsModule::root_add(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                construct_custom_box("0.1", "50.9", "0.0", "51.0"),
+                coord(DELTA),
+            ),
+            Error::InvalidCoords
+        );
+        assert_noop!(
+            DSMapsModule::root_add(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                construct_custom_box("0.0", "-180.1", "0.1", "-179.9"),
+                coord(DELTA),
+            ),
+            Error::InvalidCoords
+        );
+    });
+}
+
+#[test]
+fn it_try_to_add_root_with_bad_delta() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(
+            DSAccountsModule::account_add(
+                Origin::signed(ADMIN_ACCOUNT_ID),
+                REGISTRAR_1_ACCOUNT_ID,
+                super::REGISTRAR_ROLE
+        ));
+        // 1000x1000 areas don't fit into AreaId
+        assert_noop!(
+            DSMapsModule::root_add(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                construct_custom_box("0.0", "0.0", "1.0", "1.0"),
+                coord("0.001"),
+            ),
+            Error::CoordOverflow
+        );
+        assert_noop!(
+            DSMapsModule::root_add(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                construct_custom_box("0.0", "0.0", "1.0", "1.0"),
+                coord("0.0"),
+            ),
+            Error::BadDimesions
+        );
+        assert_noop!(
+            DSMapsModule::root_add(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                construct_custom_box("0.0", "0.0", "1.0", "1.0"),
+                coord("-0.1"),
+            ),
+            Error::InvalidCoords
+        );
     });
 }
 
@@ -292,15 +395,15 @@ fn it_try_to_add_root_as_square_2x2() {
                 super::REGISTRAR_ROLE
         ));
 
-        let bounding_box = construct_custom_box("0.051", "0.0", "0.5", "0.75");
-        let (sw_cell_row_index, sw_cell_column_index) = Page::get_cell_indexes(bounding_box.south_west);
-        assert_eq!(sw_cell_row_index, 5);
-        assert_eq!(sw_cell_column_index, 0);
-        let (ne_cell_row_index, ne_cell_column_index) = Page::get_cell_indexes(bounding_box.north_east);
-        assert_eq!(ne_cell_row_index, 50);
-        assert_eq!(ne_cell_column_index, 75);
+        let bounding_box = construct_custom_box("0.051", "0.011", "0.5", "0.75");
+        let (sw_cell_row_index, sw_cell_column_index) = Page::get_cell_indexes(bounding_box.south_west).unwrap();
+        assert_eq!(sw_cell_row_index, 9029);
+        assert_eq!(sw_cell_column_index, 18051);
+        let (ne_cell_row_index, ne_cell_column_index) = Page::get_cell_indexes(bounding_box.north_east).unwrap();
+        assert_eq!(ne_cell_row_index, 9074);
+        assert_eq!(ne_cell_column_index, 18125);
 
-        let amount_of_pages_to_extract = Page::get_amount_of_pages_to_extract_using_box(bounding_box);
+        let amount_of_pages_to_extract = Page::get_amount_of_pages_to_extract_using_box(bounding_box).unwrap();
         assert_eq!(amount_of_pages_to_extract, 4);
 
         assert_ok!(
@@ -327,15 +430,15 @@ fn it_try_to_add_root_as_rectangle_4x1() {
                 super::REGISTRAR_ROLE
         ));
 
-        let bounding_box = construct_custom_box("0.051", "0.0", "1.271", "0.011");
-        let (sw_cell_row_index, sw_cell_column_index) = Page::get_cell_indexes(bounding_box.south_west);
-        assert_eq!(sw_cell_row_index, 5);
-        assert_eq!(sw_cell_column_index, 0);
-        let (ne_cell_row_index, ne_cell_column_index) = Page::get_cell_indexes(bounding_box.north_east);
-        assert_eq!(ne_cell_row_index, 127);
-        assert_eq!(ne_cell_column_index, 1);
+        let bounding_box = construct_custom_box("0.051", "0.011", "1.271", "0.021");
+        let (sw_cell_row_index, sw_cell_column_index) = Page::get_cell_indexes(bounding_box.south_west).unwrap();
+        assert_eq!(sw_cell_row_index, 9029);
+        assert_eq!(sw_cell_column_index, 18051);
+        let (ne_cell_row_index, ne_cell_column_index) = Page::get_cell_indexes(bounding_box.north_east).unwrap();
+        assert_eq!(ne_cell_row_index, 9151);
+        assert_eq!(ne_cell_column_index, 18052);
 
-        let amount_of_pages_to_extract = Page::get_amount_of_pages_to_extract_using_box(bounding_box);
+        let amount_of_pages_to_extract = Page::get_amount_of_pages_to_extract_using_box(bounding_box).unwrap();
         assert_eq!(amount_of_pages_to_extract, 4);
 
         assert_ok!(
@@ -371,15 +474,15 @@ fn it_try_to_add_root_as_rectangle_1x4() {
                 super::REGISTRAR_ROLE
         ));
 
-        let bounding_box = construct_custom_box("0.0", "0.0", "0.011", "1.751");
-        let (sw_cell_row_index, sw_cell_column_index) = Page::get_cell_indexes(bounding_box.south_west);
-        assert_eq!(sw_cell_row_index, 0);
-        assert_eq!(sw_cell_column_index, 0);
-        let (ne_cell_row_index, ne_cell_column_index) = Page::get_cell_indexes(bounding_box.north_east);
-        assert_eq!(ne_cell_row_index, 1);
-        assert_eq!(ne_cell_column_index, 175);
+        let bounding_box = construct_custom_box("0.011", "0.011", "0.021", "1.751");
+        let (sw_cell_row_index, sw_cell_column_index) = Page::get_cell_indexes(bounding_box.south_west).unwrap();
+        assert_eq!(sw_cell_row_index, 9025);
+        assert_eq!(sw_cell_column_index, 18051);
+        let (ne_cell_row_index, ne_cell_column_index) = Page::get_cell_indexes(bounding_box.north_east).unwrap();
+        assert_eq!(ne_cell_row_index, 9026);
+        assert_eq!(ne_cell_column_index, 18225);
 
-        let amount_of_pages_to_extract = Page::get_amount_of_pages_to_extract_using_box(bounding_box);
+        let amount_of_pages_to_extract = Page::get_amount_of_pages_to_extract_using_box(bounding_box).unwrap();
         assert_eq!(amount_of_pages_to_extract, 4);
 
         assert_ok!(
@@ -413,11 +516,330 @@ fn it_try_to_remove_root() {
                 Origin::signed(REGISTRAR_1_ACCOUNT_ID),
                 ROOT_ID,
         ));
+        clear_removed_roots();
         let root = DSMapsModule::root_box_data(ROOT_ID);
         assert!(!root.is_active());
     });
 }
 
+#[test]
+fn it_remove_root_over_several_blocks() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        assert_ok!(
+            DSAccountsModule::account_add(
+                Origin::signed(ADMIN_ACCOUNT_ID),
+                REGISTRAR_1_ACCOUNT_ID,
+                super::REGISTRAR_ROLE
+        ));
+        assert_ok!(
+            DSMapsModule::root_add(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                construct_testing_box(),
+                coord(DELTA),
+        ));
+        // Zones in 3 areas, so 6 entries are cleared, 2 per block
+        for rect in [construct_testing_rect(),
+                     construct_custom_rect("55.372", "37.372", "55.373", "37.373"),
+                     construct_custom_rect("55.382", "37.372", "55.383", "37.373")].iter() {
+            assert_ok!(
+                DSMapsModule::zone_add(
+                    Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                    *rect,
+                    DEFAULT_HEIGHT,
+                    ROOT_ID,
+            ));
+        }
+        assert_ok!(
+            DSMapsModule::root_remove(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                ROOT_ID,
+        ));
+        // Root can't be used or removed again, while it's being removed
+        assert!(DSMapsModule::root_box_data(ROOT_ID).is_active());
+        let waypoints = construct_custom_waypoints("55.373", "37.373", "55.396", "37.386", 10, 20);
+        assert_noop!(
+            DSMapsModule::route_add(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                waypoints.clone(),
+                ROOT_ID,
+                ROUTE_AREAS,
+            ),
+            Error::RootIsBeingRemoved
+        );
+        assert_eq!(DSMapsModule::validate_route(&waypoints, ROOT_ID).violations,
+                   vec![RouteViolation::RootIsBeingRemoved]);
+        assert_noop!(
+            DSMapsModule::zone_add(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                construct_custom_rect("55.392", "37.372", "55.393", "37.373"),
+                DEFAULT_HEIGHT,
+                ROOT_ID,
+            ),
+            Error::RootIsBeingRemoved
+        );
+        assert_noop!(
+            DSMapsModule::root_remove(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                ROOT_ID,
+            ),
+            Error::RootIsBeingRemoved
+        );
+        // Cells stay occupied, until the root is cleared
+        assert_noop!(
+            DSMapsModule::root_add(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                construct_testing_box(),
+                coord(DELTA),
+            ),
+            Error::OverlappingRoot
+        );
+
+        for block in 2..5 {
+            DSMapsModule::on_initialize(block);
+            assert!(DSMapsModule::root_box_data(ROOT_ID).is_active());
+        }
+        assert!(DSMapsModule::get_area_zones(ROOT_ID, AREA_ID).is_empty());
+        DSMapsModule::on_initialize(5);
+        assert!(!DSMapsModule::root_box_data(ROOT_ID).is_active());
+        assert!(DSMapsModule::roots_to_remove().is_empty());
+        assert_eq!(DSMapsModule::get_area_type(ROOT_ID, AREA_ID), None);
+        assert_eq!(DSMapsModule::get_root_index([232343470, 156804055]), 0);
+        let removed = Event::pallet_ds_maps(super::RawEvent::RootRemoved(ROOT_ID));
+        assert!(System::events().iter().any(|record| record.event == removed));
+
+        assert_ok!(
+            DSMapsModule::root_add(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                construct_testing_box(),
+                coord(DELTA),
+        ));
+    });
+}
+
+#[test]
+fn it_remove_routes_and_zones_only_with_root() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(
+            DSAccountsModule::account_add(
+                Origin::signed(ADMIN_ACCOUNT_ID),
+                REGISTRAR_1_ACCOUNT_ID,
+                super::REGISTRAR_ROLE
+        ));
+        assert_ok!(
+            DSMapsModule::root_add(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                construct_testing_box(),
+                coord(DELTA),
+        ));
+        assert_ok!(
+            DSMapsModule::zone_add(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                construct_custom_rect("55.372", "37.372", "55.373", "37.373"),
+                DEFAULT_HEIGHT,
+                ROOT_ID,
+        ));
+        // Cancelled route is kept with the root too
+        for route_id in 0..2 {
+            assert_ok!(
+                DSMapsModule::route_add(
+                    Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                    construct_testing_waypoints(),
+                    ROOT_ID,
+                    ROUTE_AREAS,
+            ));
+            if route_id == 0 {
+                assert_ok!(DSMapsModule::route_cancel(Origin::signed(REGISTRAR_1_ACCOUNT_ID), route_id));
+            }
+        }
+        assert_ok!(
+            DSMapsModule::root_remove(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                ROOT_ID,
+        ));
+
+        // Zones and routes of the root are not changed, while it's being removed
+        let zone_id = DSMapsModule::pack_index(ROOT_ID, 1, 0);
+        assert_noop!(
+            DSMapsModule::zone_remove(Origin::signed(REGISTRAR_1_ACCOUNT_ID), zone_id),
+            Error::RootIsBeingRemoved
+        );
+        assert_noop!(
+            DSMapsModule::route_activate(Origin::signed(REGISTRAR_1_ACCOUNT_ID), 1),
+            Error::RootIsBeingRemoved
+        );
+        assert_noop!(
+            DSMapsModule::route_cancel(Origin::signed(REGISTRAR_1_ACCOUNT_ID), 1),
+            Error::RootIsBeingRemoved
+        );
+
+        clear_removed_roots();
+        assert!(!super::Routes::<Test>::contains_key(0));
+        assert!(!super::Routes::<Test>::contains_key(1));
+        assert_eq!(super::RoutesInRoot::iter_prefix(ROOT_ID).count(), 0);
+        assert_noop!(
+            DSMapsModule::route_cancel(Origin::signed(REGISTRAR_1_ACCOUNT_ID), 1),
+            Error::RouteDoesNotExist
+        );
+    });
+}
+
+#[test]
+fn it_add_root_in_southern_and_western_hemispheres() {
+    for kind in INDEX_KINDS.iter() {
+        new_test_ext_with_index(*kind).execute_with(|| {
+            assert_ok!(
+                DSAccountsModule::account_add(
+                    Origin::signed(ADMIN_ACCOUNT_ID),
+                    REGISTRAR_1_ACCOUNT_ID,
+                    super::REGISTRAR_ROLE
+            ));
+            // Sao Paulo
+            let bounding_box = construct_custom_box("-23.7", "-46.9", "-23.4", "-46.4");
+            let (sw_cell_row_index, sw_cell_column_index) = Page::get_cell_indexes(bounding_box.south_west).unwrap();
+            assert_eq!(sw_cell_row_index, 6653);
+            assert_eq!(sw_cell_column_index, 13359);
+            assert_ok!(
+                DSMapsModule::root_add(
+                    Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                    bounding_box,
+                    coord(DELTA),
+            ));
+            // -23.55 - -98775859
+            // -46.63 - -195580396
+            let root_id = DSMapsModule::get_root_index([-98775859, -195580396]);
+            assert_eq!(root_id, 1872710396962092129);
+            assert!(DSMapsModule::root_box_data(root_id).is_active());
+
+            assert_ok!(
+                DSMapsModule::zone_add(
+                    Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                    construct_custom_rect("-23.555", "-46.635", "-23.551", "-46.631"),
+                    DEFAULT_HEIGHT,
+                    root_id
+            ));
+
+            assert_ok!(
+                DSMapsModule::root_remove(
+                    Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                    root_id,
+            ));
+            clear_removed_roots();
+            assert_eq!(DSMapsModule::get_root_index([-98775859, -195580396]), 0);
+        });
+    }
+}
+
+#[test]
+fn it_add_roots_near_the_poles() {
+    for kind in INDEX_KINDS.iter() {
+        new_test_ext_with_index(*kind).execute_with(|| {
+            assert_ok!(
+                DSAccountsModule::account_add(
+                    Origin::signed(ADMIN_ACCOUNT_ID),
+                    REGISTRAR_1_ACCOUNT_ID,
+                    super::REGISTRAR_ROLE
+            ));
+            assert_ok!(
+                DSMapsModule::root_add(
+                    Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                    construct_custom_box("89.7", "10.0", "90.0", "10.4"),
+                    coord(DELTA),
+            ));
+            assert_ok!(
+                DSMapsModule::root_add(
+                    Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                    construct_custom_box("-90.0", "10.0", "-89.7", "10.4"),
+                    coord(DELTA),
+            ));
+            assert_noop!(
+                DSMapsModule::root_add(
+                    Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                    construct_custom_box("89.7", "10.0", "90.1", "10.4"),
+                    coord(DELTA),
+                ),
+                Error::InvalidCoords
+            );
+
+            // 89.9 - 377067930, -89.9 - -377067930
+            // 10.2 - 42781901
+            let north_root_id = DSMapsModule::get_root_index([377067930, 42781901]);
+            assert_eq!(north_root_id, 5064942551239772818);
+            assert!(DSMapsModule::root_box_data(north_root_id).is_active());
+            let south_root_id = DSMapsModule::get_root_index([-377067930, 42781901]);
+            assert_eq!(south_root_id, 6837218571537042);
+            assert!(DSMapsModule::root_box_data(south_root_id).is_active());
+        });
+    }
+}
+
+#[test]
+fn it_add_root_crossing_the_antimeridian() {
+    for kind in INDEX_KINDS.iter() {
+        new_test_ext_with_index(*kind).execute_with(|| {
+            assert_ok!(
+                DSAccountsModule::account_add(
+                    Origin::signed(ADMIN_ACCOUNT_ID),
+                    REGISTRAR_1_ACCOUNT_ID,
+                    super::REGISTRAR_ROLE
+            ));
+            // Longitude continues above 180 degrees inside the root
+            let bounding_box = construct_custom_box("65.0", "179.8", "65.2", "180.2");
+            let (_, sw_cell_column_index) = Page::get_cell_indexes(bounding_box.south_west).unwrap();
+            assert_eq!(sw_cell_column_index, 36029);
+            let (_, ne_cell_column_index) = Page::get_cell_indexes(bounding_box.north_east).unwrap();
+            assert_eq!(ne_cell_column_index, 70);
+            assert_ok!(
+                DSMapsModule::root_add(
+                    Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                    bounding_box,
+                    coord(DELTA),
+            ));
+
+            // 65.1 - 273049190
+            // 179.9 - 754555290
+            let root_id = DSMapsModule::get_root_index([273049190, 754555290]);
+            assert_eq!(root_id, 4369772282851622982);
+            assert_eq!(DSMapsModule::get_root_index([273049190, -754555290]), root_id);
+            assert!(DSMapsModule::root_box_data(root_id).is_active());
+
+            // Zone east of the antimeridian
+            assert_ok!(
+                DSMapsModule::zone_add(
+                    Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                    construct_custom_rect("65.101", "180.051", "65.105", "180.055"),
+                    DEFAULT_HEIGHT,
+                    root_id
+            ));
+
+            let overlapping_box = construct_custom_box("65.05", "-179.9", "65.15", "-179.7");
+            assert_noop!(
+                DSMapsModule::root_add(
+                    Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                    overlapping_box,
+                    coord(DELTA),
+                ),
+                Error::OverlappingRoot
+            );
+
+            assert_ok!(
+                DSMapsModule::root_remove(
+                    Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                    root_id,
+            ));
+            clear_removed_roots();
+            assert_eq!(DSMapsModule::get_root_index([273049190, 754555290]), 0);
+            assert_eq!(DSMapsModule::get_root_index([273049190, -754555290]), 0);
+            assert_ok!(
+                DSMapsModule::root_add(
+                    Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                    overlapping_box,
+                    coord(DELTA),
+            ));
+        });
+    }
+}
+
 #[test]
 fn it_try_to_add_zone_unauthorized() {
     new_test_ext().execute_with(|| {
@@ -760,6 +1182,33 @@ fn it_change_existing_area_type() {
 
 #[test]
 fn it_dispatchable_get_root_index() {
+    for kind in INDEX_KINDS.iter() {
+        new_test_ext_with_index(*kind).execute_with(|| {
+            assert_ok!(
+                DSAccountsModule::account_add(
+                    Origin::signed(ADMIN_ACCOUNT_ID),
+                    REGISTRAR_1_ACCOUNT_ID,
+                    super::REGISTRAR_ROLE
+            ));
+            assert_ok!(
+                DSMapsModule::root_add(
+                    Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                    construct_testing_box(),
+                    coord(DELTA),
+            ));
+            // 55.395 - 232343470
+            // 37.385 - 156804055
+            let root_id = DSMapsModule::get_root_index([232343470, 156804055]);
+            assert_eq!(root_id, 4098650711294235984);
+            // Proof, that everything is right: by this index we get active root
+            let root = DSMapsModule::root_box_data(root_id);
+            assert!(root.is_active());
+        });
+    }
+}
+
+#[test]
+fn it_dispatchable_get_zones_and_areas() {
     new_test_ext().execute_with(|| {
         assert_ok!(
             DSAccountsModule::account_add(
@@ -773,13 +1222,37 @@ fn it_dispatchable_get_root_index() {
                 construct_testing_box(),
                 coord(DELTA),
         ));
-        // 55.395 - 232343470
-        // 37.385 - 156804055
-        let root_id = DSMapsModule::get_root_index([232343470, 156804055]);
-        assert_eq!(root_id, 1558542996706168526);
-        // Proof, that everything is right: by this index we get active root
-        let root = DSMapsModule::root_box_data(root_id);
-        assert!(root.is_active());
+        assert_ok!(
+            DSMapsModule::zone_add(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                construct_testing_rect(),
+                DEFAULT_HEIGHT,
+                ROOT_ID
+        ));
+        // This one lies in areas 58 and 59
+        assert_ok!(
+            DSMapsModule::zone_add(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                construct_custom_rect("55.399", "37.385", "55.403", "37.386"),
+                DEFAULT_HEIGHT,
+                ROOT_ID
+        ));
+        assert_eq!(DSMapsModule::get_area_zones(ROOT_ID, AREA_ID).len(), 2);
+        assert_eq!(DSMapsModule::get_area_zones(ROOT_ID, AREA_ID + 1).len(), 1);
+        assert!(DSMapsModule::get_area_zones(ROOT_ID, AREA_ID + 2).is_empty());
+
+        // 55.392 - 232330887, 37.382 - 156791472
+        // 55.408 - 232397996, 37.389 - 156820832
+        let zones = DSMapsModule::get_rect_zones(ROOT_ID, [232330887, 156791472, 232397996, 156820832]);
+        assert_eq!(zones.len(), 2);
+        // Rect outside of the root
+        assert!(DSMapsModule::get_rect_zones(ROOT_ID, [419430, 419430, 838861, 838861]).is_empty());
+        assert!(DSMapsModule::get_rect_zones(1, [232330887, 156791472, 232397996, 156820832]).is_empty());
+
+        assert_eq!(DSMapsModule::get_area_type(ROOT_ID, AREA_ID), Some(super::GREEN_AREA));
+        assert_eq!(DSMapsModule::get_area_type(ROOT_ID, AREA_ID + 2), None);
+        assert_eq!(DSMapsModule::get_max_area(ROOT_ID), 2915);
+        assert_eq!(DSMapsModule::get_max_area(1), 0);
     });
 }
 
@@ -805,6 +1278,7 @@ fn it_add_route_by_registrar() {
                 Origin::signed(REGISTRAR_1_ACCOUNT_ID),
                 waypoints,
                 ROOT_ID,
+                ROUTE_AREAS,
         ));
     });
 }
@@ -830,6 +1304,7 @@ fn it_add_route_wrong_root() {
                 Origin::signed(REGISTRAR_1_ACCOUNT_ID),
                 waypoints,
                 ROOT_ID + 1,
+                ROUTE_AREAS,
             ),
             Error::RootDoesNotExist
         );
@@ -861,6 +1336,7 @@ fn it_add_route_wrong_timestamps() {
                 Origin::signed(REGISTRAR_1_ACCOUNT_ID),
                 waypoints,
                 ROOT_ID,
+                ROUTE_AREAS,
             ), 
             Error::WrongTimeSupplied
         );
@@ -891,6 +1367,7 @@ fn it_add_route_wrong_waypoints() {
                 Origin::signed(REGISTRAR_1_ACCOUNT_ID),
                 single_waypoint,
                 ROOT_ID,
+                ROUTE_AREAS,
             ), 
             Error::InvalidData
         );
@@ -904,6 +1381,7 @@ fn it_add_route_wrong_waypoints() {
                 Origin::signed(REGISTRAR_1_ACCOUNT_ID),
                 waypoints,
                 ROOT_ID,
+                ROUTE_AREAS,
             ), 
             Error::RouteDoesNotFitToRoot
         );
@@ -932,6 +1410,7 @@ fn it_add_route_without_zones() {
                 Origin::signed(REGISTRAR_1_ACCOUNT_ID),
                 waypoints,
                 ROOT_ID,
+                ROUTE_AREAS,
         ));
     });
 }
@@ -966,6 +1445,7 @@ fn it_add_route_one_area() {
                 Origin::signed(REGISTRAR_1_ACCOUNT_ID),
                 waypoints.clone(),
                 ROOT_ID,
+                ROUTE_AREAS,
         ));
         // But this one will fail, as it blocks the way
         assert_ok!(
@@ -980,6 +1460,42 @@ fn it_add_route_one_area() {
                 Origin::signed(REGISTRAR_1_ACCOUNT_ID),
                 waypoints,
                 ROOT_ID,
+                ROUTE_AREAS,
+            ), Error::RouteIntersectRedZone
+        );
+    });
+}
+
+#[test]
+fn it_add_route_inside_rect_zone() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(
+            DSAccountsModule::account_add(
+                Origin::signed(ADMIN_ACCOUNT_ID),
+                REGISTRAR_1_ACCOUNT_ID,
+                super::REGISTRAR_ROLE
+        ));
+        assert_ok!(
+            DSMapsModule::root_add(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                construct_testing_box(),
+                coord(DELTA),
+        ));
+        assert_ok!(
+            DSMapsModule::zone_add(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                construct_testing_rect(),
+                DEFAULT_HEIGHT,
+                ROOT_ID,
+        ));
+        // Route doesn't cross any edge of the zone, as it's fully inside
+        let waypoints = construct_custom_waypoints("55.3953", "37.3853", "55.3957", "37.3857", 10, 20);
+        assert_noop!(
+            DSMapsModule::route_add(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                waypoints,
+                ROOT_ID,
+                ROUTE_AREAS,
             ), Error::RouteIntersectRedZone
         );
     });
@@ -1015,6 +1531,7 @@ fn it_add_route_multiple_areas() {
                 Origin::signed(REGISTRAR_1_ACCOUNT_ID),
                 waypoints.clone(),
                 ROOT_ID,
+                ROUTE_AREAS,
         ));
         // But this one will
         assert_ok!(
@@ -1029,6 +1546,7 @@ fn it_add_route_multiple_areas() {
                 Origin::signed(REGISTRAR_1_ACCOUNT_ID),
                 waypoints,
                 ROOT_ID,
+                ROUTE_AREAS,
             ), Error::RouteIntersectRedZone
         );
     });
@@ -1067,6 +1585,7 @@ fn it_add_route_and_remove_zone() {
                 Origin::signed(REGISTRAR_1_ACCOUNT_ID),
                 waypoints.clone(),
                 ROOT_ID,
+                ROUTE_AREAS,
             ), 
             Error::RouteIntersectRedZone
         );
@@ -1083,13 +1602,15 @@ fn it_add_route_and_remove_zone() {
                 Origin::signed(REGISTRAR_1_ACCOUNT_ID),
                 waypoints,
                 ROOT_ID,
+                ROUTE_AREAS,
             )
         );
     });
 }
 
+// Removed zone leaves a free slot before another zone in the area
 #[test]
-fn it_add_lots_of_zones() {
+fn it_add_route_through_zone_after_removed_one() {
     new_test_ext().execute_with(|| {
         assert_ok!(
             DSAccountsModule::account_add(
@@ -1105,18 +1626,516 @@ fn it_add_lots_of_zones() {
                 coord(DELTA),
             )
         );
-        let delta: Coord = coord(DELTA);
-        let waypoints = construct_custom_waypoints("55.373", "37.373", "55.396", "37.386", 10, 20);
-        let mut testing_rect: Rect2D<Coord> = construct_testing_rect();
-        for _n in 1..11 {
-            assert_ok!(
-                DSMapsModule::zone_add(
-                    Origin::signed(REGISTRAR_1_ACCOUNT_ID),
-                    testing_rect,
-                    DEFAULT_HEIGHT, 
-                    ROOT_ID,
-                )
-            );
+        assert_ok!(
+            DSMapsModule::zone_add(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                construct_testing_rect(),
+                DEFAULT_HEIGHT,
+                ROOT_ID,
+            )
+        );
+        assert_ok!(
+            DSMapsModule::zone_add(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                construct_custom_rect("55.391", "37.381", "55.392", "37.382"),
+                DEFAULT_HEIGHT,
+                ROOT_ID,
+            )
+        );
+        assert_ok!(
+            DSMapsModule::zone_remove(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                DSMapsModule::pack_index(ROOT_ID, AREA_ID, 0),
+            )
+        );
+        assert_eq!(DSMapsModule::zones_in_area(ROOT_ID, AREA_ID), vec![1]);
+        // Zone in the second slot still blocks the way
+        let waypoints = construct_custom_waypoints("55.3905", "37.3805", "55.3925", "37.3825", 10, 20);
+        assert_noop!(
+            DSMapsModule::route_add(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                waypoints,
+                ROOT_ID,
+                ROUTE_AREAS,
+            ),
+            Error::RouteIntersectRedZone
+        );
+        // Freed slot is taken again, and then the area is full
+        assert_ok!(
+            DSMapsModule::zone_add(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                construct_custom_rect("55.397", "37.387", "55.398", "37.388"),
+                DEFAULT_HEIGHT,
+                ROOT_ID,
+            )
+        );
+        assert_eq!(DSMapsModule::zones_in_area(ROOT_ID, AREA_ID), vec![0, 1]);
+        assert_eq!(DSMapsModule::get_area_zones(ROOT_ID, AREA_ID).len(), 2);
+        assert_noop!(
+            DSMapsModule::zone_add(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                construct_custom_rect("55.393", "37.383", "55.394", "37.384"),
+                DEFAULT_HEIGHT,
+                ROOT_ID,
+            ),
+            Error::AreaFull
+        );
+        // Index of the area is dropped with the root
+        assert_ok!(
+            DSMapsModule::root_remove(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                ROOT_ID,
+            )
+        );
+        clear_removed_roots();
+        assert!(DSMapsModule::zones_in_area(ROOT_ID, AREA_ID).is_empty());
+        assert!(!super::RedZones::<Test>::contains_key(DSMapsModule::pack_index(ROOT_ID, AREA_ID, 1)));
+    });
+}
+
+#[test]
+fn it_migrate_zones_of_baseline_layout() {
+    new_test_ext().execute_with(|| {
+        // Chain, started before versioning, has zones with only id, rect and height, and a hole between them
+        super::StorageVersion::put(0);
+        System::set_block_number(10);
+        let rect = construct_testing_rect();
+        for child in [0, 2].iter() {
+            let zone_id = DSMapsModule::pack_index(ROOT_ID, AREA_ID, *child);
+            unhashed::put_raw(&super::RedZones::<Test>::hashed_key_for(zone_id), &(zone_id, rect, DEFAULT_HEIGHT).encode());
+        }
+        DSMapsModule::on_runtime_upgrade();
+        assert_eq!(DSMapsModule::storage_version(), super::STORAGE_VERSION);
+        assert_eq!(DSMapsModule::zones_in_area(ROOT_ID, AREA_ID), vec![0, 2]);
+        let zones = DSMapsModule::get_area_zones(ROOT_ID, AREA_ID);
+        assert_eq!(zones.len(), 2);
+        assert!(zones.iter().all(|zone| zone.rect == rect && zone.height == DEFAULT_HEIGHT));
+        assert!(zones.iter().all(|zone| zone.shape == ZoneShape::Rect && zone.time_window.is_none()));
+        // Pruning of temporary zones starts from the block of the upgrade
+        assert_eq!(DSMapsModule::next_pruned_block(), 10);
+        // Migration is applied once, and the index isn't rebuilt again for the same grid and kind
+        rebuild_index();
+        super::RedZones::<Test>::remove(DSMapsModule::pack_index(ROOT_ID, AREA_ID, 0));
+        DSMapsModule::on_runtime_upgrade();
+        assert_eq!(DSMapsModule::zones_in_area(ROOT_ID, AREA_ID), vec![0, 2]);
+        assert!(!DSMapsModule::index_clearing());
+        assert_eq!(DSMapsModule::index_rebuild_key(), None);
+    });
+}
+
+#[test]
+fn it_migrate_dense_bitmap_pages() {
+    new_test_ext_with_map(construct_testing_map()).execute_with(|| {
+        super::StorageVersion::put(0);
+        // Pages used to store every cell, with no length prefix
+        let mut cells = [[0u64; 50]; 32];
+        cells[3][10..20].copy_from_slice(&[ROOT_ID; 10]);
+        cells[4] = [ROOT_ID + 1; 50];
+        let (page_id, empty_page_id) = (Page::<Coord, Test>::get_index(100, 100), Page::<Coord, Test>::get_index(200, 200));
+        unhashed::put_raw(&super::EarthBitmap::<Test>::hashed_key_for(page_id), &cells.encode());
+        let empty_cells = [[0u64; 50]; 32];
+        unhashed::put_raw(&super::EarthBitmap::<Test>::hashed_key_for(empty_page_id), &empty_cells.encode());
+
+        DSMapsModule::on_runtime_upgrade();
+        rebuild_index();
+        assert_eq!(DSMapsModule::storage_version(), super::STORAGE_VERSION);
+        // Dense pages are removed with the whole index, which is built again from stored roots
+        assert!(!super::EarthBitmap::<Test>::contains_key(page_id));
+        assert!(!super::EarthBitmap::<Test>::contains_key(empty_page_id));
+        assert_eq!(DSMapsModule::get_root_index([raw("55.395"), raw("37.385")]), ROOT_ID);
+        assert_eq!(DSMapsModule::indexed_grid(), <Test as crate::Grid>::resolution());
+    });
+}
+
+#[test]
+fn it_migrate_roots_with_ids_of_legacy_cells() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(
+            DSAccountsModule::account_add(
+                Origin::signed(ADMIN_ACCOUNT_ID),
+                REGISTRAR_1_ACCOUNT_ID,
+                super::REGISTRAR_ROLE
+            )
+        );
+        // Cells of the root used to be counted from the equator and the prime meridian
+        super::StorageVersion::put(0);
+        let legacy_id = crate::RootBox::<Coord>::get_index(5537, 3737, 5592, 3790);
+        super::RootBoxes::<Test>::insert(legacy_id, crate::RootBox::new(legacy_id, construct_testing_box(), coord(DELTA)));
+        let zone_id = DSMapsModule::pack_index(legacy_id, AREA_ID, 0);
+        unhashed::put_raw(&super::RedZones::<Test>::hashed_key_for(zone_id),
+                          &(zone_id, construct_testing_rect(), DEFAULT_HEIGHT).encode());
+        let mut cells = [[0u64; 50]; 32];
+        cells[5][30..40].copy_from_slice(&[legacy_id; 10]);
+        let legacy_page_id = (5568 << 16) | 3750;
+        unhashed::put_raw(&super::EarthBitmap::<Test>::hashed_key_for(legacy_page_id), &cells.encode());
+
+        DSMapsModule::on_runtime_upgrade();
+        rebuild_index();
+        assert!(!super::EarthBitmap::<Test>::contains_key(legacy_page_id));
+        // Root keeps its id, and is found by cells of the current grid
+        assert_ne!(legacy_id, ROOT_ID);
+        assert_eq!(DSMapsModule::get_root_index([raw("55.395"), raw("37.385")]), legacy_id);
+        assert_eq!(DSMapsModule::get_area_zones(legacy_id, AREA_ID).len(), 1);
+        assert_noop!(
+            DSMapsModule::root_add(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                construct_testing_box(),
+                coord(DELTA),
+            ),
+            Error::OverlappingRoot
+        );
+    });
+}
+
+#[test]
+fn it_rebuild_index_when_grid_changes() {
+    new_test_ext_with_map(construct_testing_map()).execute_with(|| {
+        // Index was built with twice coarser cells
+        let old_grid = crate::GridResolution { cell_length: 2, cell_width: 2, ..DSMapsModule::indexed_grid() };
+        super::IndexedGrid::put(old_grid);
+        let (root_id, cells) = construct_root_cells(construct_testing_box());
+        super::index::BitmapIndex::<Test>::remove(root_id, cells);
+        assert_eq!(DSMapsModule::get_root_index([raw("55.395"), raw("37.385")]), 0);
+
+        DSMapsModule::on_runtime_upgrade();
+        rebuild_index();
+        assert_eq!(DSMapsModule::indexed_grid(), <Test as crate::Grid>::resolution());
+        assert_eq!(DSMapsModule::get_root_index([raw("55.395"), raw("37.385")]), ROOT_ID);
+        // Unchanged grid doesn't touch the index
+        super::index::BitmapIndex::<Test>::remove(root_id, cells);
+        DSMapsModule::on_runtime_upgrade();
+        assert_eq!(DSMapsModule::get_root_index([raw("55.395"), raw("37.385")]), 0);
+    });
+}
+
+#[test]
+fn it_checks_integrity_of_grid() {
+    for grid in [DEFAULT_GRID, COARSE_GRID].iter() {
+        new_test_ext_with_grid(*grid).execute_with(|| {
+            DSMapsModule::integrity_test();
+        });
+    }
+}
+
+#[test]
+#[should_panic(expected = "PageWidth must be in 1..=255")]
+fn it_checks_integrity_of_too_wide_page() {
+    new_test_ext_with_grid(crate::GridResolution { page_width: 256, ..DEFAULT_GRID }).execute_with(|| {
+        DSMapsModule::integrity_test();
+    });
+}
+
+#[test]
+#[should_panic(expected = "rows of the grid don't fit into 16 bits")]
+fn it_checks_integrity_of_too_fine_grid() {
+    // 0.001 degree cells give 180 000 rows
+    new_test_ext_with_grid(crate::GridResolution { cell_size_degree: 3, ..DEFAULT_GRID }).execute_with(|| {
+        DSMapsModule::integrity_test();
+    });
+}
+
+#[test]
+fn it_add_root_zone_and_route_on_coarse_grid() {
+    for kind in INDEX_KINDS.iter() {
+        set_index_kind(*kind);
+        new_test_ext_with_grid(COARSE_GRID).execute_with(|| {
+            assert_ok!(
+                DSAccountsModule::account_add(
+                    Origin::signed(ADMIN_ACCOUNT_ID),
+                    REGISTRAR_1_ACCOUNT_ID,
+                    super::REGISTRAR_ROLE
+            ));
+            assert_ok!(
+                DSMapsModule::root_add(
+                    Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                    construct_testing_box(),
+                    coord(DELTA),
+            ));
+            // Id of the root describes cells of this grid
+            let (root_id, cells) = construct_root_cells(construct_testing_box());
+            assert_ne!(root_id, ROOT_ID);
+            assert_eq!(crate::RootBox::<Coord>::get_boundary_cell_indexes(root_id), cells);
+            assert_eq!(DSMapsModule::get_root_index([raw("55.395"), raw("37.385")]), root_id);
+            // Cells are 0.05 degree wide, so the root's west cell starts at 37.35
+            assert_eq!(DSMapsModule::get_root_index([raw("55.395"), raw("37.36")]), root_id);
+            assert_eq!(DSMapsModule::get_root_index([raw("55.395"), raw("37.34")]), 0);
+            assert_noop!(
+                DSMapsModule::root_add(
+                    Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                    construct_custom_box("55.9", "37.8", "56.0", "37.95"),
+                    coord(DELTA),
+                ),
+                Error::OverlappingRoot
+            );
+
+            assert_ok!(
+                DSMapsModule::zone_add(
+                    Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                    construct_testing_rect(),
+                    DEFAULT_HEIGHT,
+                    root_id
+            ));
+            assert_eq!(DSMapsModule::get_area_zones(root_id, AREA_ID).len(), 1);
+            assert_ok!(
+                DSMapsModule::route_add(
+                    Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                    construct_custom_waypoints("55.373", "37.373", "55.376", "37.376", 10, 20),
+                    root_id,
+                    ROUTE_AREAS,
+            ));
+
+            assert_ok!(
+                DSMapsModule::root_remove(
+                    Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                    root_id,
+            ));
+            clear_removed_roots();
+            assert_eq!(DSMapsModule::get_root_index([raw("55.395"), raw("37.385")]), 0);
+            assert_eq!(super::RedZones::<Test>::iter().count(), 0);
+        });
+    }
+}
+
+#[test]
+fn it_rebuild_index_when_index_kind_changes() {
+    new_test_ext_with_map(construct_testing_map()).execute_with(|| {
+        assert_eq!(DSMapsModule::indexed_kind(), Some(IndexKind::Bitmap));
+        // Runtime has switched to the quadtree, which is empty yet
+        set_index_kind(IndexKind::Quadtree);
+        assert_eq!(DSMapsModule::get_root_index([raw("55.395"), raw("37.385")]), 0);
+
+        DSMapsModule::on_runtime_upgrade();
+        rebuild_index();
+        assert_eq!(DSMapsModule::indexed_kind(), Some(IndexKind::Quadtree));
+        assert_eq!(DSMapsModule::get_root_index([raw("55.395"), raw("37.385")]), ROOT_ID);
+        assert_eq!(super::EarthBitmap::<Test>::iter().count(), 0);
+
+        // Index, built before its kind was stored, is rebuilt too
+        super::IndexedKind::kill();
+        let (root_id, cells) = construct_root_cells(construct_testing_box());
+        super::index::QuadtreeIndex::<Test>::remove(root_id, cells);
+        assert_eq!(DSMapsModule::get_root_index([raw("55.395"), raw("37.385")]), 0);
+        DSMapsModule::on_runtime_upgrade();
+        rebuild_index();
+        assert_eq!(DSMapsModule::indexed_kind(), Some(IndexKind::Quadtree));
+        assert_eq!(DSMapsModule::get_root_index([raw("55.395"), raw("37.385")]), ROOT_ID);
+    });
+}
+
+#[test]
+fn it_rebuild_index_over_several_blocks() {
+    new_test_ext_with_map(construct_testing_map()).execute_with(|| {
+        System::set_block_number(1);
+        assert_ok!(
+            DSAccountsModule::account_add(
+                Origin::signed(ADMIN_ACCOUNT_ID),
+                REGISTRAR_1_ACCOUNT_ID,
+                super::REGISTRAR_ROLE
+        ));
+        let boxes = [construct_custom_box("-23.7", "-46.9", "-23.4", "-46.4"),
+                     construct_custom_box("65.0", "179.8", "65.2", "180.2")];
+        for bounding_box in boxes.iter() {
+            assert_ok!(
+                DSMapsModule::root_add(
+                    Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                    *bounding_box,
+                    coord(DELTA),
+            ));
+        }
+        set_index_kind(IndexKind::Quadtree);
+        DSMapsModule::on_runtime_upgrade();
+        // Roots can't be added, until every stored one is in the index
+        assert_noop!(
+            DSMapsModule::root_add(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                construct_custom_box("10.0", "10.0", "10.1", "10.1"),
+                coord(DELTA),
+            ),
+            Error::IndexIsBeingRebuilt
+        );
+
+        // 3 roots are stored, 2 per block
+        DSMapsModule::on_initialize(1);
+        assert!(DSMapsModule::index_rebuild_key().is_some());
+        DSMapsModule::on_initialize(2);
+        assert!(DSMapsModule::index_rebuild_key().is_none());
+        assert_eq!(DSMapsModule::get_root_index([raw("55.395"), raw("37.385")]), ROOT_ID);
+        assert_eq!(DSMapsModule::get_root_index([-98775859, -195580396]), 1872710396962092129);
+        assert_eq!(DSMapsModule::get_root_index([273049190, -754555290]), 4369772282851622982);
+        assert!(DSMapsModule::roots_to_remove().is_empty());
+        assert_ok!(
+            DSMapsModule::root_add(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                construct_custom_box("10.0", "10.0", "10.1", "10.1"),
+                coord(DELTA),
+        ));
+    });
+}
+
+#[test]
+fn it_remove_roots_which_dont_fit_rebuilt_index() {
+    new_test_ext_with_index(IndexKind::Quadtree).execute_with(|| {
+        System::set_block_number(1);
+        assert_ok!(
+            DSAccountsModule::account_add(
+                Origin::signed(ADMIN_ACCOUNT_ID),
+                REGISTRAR_1_ACCOUNT_ID,
+                super::REGISTRAR_ROLE
+        ));
+        // Root of many bitmap pages, with a zone in it
+        assert_ok!(
+            DSMapsModule::root_add(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                construct_custom_box("50.0", "30.0", "52.0", "32.0"),
+                coord("0.1"),
+        ));
+        let root_id = DSMapsModule::get_root_index([raw("51.05"), raw("31.05")]);
+        assert_ne!(root_id, 0);
+        assert_ok!(
+            DSMapsModule::zone_add(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                construct_custom_rect("51.01", "31.01", "51.02", "31.02"),
+                DEFAULT_HEIGHT,
+                root_id
+        ));
+
+        set_index_kind(IndexKind::Bitmap);
+        DSMapsModule::on_runtime_upgrade();
+        rebuild_index();
+        let not_indexed = Event::pallet_ds_maps(super::RawEvent::RootNotIndexed(root_id));
+        assert!(System::events().iter().any(|record| record.event == not_indexed));
+        assert_eq!(DSMapsModule::roots_to_remove(), vec![root_id]);
+        assert_eq!(DSMapsModule::get_root_index([raw("51.05"), raw("31.05")]), 0);
+
+        // Root is removed with its zones, like any removed root
+        clear_removed_roots();
+        assert!(!super::RootBoxes::<Test>::contains_key(root_id));
+        assert_eq!(super::RedZones::<Test>::iter().count(), 0);
+        assert_ok!(
+            DSMapsModule::root_add(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                construct_custom_box("51.0", "31.0", "51.1", "31.1"),
+                coord(DELTA),
+        ));
+    });
+}
+
+/// Amount of keys, which start with the prefix, whatever their values are
+fn count_keys(prefix: &[u8]) -> usize {
+    let mut count = 0;
+    let mut key = prefix.to_vec();
+    while let Some(next) = sp_io::storage::next_key(&key).filter(|next| next.starts_with(prefix)) {
+        count += 1;
+        key = next;
+    }
+    count
+}
+
+#[test]
+fn it_clear_old_index_over_several_blocks() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(
+            DSAccountsModule::account_add(
+                Origin::signed(ADMIN_ACCOUNT_ID),
+                REGISTRAR_1_ACCOUNT_ID,
+                super::REGISTRAR_ROLE
+        ));
+        assert_ok!(
+            DSMapsModule::root_add(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                construct_testing_box(),
+                coord(DELTA),
+        ));
+        // Pages of the old index, which no root owns, are removed too
+        for page_id in 0..20 {
+            unhashed::put_raw(&super::EarthBitmap::<Test>::hashed_key_for(1_000_000 + page_id), &[1, 2, 3]);
+        }
+        let old_keys = count_keys(&super::EarthBitmap::<Test>::final_prefix());
+        assert!(old_keys > 20);
+
+        set_index_kind(IndexKind::Quadtree);
+        DSMapsModule::on_runtime_upgrade();
+        DSMapsModule::on_initialize(1);
+        assert!(DSMapsModule::index_clearing());
+        assert_eq!(count_keys(&super::EarthBitmap::<Test>::final_prefix()), old_keys - 16);
+        assert_eq!(super::QuadNodes::iter().count(), 0);
+
+        rebuild_index();
+        assert!(!DSMapsModule::index_clearing());
+        assert_eq!(count_keys(&super::EarthBitmap::<Test>::final_prefix()), 0);
+        assert_eq!(DSMapsModule::get_root_index([raw("55.395"), raw("37.385")]), ROOT_ID);
+    });
+}
+
+#[test]
+fn it_keep_roots_which_exceed_index_budget() {
+    new_test_ext_with_index(IndexKind::Quadtree).execute_with(|| {
+        System::set_block_number(1);
+        assert_ok!(
+            DSAccountsModule::account_add(
+                Origin::signed(ADMIN_ACCOUNT_ID),
+                REGISTRAR_1_ACCOUNT_ID,
+                super::REGISTRAR_ROLE
+        ));
+        // Root is one cell high and 700 cells wide
+        let bounding_box = construct_custom_box("50.001", "30.001", "50.011", "37.001");
+        assert_ok!(
+            DSMapsModule::root_add(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                bounding_box,
+                coord(DELTA),
+        ));
+        let (root_id, [row, first_column, _, end_column]) = construct_root_cells(bounding_box);
+
+        super::IndexedKind::put(IndexKind::Bitmap);
+        DSMapsModule::on_runtime_upgrade();
+        super::QuadNodes::remove_all();
+        super::IndexClearing::kill();
+        // Every node along the root's row has roots below, so the search reads them all
+        for level in 0..crate::index::QUADTREE_DEPTH {
+            let shift = crate::index::QUADTREE_DEPTH - level;
+            for column in first_column..end_column {
+                let key = (level as u64) << 32 | ((row >> shift) as u64) << 16 | (column >> shift) as u64;
+                super::QuadNodes::insert(key, crate::index::QuadNode { roots: Vec::new(), subtree_roots: 1 });
+            }
+        }
+        rebuild_index();
+        let left_unindexed = Event::pallet_ds_maps(super::RawEvent::RootLeftUnindexed(root_id));
+        assert!(System::events().iter().any(|record| record.event == left_unindexed));
+        assert!(DSMapsModule::roots_to_remove().is_empty());
+        assert!(super::RootBoxes::<Test>::contains_key(root_id));
+    });
+}
+
+#[test]
+fn it_add_lots_of_zones() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(
+            DSAccountsModule::account_add(
+                Origin::signed(ADMIN_ACCOUNT_ID),
+                REGISTRAR_1_ACCOUNT_ID,
+                super::REGISTRAR_ROLE
+            )
+        );
+        assert_ok!(
+            DSMapsModule::root_add(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                construct_testing_box(),
+                coord(DELTA),
+            )
+        );
+        let delta: Coord = coord(DELTA);
+        let waypoints = construct_custom_waypoints("55.373", "37.373", "55.396", "37.386", 10, 20);
+        let mut testing_rect: Rect2D<Coord> = construct_testing_rect();
+        for _n in 1..11 {
+            assert_ok!(
+                DSMapsModule::zone_add(
+                    Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                    testing_rect,
+                    DEFAULT_HEIGHT, 
+                    ROOT_ID,
+                )
+            );
             testing_rect.north_east.lon += delta;
             testing_rect.south_west.lon += delta;
         }
@@ -1126,6 +2145,7 @@ fn it_add_lots_of_zones() {
                 Origin::signed(REGISTRAR_1_ACCOUNT_ID),
                 waypoints.clone(),
                 ROOT_ID,
+                ROUTE_AREAS,
             ), 
             Error::RouteIntersectRedZone
         );
@@ -1142,7 +2162,1702 @@ fn it_add_lots_of_zones() {
                 Origin::signed(REGISTRAR_1_ACCOUNT_ID),
                 waypoints,
                 ROOT_ID,
+                ROUTE_AREAS,
             )
         );
     });
-}
\ No newline at end of file
+}
+
+// Route goes around the zone on paper, but it's middle leg cuts through it
+#[test]
+fn it_add_route_with_intermediate_leg_through_zone() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(
+            DSAccountsModule::account_add(
+                Origin::signed(ADMIN_ACCOUNT_ID),
+                REGISTRAR_1_ACCOUNT_ID,
+                super::REGISTRAR_ROLE
+            )
+        );
+        assert_ok!(
+            DSMapsModule::root_add(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                construct_testing_box(),
+                coord(DELTA),
+            )
+        );
+        assert_ok!(
+            DSMapsModule::zone_add(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                construct_testing_rect(),
+                DEFAULT_HEIGHT, 
+                ROOT_ID,
+            )
+        );
+        // First and last waypoints are south of the zone, but second one is north of it
+        let waypoints = construct_route(&[
+            (["55.393", "37.3855", "1"], 10),
+            (["55.398", "37.3855", "1"], 20),
+            (["55.393", "37.3875", "1"], 30),
+        ]);
+        assert_noop!(
+            DSMapsModule::route_add(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                waypoints,
+                ROOT_ID,
+                ROUTE_AREAS,
+            ), 
+            Error::RouteIntersectRedZone
+        );
+        // This one flies around the zone
+        let waypoints = construct_route(&[
+            (["55.393", "37.3855", "1"], 10),
+            (["55.393", "37.388", "1"], 20),
+            (["55.398", "37.388", "1"], 30),
+        ]);
+        assert_ok!(
+            DSMapsModule::route_add(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                waypoints,
+                ROOT_ID,
+                ROUTE_AREAS,
+            )
+        );
+    });
+}
+
+#[test]
+fn it_add_route_with_wrong_intermediate_waypoint() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(
+            DSAccountsModule::account_add(
+                Origin::signed(ADMIN_ACCOUNT_ID),
+                REGISTRAR_1_ACCOUNT_ID,
+                super::REGISTRAR_ROLE
+            )
+        );
+        assert_ok!(
+            DSMapsModule::root_add(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                construct_testing_box(),
+                coord(DELTA),
+            )
+        );
+        // Time goes backwards in the middle of the route
+        let waypoints = construct_route(&[
+            (["55.393", "37.381", "1"], 10),
+            (["55.394", "37.382", "1"], 30),
+            (["55.395", "37.383", "1"], 20),
+            (["55.396", "37.384", "1"], 40),
+        ]);
+        assert_noop!(
+            DSMapsModule::route_add(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                waypoints,
+                ROOT_ID,
+                ROUTE_AREAS,
+            ), 
+            Error::WrongTimeSupplied
+        );
+        // Second waypoint lies outside of root
+        let waypoints = construct_route(&[
+            (["55.393", "37.381", "1"], 10),
+            (["10", "37", "1"], 20),
+            (["55.395", "37.383", "1"], 30),
+        ]);
+        assert_noop!(
+            DSMapsModule::route_add(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                waypoints,
+                ROOT_ID,
+                ROUTE_AREAS,
+            ), 
+            Error::RouteDoesNotFitToRoot
+        );
+    });
+}
+
+// Root, which is high enough to fly above buildings
+fn construct_testing_airspace() -> Box3D<Coord> {
+    let mut bounding_box = construct_testing_box();
+    bounding_box.south_west.alt = coord("0");
+    bounding_box.north_east.alt = coord("120");
+    bounding_box
+}
+
+#[test]
+fn it_add_route_above_zone() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(
+            DSAccountsModule::account_add(
+                Origin::signed(ADMIN_ACCOUNT_ID),
+                REGISTRAR_1_ACCOUNT_ID,
+                super::REGISTRAR_ROLE
+            )
+        );
+        assert_ok!(
+            DSMapsModule::root_add(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                construct_testing_airspace(),
+                coord(DELTA),
+            )
+        );
+        assert_ok!(
+            DSMapsModule::zone_add(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                construct_testing_rect(),
+                40, 
+                ROOT_ID,
+            )
+        );
+        // Flying straight above the building
+        let waypoints = construct_route(&[
+            (["55.393", "37.3855", "50"], 10),
+            (["55.398", "37.3855", "50"], 20),
+        ]);
+        assert_ok!(
+            DSMapsModule::route_add(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                waypoints,
+                ROOT_ID,
+                ROUTE_AREAS,
+            )
+        );
+        // Same leg, but lower than the building
+        let waypoints = construct_route(&[
+            (["55.393", "37.3855", "30"], 10),
+            (["55.398", "37.3855", "30"], 20),
+        ]);
+        assert_noop!(
+            DSMapsModule::route_add(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                waypoints,
+                ROOT_ID,
+                ROUTE_AREAS,
+            ),
+            Error::RouteIntersectRedZone
+        );
+        // Climbing, zone is crossed at 40% of the leg, at altitude 60.
+        // It's flown later, so it doesn't conflict with the first route
+        let waypoints = construct_route(&[
+            (["55.393", "37.3855", "20"], 110),
+            (["55.398", "37.3855", "120"], 120),
+        ]);
+        assert_ok!(
+            DSMapsModule::route_add(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                waypoints,
+                ROOT_ID,
+                ROUTE_AREAS,
+            )
+        );
+        // Climbing too slow, zone is crossed at altitude 30
+        let waypoints = construct_route(&[
+            (["55.393", "37.3855", "10"], 10),
+            (["55.398", "37.3855", "60"], 20),
+        ]);
+        assert_noop!(
+            DSMapsModule::route_add(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                waypoints,
+                ROOT_ID,
+                ROUTE_AREAS,
+            ),
+            Error::RouteIntersectRedZone
+        );
+    });
+}
+
+#[test]
+fn it_add_route_above_root_ceiling() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(
+            DSAccountsModule::account_add(
+                Origin::signed(ADMIN_ACCOUNT_ID),
+                REGISTRAR_1_ACCOUNT_ID,
+                super::REGISTRAR_ROLE
+            )
+        );
+        assert_ok!(
+            DSMapsModule::root_add(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                construct_testing_airspace(),
+                coord(DELTA),
+            )
+        );
+        let waypoints = construct_route(&[
+            (["55.393", "37.381", "100"], 10),
+            (["55.394", "37.382", "200"], 20),
+        ]);
+        assert_noop!(
+            DSMapsModule::route_add(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                waypoints,
+                ROOT_ID,
+                ROUTE_AREAS,
+            ),
+            Error::RouteDoesNotFitToRoot
+        );
+    });
+}
+
+#[test]
+fn it_add_raw_route_with_altitude() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(
+            DSAccountsModule::account_add(
+                Origin::signed(ADMIN_ACCOUNT_ID),
+                REGISTRAR_1_ACCOUNT_ID,
+                super::REGISTRAR_ROLE
+            )
+        );
+        assert_ok!(
+            DSMapsModule::root_add(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                construct_testing_airspace(),
+                coord(DELTA),
+            )
+        );
+        // 55.393 - 232335081, 55.398 - 232356052, 37.3855 - 156806152
+        // 50 - 209715200, 200 - 838860800
+        assert_ok!(
+            DSMapsModule::raw_route_add(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                [232335081, 156806152, 209715200, 232356052, 156806152, 209715200],
+                10,
+                20,
+                ROOT_ID,
+                ROUTE_AREAS,
+            )
+        );
+        assert_noop!(
+            DSMapsModule::raw_route_add(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                [232335081, 156806152, 209715200, 232356052, 156806152, 838860800],
+                10,
+                20,
+                ROOT_ID,
+                ROUTE_AREAS,
+            ),
+            Error::RouteDoesNotFitToRoot
+        );
+    });
+}
+
+// L-shaped zone in area 58, which leaves empty north-east part of it's bounding rect
+//  +--+
+//  |  |
+//  |  +--------+
+//  |           |
+//  +-----------+
+fn construct_testing_polygon() -> Vec<Point2D<Coord>> {
+    vec![
+        Point2D::new(coord("55.3915"), coord("37.3815")),
+        Point2D::new(coord("55.3985"), coord("37.3815")),
+        Point2D::new(coord("55.3985"), coord("37.383")),
+        Point2D::new(coord("55.393"), coord("37.383")),
+        Point2D::new(coord("55.393"), coord("37.3885")),
+        Point2D::new(coord("55.3915"), coord("37.3885")),
+    ]
+}
+
+#[test]
+fn it_add_polygon_zone() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(
+            DSAccountsModule::account_add(
+                Origin::signed(ADMIN_ACCOUNT_ID),
+                REGISTRAR_1_ACCOUNT_ID,
+                super::REGISTRAR_ROLE
+            )
+        );
+        assert_ok!(
+            DSMapsModule::root_add(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                construct_testing_box(),
+                coord(DELTA),
+            )
+        );
+        assert_noop!(
+            DSMapsModule::zone_add_polygon(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                construct_testing_polygon()[..2].to_vec(),
+                DEFAULT_HEIGHT,
+                ROOT_ID,
+            ),
+            Error::WrongVerticesAmount
+        );
+        let mut too_many_vertices = construct_testing_polygon();
+        too_many_vertices.extend(construct_testing_polygon());
+        assert_noop!(
+            DSMapsModule::zone_add_polygon(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                too_many_vertices,
+                DEFAULT_HEIGHT,
+                ROOT_ID,
+            ),
+            Error::WrongVerticesAmount
+        );
+        // Bow tie, which diagonals cross each other
+        let self_intersecting = vec![
+            Point2D::new(coord("55.391"), coord("37.381")),
+            Point2D::new(coord("55.399"), coord("37.389")),
+            Point2D::new(coord("55.391"), coord("37.389")),
+            Point2D::new(coord("55.399"), coord("37.381")),
+        ];
+        assert_noop!(
+            DSMapsModule::zone_add_polygon(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                self_intersecting,
+                DEFAULT_HEIGHT,
+                ROOT_ID,
+            ),
+            Error::PolygonNotSimple
+        );
+        assert_ok!(
+            DSMapsModule::zone_add_polygon(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                construct_testing_polygon(),
+                DEFAULT_HEIGHT,
+                ROOT_ID,
+            )
+        );
+        let zone = DSMapsModule::zone_data(DSMapsModule::pack_index(ROOT_ID, AREA_ID, 0));
+        assert!(construct_custom_rect("55.3915", "37.3815", "55.3985", "37.3885") == zone.rect);
+        assert_eq!(zone.shape, ZoneShape::Polygon(construct_testing_polygon()));
+    });
+}
+
+#[test]
+fn it_add_zones_inside_bounding_rect_of_polygon() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(
+            DSAccountsModule::account_add(
+                Origin::signed(ADMIN_ACCOUNT_ID),
+                REGISTRAR_1_ACCOUNT_ID,
+                super::REGISTRAR_ROLE
+            )
+        );
+        assert_ok!(
+            DSMapsModule::root_add(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                construct_testing_box(),
+                coord(DELTA),
+            )
+        );
+        assert_ok!(
+            DSMapsModule::zone_add_polygon(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                construct_testing_polygon(),
+                DEFAULT_HEIGHT,
+                ROOT_ID,
+            )
+        );
+        // Crosses the lower part of the polygon
+        assert_noop!(
+            DSMapsModule::zone_add(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                construct_custom_rect("55.392", "37.384", "55.3925", "37.385"),
+                DEFAULT_HEIGHT,
+                ROOT_ID,
+            ),
+            Error::OverlappingZone
+        );
+        // Lies inside of polygon's bounding rect, but outside of the polygon
+        assert_ok!(
+            DSMapsModule::zone_add(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                construct_testing_rect(),
+                DEFAULT_HEIGHT,
+                ROOT_ID,
+            )
+        );
+        assert_eq!(DSMapsModule::zones_in_area(ROOT_ID, AREA_ID), vec![0, 1]);
+    });
+}
+
+#[test]
+fn it_add_route_near_polygon_zone() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(
+            DSAccountsModule::account_add(
+                Origin::signed(ADMIN_ACCOUNT_ID),
+                REGISTRAR_1_ACCOUNT_ID,
+                super::REGISTRAR_ROLE
+            )
+        );
+        assert_ok!(
+            DSMapsModule::root_add(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                construct_testing_box(),
+                coord(DELTA),
+            )
+        );
+        assert_ok!(
+            DSMapsModule::zone_add_polygon(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                construct_testing_polygon(),
+                DEFAULT_HEIGHT,
+                ROOT_ID,
+            )
+        );
+        // Route lies inside of zone's bounding rect, but outside of the zone itself
+        let waypoints = construct_route(&[
+            (["55.395", "37.385", "1"], 10),
+            (["55.398", "37.388", "1"], 20),
+        ]);
+        assert_ok!(
+            DSMapsModule::route_add(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                waypoints,
+                ROOT_ID,
+                ROUTE_AREAS,
+            )
+        );
+        // This one goes through the lower part of the zone
+        let waypoints = construct_route(&[
+            (["55.389", "37.3875", "1"], 10),
+            (["55.396", "37.3875", "1"], 20),
+        ]);
+        assert_noop!(
+            DSMapsModule::route_add(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                waypoints,
+                ROOT_ID,
+                ROUTE_AREAS,
+            ),
+            Error::RouteIntersectRedZone
+        );
+        // And this one starts inside the zone, not crossing any of it's edges
+        let waypoints = construct_route(&[
+            (["55.392", "37.384", "1"], 10),
+            (["55.392", "37.387", "1"], 20),
+        ]);
+        assert_noop!(
+            DSMapsModule::route_add(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                waypoints,
+                ROOT_ID,
+                ROUTE_AREAS,
+            ),
+            Error::RouteIntersectRedZone
+        );
+    });
+}
+
+#[test]
+fn it_add_circle_zone() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(
+            DSAccountsModule::account_add(
+                Origin::signed(ADMIN_ACCOUNT_ID),
+                REGISTRAR_1_ACCOUNT_ID,
+                super::REGISTRAR_ROLE
+            )
+        );
+        assert_ok!(
+            DSMapsModule::root_add(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                construct_testing_box(),
+                coord(DELTA),
+            )
+        );
+        let center = Point2D::new(coord("55.396"), coord("37.386"));
+        assert_noop!(
+            DSMapsModule::zone_add_circle(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                center,
+                0,
+                DEFAULT_HEIGHT,
+                ROOT_ID,
+            ),
+            Error::InvalidData
+        );
+        // 2 km circle doesn't fit into the root
+        assert_noop!(
+            DSMapsModule::zone_add_circle(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                center,
+                2000,
+                DEFAULT_HEIGHT,
+                ROOT_ID,
+            ),
+            Error::ZoneDoesntFit
+        );
+        assert_ok!(
+            DSMapsModule::zone_add_circle(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                center,
+                300,
+                DEFAULT_HEIGHT,
+                ROOT_ID,
+            )
+        );
+        let zone = DSMapsModule::zone_data(DSMapsModule::pack_index(ROOT_ID, AREA_ID, 0));
+        assert_eq!(zone.shape, ZoneShape::Circle(center, 300));
+        // Bounding rect is ~0.0027 degrees of latitude and ~0.0048 degrees of longitude from center
+        assert!(zone.rect.is_point_inside(Point2D::new(coord("55.3935"), coord("37.3815"))));
+        assert!(!zone.rect.is_point_inside(Point2D::new(coord("55.393"), coord("37.381"))));
+    });
+}
+
+#[test]
+fn it_add_route_near_circle_zone() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(
+            DSAccountsModule::account_add(
+                Origin::signed(ADMIN_ACCOUNT_ID),
+                REGISTRAR_1_ACCOUNT_ID,
+                super::REGISTRAR_ROLE
+            )
+        );
+        assert_ok!(
+            DSMapsModule::root_add(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                construct_testing_box(),
+                coord(DELTA),
+            )
+        );
+        assert_ok!(
+            DSMapsModule::zone_add_circle(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                Point2D::new(coord("55.396"), coord("37.386")),
+                300,
+                DEFAULT_HEIGHT,
+                ROOT_ID,
+            )
+        );
+        // Passes the corner of bounding rect, ~330 metres from the center
+        let waypoints = construct_route(&[
+            (["55.3935", "37.3895", "1"], 10),
+            (["55.3945", "37.3905", "1"], 20),
+        ]);
+        assert_ok!(
+            DSMapsModule::route_add(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                waypoints,
+                ROOT_ID,
+                ROUTE_AREAS,
+            )
+        );
+        // Passes ~220 metres from the center
+        let waypoints = construct_route(&[
+            (["55.392", "37.3895", "1"], 10),
+            (["55.399", "37.3895", "1"], 20),
+        ]);
+        assert_noop!(
+            DSMapsModule::route_add(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                waypoints,
+                ROOT_ID,
+                ROUTE_AREAS,
+            ),
+            Error::RouteIntersectRedZone
+        );
+    });
+}
+
+#[test]
+fn it_add_route_and_store_it() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(
+            DSAccountsModule::account_add(
+                Origin::signed(ADMIN_ACCOUNT_ID),
+                REGISTRAR_1_ACCOUNT_ID,
+                super::REGISTRAR_ROLE
+            )
+        );
+        assert_ok!(
+            DSMapsModule::root_add(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                construct_testing_box(),
+                coord(DELTA),
+            )
+        );
+        assert_eq!(DSMapsModule::next_route_id(), 0);
+        assert_ok!(
+            DSMapsModule::route_add(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                construct_testing_waypoints(),
+                ROOT_ID,
+                ROUTE_AREAS,
+            )
+        );
+        assert_eq!(DSMapsModule::next_route_id(), 1);
+        let route = DSMapsModule::route_data(0);
+        assert_eq!(route.pilot, REGISTRAR_1_ACCOUNT_ID);
+        assert_eq!(route.waypoints, construct_testing_waypoints());
+        assert_eq!(route.root_id, ROOT_ID);
+        assert_eq!(route.status, RouteStatus::Planned);
+    });
+}
+
+#[test]
+fn it_change_route_status() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(
+            DSAccountsModule::account_add(
+                Origin::signed(ADMIN_ACCOUNT_ID),
+                REGISTRAR_1_ACCOUNT_ID,
+                super::REGISTRAR_ROLE
+            )
+        );
+        assert_ok!(
+            DSMapsModule::root_add(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                construct_testing_box(),
+                coord(DELTA),
+            )
+        );
+        assert_ok!(
+            DSMapsModule::route_add(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                construct_testing_waypoints(),
+                ROOT_ID,
+                ROUTE_AREAS,
+            )
+        );
+        assert_noop!(
+            DSMapsModule::route_activate(Origin::signed(REGISTRAR_1_ACCOUNT_ID), 1),
+            Error::RouteDoesNotExist
+        );
+        assert_noop!(
+            DSMapsModule::route_activate(Origin::signed(ADMIN_ACCOUNT_ID), 0),
+            Error::NotAuthorized
+        );
+        // Can't complete a route, which wasn't flown
+        assert_noop!(
+            DSMapsModule::route_complete(Origin::signed(REGISTRAR_1_ACCOUNT_ID), 0),
+            Error::WrongRouteStatus
+        );
+        assert_ok!(DSMapsModule::route_activate(Origin::signed(REGISTRAR_1_ACCOUNT_ID), 0));
+        assert_eq!(DSMapsModule::route_data(0).status, RouteStatus::Active);
+        assert_ok!(DSMapsModule::route_complete(Origin::signed(REGISTRAR_1_ACCOUNT_ID), 0));
+        assert_eq!(DSMapsModule::route_data(0).status, RouteStatus::Completed);
+        assert_noop!(
+            DSMapsModule::route_cancel(Origin::signed(REGISTRAR_1_ACCOUNT_ID), 0),
+            Error::WrongRouteStatus
+        );
+    });
+}
+
+#[test]
+fn it_cancel_route() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(
+            DSAccountsModule::account_add(
+                Origin::signed(ADMIN_ACCOUNT_ID),
+                REGISTRAR_1_ACCOUNT_ID,
+                super::REGISTRAR_ROLE
+            )
+        );
+        assert_ok!(
+            DSAccountsModule::account_add(
+                Origin::signed(ADMIN_ACCOUNT_ID),
+                REGISTRAR_2_ACCOUNT_ID,
+                super::REGISTRAR_ROLE
+            )
+        );
+        assert_ok!(
+            DSAccountsModule::register_pilot(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                PILOT_1_ACCOUNT_ID,
+                Vec::new()
+            )
+        );
+        assert_ok!(
+            DSMapsModule::root_add(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                construct_testing_box(),
+                coord(DELTA),
+            )
+        );
+        assert_ok!(
+            DSMapsModule::route_add(
+                Origin::signed(PILOT_1_ACCOUNT_ID),
+                construct_testing_waypoints(),
+                ROOT_ID,
+                ROUTE_AREAS,
+            )
+        );
+        // Other accounts can't cancel pilot's route, except registrars
+        assert_noop!(
+            DSMapsModule::route_cancel(Origin::signed(ADMIN_ACCOUNT_ID), 0),
+            Error::NotAuthorized
+        );
+        assert_ok!(DSMapsModule::route_cancel(Origin::signed(REGISTRAR_2_ACCOUNT_ID), 0));
+        assert_eq!(DSMapsModule::route_data(0).status, RouteStatus::Cancelled);
+        assert_noop!(
+            DSMapsModule::route_activate(Origin::signed(PILOT_1_ACCOUNT_ID), 0),
+            Error::WrongRouteStatus
+        );
+    });
+}
+
+// Two routes cross each other at the same altitude
+fn construct_crossing_routes(delay: u64) -> (Vec<Waypoint<Coord, Moment>>, Vec<Waypoint<Coord, Moment>>) {
+    let first = construct_route(&[
+        (["55.393", "37.3855", "1"], 10),
+        (["55.398", "37.3855", "1"], 20),
+    ]);
+    let second = construct_route(&[
+        (["55.3955", "37.383", "1"], 10 + delay),
+        (["55.3955", "37.388", "1"], 20 + delay),
+    ]);
+    (first, second)
+}
+
+#[test]
+fn it_add_conflicting_routes() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(
+            DSAccountsModule::account_add(
+                Origin::signed(ADMIN_ACCOUNT_ID),
+                REGISTRAR_1_ACCOUNT_ID,
+                super::REGISTRAR_ROLE
+            )
+        );
+        assert_ok!(
+            DSMapsModule::root_add(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                construct_testing_box(),
+                coord(DELTA),
+            )
+        );
+        let (first, second) = construct_crossing_routes(0);
+        assert_ok!(
+            DSMapsModule::route_add(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                first,
+                ROOT_ID,
+                ROUTE_AREAS,
+            )
+        );
+        assert_eq!(DSMapsModule::routes_in_area(ROOT_ID, AREA_ID), vec![0]);
+        assert_noop!(
+            DSMapsModule::route_add(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                second,
+                ROOT_ID,
+                ROUTE_AREAS,
+            ),
+            Error::RouteConflict
+        );
+        // Crossing is passed within time separation
+        let (_, second) = construct_crossing_routes(50);
+        assert_noop!(
+            DSMapsModule::route_add(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                second,
+                ROOT_ID,
+                ROUTE_AREAS,
+            ),
+            Error::RouteConflict
+        );
+        // Enough time passes between the drones
+        let (_, second) = construct_crossing_routes(100);
+        assert_ok!(
+            DSMapsModule::route_add(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                second,
+                ROOT_ID,
+                ROUTE_AREAS,
+            )
+        );
+    });
+}
+
+#[test]
+fn it_add_route_near_route_in_next_area() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(
+            DSAccountsModule::account_add(
+                Origin::signed(ADMIN_ACCOUNT_ID),
+                REGISTRAR_1_ACCOUNT_ID,
+                super::REGISTRAR_ROLE
+            )
+        );
+        assert_ok!(
+            DSMapsModule::root_add(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                construct_testing_box(),
+                coord(DELTA),
+            )
+        );
+        // Routes are on both sides of the border between areas, about 45 metres from each other
+        assert_ok!(
+            DSMapsModule::route_add(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                construct_route(&[
+                    (["55.4008", "37.383", "1"], 10),
+                    (["55.4008", "37.388", "1"], 20),
+                ]),
+                ROOT_ID,
+                ROUTE_AREAS,
+            )
+        );
+        assert_eq!(DSMapsModule::routes_in_area(ROOT_ID, AREA_ID), vec![0]);
+        let next_area = DSMapsModule::validate_route(&construct_route(&[
+            (["55.4012", "37.383", "1"], 10),
+            (["55.4012", "37.388", "1"], 20),
+        ]), ROOT_ID).segment_areas[0].clone();
+        assert_eq!(next_area, vec![AREA_ID + 1]);
+        assert_noop!(
+            DSMapsModule::route_add(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                construct_route(&[
+                    (["55.4012", "37.383", "1"], 10),
+                    (["55.4012", "37.388", "1"], 20),
+                ]),
+                ROOT_ID,
+                ROUTE_AREAS,
+            ),
+            Error::RouteConflict
+        );
+        // Two areas away, the route is far enough
+        assert_ok!(
+            DSMapsModule::route_add(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                construct_route(&[
+                    (["55.4112", "37.383", "1"], 10),
+                    (["55.4112", "37.388", "1"], 20),
+                ]),
+                ROOT_ID,
+                ROUTE_AREAS,
+            )
+        );
+    });
+}
+
+#[test]
+fn it_add_routes_up_to_area_limit() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(
+            DSAccountsModule::account_add(
+                Origin::signed(ADMIN_ACCOUNT_ID),
+                REGISTRAR_1_ACCOUNT_ID,
+                super::REGISTRAR_ROLE
+            )
+        );
+        assert_ok!(
+            DSMapsModule::root_add(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                construct_testing_box(),
+                coord(DELTA),
+            )
+        );
+        // Same path, flown with enough time between the drones
+        let route_at = |start: u64| construct_route(&[
+            (["55.393", "37.3855", "1"], start),
+            (["55.398", "37.3855", "1"], start + 10),
+        ]);
+        for route_id in 0..3 {
+            assert_ok!(DSMapsModule::route_add(Origin::signed(REGISTRAR_1_ACCOUNT_ID), route_at(10 + 100 * route_id), ROOT_ID, ROUTE_AREAS));
+        }
+        assert_eq!(DSMapsModule::routes_in_area(ROOT_ID, AREA_ID), vec![0, 1, 2]);
+        assert_eq!(
+            DSMapsModule::validate_route(&route_at(310), ROOT_ID).violations,
+            vec![RouteViolation::TooManyRoutesInArea(AREA_ID)]
+        );
+        assert_noop!(
+            DSMapsModule::route_add(Origin::signed(REGISTRAR_1_ACCOUNT_ID), route_at(310), ROOT_ID, ROUTE_AREAS),
+            Error::TooManyRoutesInArea
+        );
+
+        // First route ends at 20, and can't conflict with anything after 80, so it's forgotten
+        Timestamp::set_timestamp(100);
+        assert_ok!(DSMapsModule::route_add(Origin::signed(REGISTRAR_1_ACCOUNT_ID), route_at(310), ROOT_ID, ROUTE_AREAS));
+        assert_eq!(DSMapsModule::routes_in_area(ROOT_ID, AREA_ID), vec![1, 2, 3]);
+    });
+}
+
+#[test]
+fn it_add_route_after_conflicting_one_cancelled() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(
+            DSAccountsModule::account_add(
+                Origin::signed(ADMIN_ACCOUNT_ID),
+                REGISTRAR_1_ACCOUNT_ID,
+                super::REGISTRAR_ROLE
+            )
+        );
+        assert_ok!(
+            DSMapsModule::root_add(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                construct_testing_box(),
+                coord(DELTA),
+            )
+        );
+        let (first, second) = construct_crossing_routes(0);
+        assert_ok!(
+            DSMapsModule::route_add(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                first,
+                ROOT_ID,
+                ROUTE_AREAS,
+            )
+        );
+        assert_noop!(
+            DSMapsModule::route_add(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                second.clone(),
+                ROOT_ID,
+                ROUTE_AREAS,
+            ),
+            Error::RouteConflict
+        );
+        assert_ok!(DSMapsModule::route_cancel(Origin::signed(REGISTRAR_1_ACCOUNT_ID), 0));
+        assert!(DSMapsModule::routes_in_area(ROOT_ID, AREA_ID).is_empty());
+        assert_ok!(
+            DSMapsModule::route_add(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                second,
+                ROOT_ID,
+                ROUTE_AREAS,
+            )
+        );
+    });
+}
+
+#[test]
+fn it_add_temporary_zone() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(
+            DSAccountsModule::account_add(
+                Origin::signed(ADMIN_ACCOUNT_ID),
+                REGISTRAR_1_ACCOUNT_ID,
+                super::REGISTRAR_ROLE
+            )
+        );
+        assert_ok!(
+            DSMapsModule::root_add(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                construct_testing_box(),
+                coord(DELTA),
+            )
+        );
+        assert_noop!(
+            DSMapsModule::zone_add_temporary(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                construct_testing_rect(),
+                DEFAULT_HEIGHT,
+                ROOT_ID,
+                200,
+                100,
+            ),
+            Error::WrongTimeSupplied
+        );
+        assert_ok!(
+            DSMapsModule::zone_add(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                construct_testing_rect(),
+                DEFAULT_HEIGHT,
+                ROOT_ID,
+            )
+        );
+        // Restriction covers the building and lots of space around it
+        assert_ok!(
+            DSMapsModule::zone_add_temporary(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                construct_custom_rect("55.392", "37.382", "55.399", "37.389"),
+                DEFAULT_HEIGHT,
+                ROOT_ID,
+                100,
+                200,
+            )
+        );
+        let zone = DSMapsModule::zone_data(DSMapsModule::pack_index(ROOT_ID, AREA_ID, 1));
+        assert_eq!(zone.time_window, Some((100, 200)));
+        // Block is expected to last 6 seconds, so the zone is checked in the next block
+        assert_eq!(DSMapsModule::zones_expiry(1, DSMapsModule::pack_index(ROOT_ID, AREA_ID, 1)), 200);
+    });
+}
+
+#[test]
+fn it_add_route_through_temporary_zone() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(
+            DSAccountsModule::account_add(
+                Origin::signed(ADMIN_ACCOUNT_ID),
+                REGISTRAR_1_ACCOUNT_ID,
+                super::REGISTRAR_ROLE
+            )
+        );
+        assert_ok!(
+            DSMapsModule::root_add(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                construct_testing_box(),
+                coord(DELTA),
+            )
+        );
+        assert_ok!(
+            DSMapsModule::zone_add_temporary(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                construct_testing_rect(),
+                DEFAULT_HEIGHT,
+                ROOT_ID,
+                100,
+                200,
+            )
+        );
+        // Flown before the restriction starts
+        let waypoints = construct_route(&[
+            (["55.393", "37.3855", "1"], 10),
+            (["55.398", "37.3855", "1"], 20),
+        ]);
+        assert_ok!(
+            DSMapsModule::route_add(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                waypoints,
+                ROOT_ID,
+                ROUTE_AREAS,
+            )
+        );
+        // Flown while the restriction is active
+        let waypoints = construct_route(&[
+            (["55.393", "37.3855", "1"], 150),
+            (["55.398", "37.3855", "1"], 160),
+        ]);
+        assert_noop!(
+            DSMapsModule::route_add(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                waypoints,
+                ROOT_ID,
+                ROUTE_AREAS,
+            ),
+            Error::RouteIntersectRedZone
+        );
+        // Flown after the restriction is over
+        let waypoints = construct_route(&[
+            (["55.393", "37.3855", "1"], 300),
+            (["55.398", "37.3855", "1"], 310),
+        ]);
+        assert_ok!(
+            DSMapsModule::route_add(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                waypoints,
+                ROOT_ID,
+                ROUTE_AREAS,
+            )
+        );
+    });
+}
+
+#[test]
+fn it_prune_expired_zones_up_to_limit() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(
+            DSAccountsModule::account_add(
+                Origin::signed(ADMIN_ACCOUNT_ID),
+                REGISTRAR_1_ACCOUNT_ID,
+                super::REGISTRAR_ROLE
+            )
+        );
+        assert_ok!(
+            DSMapsModule::root_add(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                construct_testing_box(),
+                coord(DELTA),
+            )
+        );
+        // Temporary zones may overlap
+        for _ in 0..4 {
+            assert_ok!(
+                DSMapsModule::zone_add_temporary(
+                    Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                    construct_testing_rect(),
+                    DEFAULT_HEIGHT,
+                    ROOT_ID,
+                    100,
+                    200,
+                )
+            );
+        }
+        let zone_id = |child| DSMapsModule::pack_index(ROOT_ID, AREA_ID, child);
+        // Removed zone's expiry is skipped, but still counted
+        assert_ok!(DSMapsModule::zone_remove(Origin::signed(REGISTRAR_1_ACCOUNT_ID), zone_id(3)));
+        assert_eq!(super::ZonesExpiry::<Test>::iter_prefix(1).count(), 4);
+
+        Timestamp::set_timestamp(300);
+        DSMapsModule::on_initialize(1);
+        assert_eq!(super::ZonesExpiry::<Test>::iter_prefix(1).count(), 2);
+        assert_eq!(DSMapsModule::next_pruned_block(), 1);
+        // Zones, left by the limit, are pruned in the next block
+        DSMapsModule::on_initialize(2);
+        assert!(DSMapsModule::zones_in_area(ROOT_ID, AREA_ID).is_empty());
+        assert!(super::ZonesExpiry::<Test>::iter().next().is_none());
+        assert_eq!(DSMapsModule::next_pruned_block(), 2);
+    });
+}
+
+#[test]
+fn it_prune_expired_zones() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(
+            DSAccountsModule::account_add(
+                Origin::signed(ADMIN_ACCOUNT_ID),
+                REGISTRAR_1_ACCOUNT_ID,
+                super::REGISTRAR_ROLE
+            )
+        );
+        assert_ok!(
+            DSMapsModule::root_add(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                construct_testing_box(),
+                coord(DELTA),
+            )
+        );
+        assert_ok!(
+            DSMapsModule::zone_add_temporary(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                construct_testing_rect(),
+                DEFAULT_HEIGHT,
+                ROOT_ID,
+                100,
+                200,
+            )
+        );
+        let zone_id = DSMapsModule::pack_index(ROOT_ID, AREA_ID, 0);
+        Timestamp::set_timestamp(150);
+        DSMapsModule::on_initialize(1);
+        assert!(DSMapsModule::zone_data(zone_id).time_window.is_some());
+        // Block came earlier, than the zone is over
+        assert!(!super::ZonesExpiry::<Test>::contains_key(1, zone_id));
+        assert_eq!(DSMapsModule::zones_expiry(2, zone_id), 200);
+
+        Timestamp::set_timestamp(200);
+        DSMapsModule::on_initialize(2);
+        assert!(DSMapsModule::zone_data(zone_id).time_window.is_none());
+        assert!(super::ZonesExpiry::<Test>::iter().next().is_none());
+        assert_noop!(
+            DSMapsModule::zone_remove(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                zone_id,
+            ),
+            Error::ZoneDoesntExist
+        );
+    });
+}
+
+// Zone lies on the border between areas 58 and 59
+#[test]
+fn it_add_zone_in_multiple_areas() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(
+            DSAccountsModule::account_add(
+                Origin::signed(ADMIN_ACCOUNT_ID),
+                REGISTRAR_1_ACCOUNT_ID,
+                super::REGISTRAR_ROLE
+            )
+        );
+        assert_ok!(
+            DSMapsModule::root_add(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                construct_testing_box(),
+                coord(DELTA),
+            )
+        );
+        // Zone, which overlaps 6 areas, is too big
+        assert_noop!(
+            DSMapsModule::zone_add(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                construct_custom_rect("55.392", "37.382", "55.445", "37.384"),
+                DEFAULT_HEIGHT,
+                ROOT_ID,
+            ),
+            Error::ZoneDoesntFit
+        );
+        assert_ok!(
+            DSMapsModule::zone_add(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                construct_custom_rect("55.399", "37.385", "55.403", "37.386"),
+                DEFAULT_HEIGHT,
+                ROOT_ID,
+            )
+        );
+        let zone_id = DSMapsModule::pack_index(ROOT_ID, AREA_ID, 0);
+        let second_slot = DSMapsModule::pack_index(ROOT_ID, AREA_ID + 1, 0);
+        // Both areas refer to the same zone
+        assert_eq!(DSMapsModule::zone_data(zone_id).zone_id, zone_id);
+        assert_eq!(DSMapsModule::zone_data(second_slot).zone_id, zone_id);
+        assert!(DSMapsModule::area_info(ROOT_ID, AREA_ID + 1).area_type == super::GREEN_AREA);
+        // Route crosses only the part of zone, lying in area 59
+        let waypoints = construct_route(&[
+            (["55.402", "37.383", "1"], 10),
+            (["55.402", "37.389", "1"], 20),
+        ]);
+        assert_noop!(
+            DSMapsModule::route_add(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                waypoints.clone(),
+                ROOT_ID,
+                ROUTE_AREAS,
+            ),
+            Error::RouteIntersectRedZone
+        );
+        // Zone is removed from both areas
+        assert_ok!(
+            DSMapsModule::zone_remove(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                second_slot,
+            )
+        );
+        assert_eq!(DSMapsModule::zone_data(zone_id).zone_id, 0);
+        assert_eq!(DSMapsModule::zone_data(second_slot).zone_id, 0);
+        assert_ok!(
+            DSMapsModule::route_add(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                waypoints,
+                ROOT_ID,
+                ROUTE_AREAS,
+            )
+        );
+    });
+}
+
+#[test]
+fn it_validate_route_and_report_all_violations() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(
+            DSAccountsModule::account_add(
+                Origin::signed(ADMIN_ACCOUNT_ID),
+                REGISTRAR_1_ACCOUNT_ID,
+                super::REGISTRAR_ROLE
+            )
+        );
+        assert_ok!(
+            DSMapsModule::root_add(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                construct_testing_box(),
+                coord(DELTA),
+            )
+        );
+        assert_ok!(
+            DSMapsModule::zone_add(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                construct_testing_rect(),
+                DEFAULT_HEIGHT,
+                ROOT_ID,
+            )
+        );
+        // In area 59
+        assert_ok!(
+            DSMapsModule::zone_add(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                construct_custom_rect("55.405", "37.385", "55.406", "37.386"),
+                DEFAULT_HEIGHT,
+                ROOT_ID,
+            )
+        );
+        assert_ok!(
+            DSMapsModule::route_add(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                construct_route(&[
+                    (["55.392", "37.382", "1"], 10),
+                    (["55.392", "37.389", "1"], 20),
+                ]),
+                ROOT_ID,
+                ROUTE_AREAS,
+            )
+        );
+        // Crosses the route above, then both zones
+        let waypoints = construct_route(&[
+            (["55.3915", "37.3855", "1"], 10),
+            (["55.4", "37.3855", "1"], 20),
+            (["55.407", "37.3855", "1"], 30),
+        ]);
+        let report = DSMapsModule::validate_route(&waypoints, ROOT_ID);
+        assert!(!report.is_valid());
+        assert_eq!(report.segment_areas.len(), 2);
+        assert_eq!(report.segment_areas[0], vec![AREA_ID]);
+        assert!(report.segment_areas[1].contains(&(AREA_ID + 1)));
+        assert_eq!(report.violations, vec![
+            RouteViolation::ZoneCrossed(0, DSMapsModule::pack_index(ROOT_ID, AREA_ID, 0)),
+            RouteViolation::ZoneCrossed(1, DSMapsModule::pack_index(ROOT_ID, AREA_ID + 1, 0)),
+            RouteViolation::RouteConflict(0, 0),
+        ]);
+        // Nothing is changed, and route_add fails on the first problem
+        assert_eq!(DSMapsModule::next_route_id(), 1);
+        assert_noop!(
+            DSMapsModule::route_add(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                waypoints,
+                ROOT_ID,
+                ROUTE_AREAS,
+            ),
+            Error::RouteIntersectRedZone
+        );
+
+        // Problems with time and bounds
+        Timestamp::set_timestamp(100);
+        let waypoints = construct_route(&[
+            (["55.3915", "37.3855", "1"], 20),
+            (["0.5", "0.5", "1"], 10),
+        ]);
+        let report = DSMapsModule::validate_route(&waypoints, ROOT_ID);
+        assert!(report.segment_areas.is_empty());
+        assert_eq!(report.violations, vec![
+            RouteViolation::ArrivalInPast,
+            RouteViolation::WrongArrivalTime(1),
+            RouteViolation::WaypointOutsideRoot(1),
+        ]);
+
+        let report = DSMapsModule::validate_route(&waypoints[..1], ROOT_ID);
+        assert_eq!(report.violations, vec![RouteViolation::TooFewWaypoints]);
+        let report = DSMapsModule::validate_route(&waypoints, 1);
+        assert_eq!(report.violations, vec![RouteViolation::RootDoesNotExist]);
+    });
+}
+
+#[test]
+fn it_validate_route_in_root_with_too_many_areas() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(
+            DSAccountsModule::account_add(
+                Origin::signed(ADMIN_ACCOUNT_ID),
+                REGISTRAR_1_ACCOUNT_ID,
+                super::REGISTRAR_ROLE
+            )
+        );
+        // Such root can't be added now, but might be stored before areas were checked
+        super::RootBoxes::<Test>::insert(ROOT_ID, crate::RootBox::new(ROOT_ID, construct_testing_box(), coord("0.000005")));
+        let report = DSMapsModule::validate_route(&construct_testing_waypoints(), ROOT_ID);
+        assert_eq!(report.violations, vec![RouteViolation::CoordOverflow, RouteViolation::CoordOverflow]);
+        assert_noop!(
+            DSMapsModule::route_add(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                construct_testing_waypoints(),
+                ROOT_ID,
+                ROUTE_AREAS,
+            ),
+            Error::CoordOverflow
+        );
+    });
+}
+
+#[test]
+fn it_charges_route_by_read_areas_and_zones() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(
+            DSAccountsModule::account_add(
+                Origin::signed(ADMIN_ACCOUNT_ID),
+                REGISTRAR_1_ACCOUNT_ID,
+                super::REGISTRAR_ROLE
+        ));
+        assert_ok!(
+            DSMapsModule::root_add(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                construct_testing_box(),
+                coord(DELTA),
+        ));
+        // Zone lies in the same area, as the route, but far from it
+        assert_ok!(
+            DSMapsModule::zone_add(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                construct_custom_rect("55.392", "37.389", "55.393", "37.390"),
+                DEFAULT_HEIGHT,
+                ROOT_ID
+        ));
+
+        // Area is read while checking zones, then it and 8 areas around are read to find routes nearby
+        let post_info = DSMapsModule::route_add(
+            Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+            construct_testing_waypoints(),
+            ROOT_ID,
+            ROUTE_AREAS,
+        ).unwrap();
+        let actual_weight = <() as crate::WeightInfo>::route_add(2, 10, 1, 0);
+        assert_eq!(post_info.actual_weight, Some(actual_weight));
+        assert!(actual_weight < DSMapsModule::route_add_max_weight(2, 10));
+        // Longer routes and higher limits are declared heavier
+        assert!(DSMapsModule::route_add_max_weight(2, 10) < DSMapsModule::route_add_max_weight(3, 10));
+        assert!(DSMapsModule::route_add_max_weight(2, 10) < DSMapsModule::route_add_max_weight(2, 11));
+    });
+}
+
+#[test]
+fn it_charges_route_status_by_route_areas() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(
+            DSAccountsModule::account_add(
+                Origin::signed(ADMIN_ACCOUNT_ID),
+                REGISTRAR_1_ACCOUNT_ID,
+                super::REGISTRAR_ROLE
+        ));
+        assert_ok!(
+            DSMapsModule::root_add(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                construct_testing_box(),
+                coord(DELTA),
+        ));
+        assert_ok!(
+            DSMapsModule::route_add(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                construct_testing_waypoints(),
+                ROOT_ID,
+                ROUTE_AREAS,
+        ));
+        // Route crosses one area, and only it forgets the route
+        let post_info = DSMapsModule::route_cancel(Origin::signed(REGISTRAR_1_ACCOUNT_ID), 0).unwrap();
+        let actual_weight = <() as crate::WeightInfo>::change_route_status(1);
+        assert_eq!(post_info.actual_weight, Some(actual_weight));
+        assert!(actual_weight < <() as crate::WeightInfo>::change_route_status(MaxAreasPerRoute::get()));
+        assert_eq!(super::RoutesInArea::iter_prefix(ROOT_ID).filter(|(_, routes)| !routes.is_empty()).count(), 0);
+        // Limit of read areas is capped, and so is the declared weight of the addition
+        assert_eq!(DSMapsModule::route_add_max_weight(2, ROUTE_AREAS),
+                   DSMapsModule::route_add_max_weight(2, MaxAreasPerRoute::get()));
+    });
+}
+
+#[test]
+fn it_try_to_add_route_over_area_limit() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(
+            DSAccountsModule::account_add(
+                Origin::signed(ADMIN_ACCOUNT_ID),
+                REGISTRAR_1_ACCOUNT_ID,
+                super::REGISTRAR_ROLE
+        ));
+        assert_ok!(
+            DSMapsModule::root_add(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                construct_testing_box(),
+                coord(DELTA),
+        ));
+        // Crossed area, then it and 8 areas around are read
+        let waypoints = construct_testing_waypoints();
+        let report = DSMapsModule::validate_route(&waypoints, ROOT_ID);
+        assert!(report.is_valid());
+        assert_eq!(report.read_areas, 10);
+
+        assert_noop!(
+            DSMapsModule::route_add(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                waypoints.clone(),
+                ROOT_ID,
+                report.read_areas - 1,
+            ),
+            Error::AreaLimitExceeded
+        );
+        assert_ok!(
+            DSMapsModule::route_add(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                waypoints,
+                ROOT_ID,
+                report.read_areas,
+        ));
+    });
+}
+
+#[test]
+fn it_try_to_add_route_with_too_many_waypoints() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(
+            DSAccountsModule::account_add(
+                Origin::signed(ADMIN_ACCOUNT_ID),
+                REGISTRAR_1_ACCOUNT_ID,
+                super::REGISTRAR_ROLE
+        ));
+        assert_ok!(
+            DSMapsModule::root_add(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                construct_testing_box(),
+                coord(DELTA),
+        ));
+        // Mock allows 8 waypoints
+        let waypoints: Vec<Waypoint<Coord, Moment>> = (0..9_u64)
+            .map(|i| Waypoint::new(Point3D::new(coord("55.395"), coord("37.385"), coord("1")), 100 + i))
+            .collect();
+        assert_noop!(
+            DSMapsModule::route_add(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                waypoints.clone(),
+                ROOT_ID,
+                ROUTE_AREAS,
+            ),
+            Error::TooManyWaypoints
+        );
+        let report = DSMapsModule::validate_route(&waypoints, ROOT_ID);
+        assert_eq!(report.violations, vec![RouteViolation::TooManyWaypoints]);
+    });
+}
+
+fn raw(s: &str) -> i32 {
+    coord::<Coord>(s).to_bits()
+}
+
+fn construct_testing_map() -> crate::GenesisConfig {
+    crate::GenesisConfig {
+        roots: vec![([raw("55.371"), raw("37.371"), raw("1"), raw("55.921"), raw("37.901"), raw("3")], raw(DELTA))],
+        zones: vec![([raw("55.395"), raw("37.385"), raw("55.396"), raw("37.386")], DEFAULT_HEIGHT, ROOT_ID)],
+        // Area to the north of the zone is forbidden
+        area_types: vec![(ROOT_ID, AREA_ID + 1, 0)],
+    }
+}
+
+#[test]
+fn it_builds_map_at_genesis() {
+    new_test_ext_with_map(construct_testing_map()).execute_with(|| {
+        assert_eq!(DSMapsModule::get_root_index([raw("55.395"), raw("37.385")]), ROOT_ID);
+        assert_eq!(DSMapsModule::get_max_area(ROOT_ID), 2915);
+
+        let zones = DSMapsModule::get_area_zones(ROOT_ID, AREA_ID);
+        assert_eq!(zones.len(), 1);
+        assert_eq!(zones[0].rect, construct_testing_rect());
+        assert_eq!(DSMapsModule::get_area_type(ROOT_ID, AREA_ID), Some(crate::GREEN_AREA));
+        assert_eq!(DSMapsModule::get_area_type(ROOT_ID, AREA_ID + 1), Some(0));
+
+        // Genesis map behaves the same way, as the one added by extrinsics
+        assert_noop!(
+            DSMapsModule::root_add(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                construct_testing_box(),
+                coord(DELTA),
+            ),
+            Error::OverlappingRoot
+        );
+        assert_noop!(
+            DSMapsModule::zone_add(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                construct_testing_rect(),
+                DEFAULT_HEIGHT,
+                ROOT_ID,
+            ),
+            Error::OverlappingZone
+        );
+        assert_noop!(
+            DSMapsModule::zone_add(
+                Origin::signed(REGISTRAR_1_ACCOUNT_ID),
+                construct_custom_rect("55.402", "37.382", "55.403", "37.383"),
+                DEFAULT_HEIGHT,
+                ROOT_ID,
+            ),
+            Error::ForbiddenArea
+        );
+    });
+}
+
+#[test]
+#[should_panic(expected = "can't be added")]
+fn it_try_to_build_overlapping_roots_at_genesis() {
+    let mut map = construct_testing_map();
+    map.roots.push(map.roots[0]);
+    new_test_ext_with_map(map);
+}
+
+#[test]
+#[should_panic(expected = "can't be added")]
+fn it_try_to_build_zone_without_root_at_genesis() {
+    let mut map = construct_testing_map();
+    map.roots.clear();
+    new_test_ext_with_map(map);
+}
+
+type Quadtree = crate::index::QuadtreeIndex<Test>;
+
+/// Id and cells of the root, which is made of the box
+fn construct_root_cells(bounding_box: Box3D<Coord>) -> (u64, [u32; 4]) {
+    let (sw_row, sw_column) = Page::<Coord, Test>::get_cell_indexes(bounding_box.south_west).unwrap();
+    let (ne_row, ne_column) = Page::<Coord, Test>::get_cell_indexes(bounding_box.north_east).unwrap();
+    (crate::RootBox::<Coord>::get_index(sw_row, sw_column, ne_row, ne_column), [sw_row, sw_column, ne_row, ne_column])
+}
+
+fn insert_to_quadtree(bounding_box: Box3D<Coord>) -> DispatchResult {
+    let (root_id, cells) = construct_root_cells(bounding_box);
+    Quadtree::insert(root_id, cells, &mut Quadtree::search_budget())
+}
+
+fn remove_from_quadtree(bounding_box: Box3D<Coord>) {
+    let (root_id, cells) = construct_root_cells(bounding_box);
+    Quadtree::remove(root_id, cells);
+}
+
+fn find_in_quadtree(lat: &str, lon: &str) -> u64 {
+    let point = Point3D::new(coord(lat), coord(lon), coord("1"));
+    let (row, column) = Page::<Coord, Test>::get_cell_indexes(point).unwrap();
+    Quadtree::find(row, column)
+}
+
+#[test]
+fn it_store_roots_of_any_size_in_quadtree() {
+    new_test_ext().execute_with(|| {
+        // Country-scale root, far beyond the bitmap's page limit
+        let country = construct_custom_box("45.0", "10.0", "55.0", "30.0");
+        let neighbour = construct_custom_box("45.0", "30.0", "45.5", "30.5");
+        let small = construct_custom_box("49.0", "19.0", "49.1", "19.1");
+        assert_ok!(insert_to_quadtree(country));
+        assert_ok!(insert_to_quadtree(construct_testing_box()));
+        assert_ok!(insert_to_quadtree(neighbour));
+        assert_eq!(find_in_quadtree("50.0", "20.0"), construct_root_cells(country).0);
+        assert_eq!(find_in_quadtree("45.0", "10.0"), construct_root_cells(country).0);
+        assert_eq!(find_in_quadtree("55.395", "37.385"), ROOT_ID);
+        assert_eq!(find_in_quadtree("45.2", "30.2"), construct_root_cells(neighbour).0);
+        assert_eq!(find_in_quadtree("0.0", "0.0"), 0);
+
+        // Small root inside the big one, and big root around the small one
+        assert_noop!(insert_to_quadtree(small), Error::OverlappingRoot);
+        assert_noop!(
+            insert_to_quadtree(construct_custom_box("55.0", "35.0", "60.0", "40.0")),
+            Error::OverlappingRoot
+        );
+
+        remove_from_quadtree(country);
+        assert_eq!(find_in_quadtree("50.0", "20.0"), 0);
+        assert_eq!(find_in_quadtree("55.395", "37.385"), ROOT_ID);
+        assert_ok!(insert_to_quadtree(small));
+        // Root, which is not stored, is ignored
+        remove_from_quadtree(country);
+        assert_eq!(find_in_quadtree("49.05", "19.05"), construct_root_cells(small).0);
+
+        // Nodes without roots are removed
+        remove_from_quadtree(small);
+        remove_from_quadtree(construct_testing_box());
+        remove_from_quadtree(neighbour);
+        assert!(!super::QuadNodes::contains_key(0));
+    });
+}
+
+#[test]
+fn it_limit_nodes_searched_in_quadtree() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(insert_to_quadtree(construct_custom_box("45.0", "10.0", "55.0", "30.0")));
+        assert_ok!(insert_to_quadtree(construct_custom_box("45.0", "30.0", "45.5", "30.5")));
+        let (_, cells) = construct_root_cells(construct_custom_box("40.0", "10.0", "44.0", "30.0"));
+        assert_eq!(Quadtree::cells_overlap(cells, crate::index::QUADTREE_MAX_SEARCHED_NODES), Ok(false));
+        // Nodes of both roots are below the top one
+        assert_eq!(Quadtree::cells_overlap(cells, 1), Err(Error::IndexLimitExceeded.into()));
+        // Root around the stored ones overlaps them
+        let (_, cells) = construct_root_cells(construct_custom_box("40.0", "0.0", "60.0", "40.0"));
+        assert_eq!(Quadtree::cells_overlap(cells, crate::index::QUADTREE_MAX_SEARCHED_NODES), Ok(true));
+    });
+}
+
+#[test]
+fn it_store_root_across_antimeridian_in_quadtree() {
+    new_test_ext().execute_with(|| {
+        let root = construct_custom_box("60.0", "170.0", "65.0", "190.0");
+        assert_ok!(insert_to_quadtree(root));
+        assert_eq!(find_in_quadtree("62.0", "175.0"), construct_root_cells(root).0);
+        assert_eq!(find_in_quadtree("62.0", "-175.0"), construct_root_cells(root).0);
+        assert_eq!(find_in_quadtree("62.0", "-169.0"), 0);
+        assert_noop!(
+            insert_to_quadtree(construct_custom_box("61.0", "-179.0", "61.5", "-178.0")),
+            Error::OverlappingRoot
+        );
+        remove_from_quadtree(root);
+        assert_eq!(find_in_quadtree("62.0", "-175.0"), 0);
+        assert!(!super::QuadNodes::contains_key(0));
+    });
+}
+
+#[test]
+fn it_migrate_roots_to_spatial_index() {
+    new_test_ext_with_map(construct_testing_map()).execute_with(|| {
+        super::StorageVersion::put(0);
+        DSMapsModule::on_runtime_upgrade();
+        rebuild_index();
+        assert_eq!(DSMapsModule::storage_version(), super::STORAGE_VERSION);
+        assert_eq!(DSMapsModule::get_root_index([raw("55.395"), raw("37.385")]), ROOT_ID);
+        assert_eq!(DSMapsModule::get_root_index([raw("55.395"), raw("37.365")]), 0);
+    });
+}
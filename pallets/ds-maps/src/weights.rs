@@ -0,0 +1,145 @@
+//! Weights for pallet_ds_maps
+//!
+//! Weights depend on the components of the benchmarks, and the bounds of the runtime
+//! (areas per zone, zones per area, routes per area) are given by the caller, so no count
+//! depends on the runtime's constants. Execution times are not measured yet, and the file
+//! must be regenerated from the benchmarks on the reference hardware with scripts/benchmark.sh,
+//! which runs `benchmark --pallet pallet_ds_maps`.
+
+#![allow(unused_parens)]
+#![allow(unused_imports)]
+
+use frame_support::{traits::Get, weights::Weight};
+use sp_std::marker::PhantomData;
+
+/// Weight functions for pallet_ds_maps.
+pub struct SubstrateWeight<T>(PhantomData<T>);
+impl<T: frame_system::Config> crate::WeightInfo for SubstrateWeight<T> {
+    // Storage: DSAccountsModule AccountRegistry (r:1 w:0)
+    // Storage: DSMapsModule IndexRebuildKey (r:1 w:0)
+    // Storage: DSMapsModule RootBoxes (r:1 w:1)
+    // Storage: DSMapsModule EarthBitmap (r:p w:p)
+    fn root_add(p: u32, ) -> Weight {
+        (45_000_000 as Weight)
+            .saturating_add((30_000_000 as Weight).saturating_mul(p as Weight))
+            .saturating_add(T::DbWeight::get().reads(3 as Weight))
+            .saturating_add(T::DbWeight::get().reads((1 as Weight).saturating_mul(p as Weight)))
+            .saturating_add(T::DbWeight::get().writes(1 as Weight))
+            .saturating_add(T::DbWeight::get().writes((1 as Weight).saturating_mul(p as Weight)))
+    }
+    // Storage: DSAccountsModule AccountRegistry (r:1 w:0)
+    // Storage: DSMapsModule RootBoxes (r:1 w:0)
+    // Storage: DSMapsModule RootsToRemove (r:1 w:0)
+    // Storage: DSMapsModule AreaData (r:a w:0)
+    // Storage: DSMapsModule ZonesInArea (r:a w:a)
+    // Storage: DSMapsModule RedZones (r:a*z w:a)
+    fn zone_add(a: u32, z: u32, ) -> Weight {
+        (40_000_000 as Weight)
+            .saturating_add((20_000_000 as Weight).saturating_mul(a as Weight))
+            .saturating_add((2_000_000 as Weight).saturating_mul((a as Weight).saturating_mul(z as Weight)))
+            .saturating_add(T::DbWeight::get().reads(3 as Weight))
+            .saturating_add(T::DbWeight::get().reads((2 as Weight).saturating_mul(a as Weight)))
+            .saturating_add(T::DbWeight::get().reads((a as Weight).saturating_mul(z as Weight)))
+            .saturating_add(T::DbWeight::get().writes((2 as Weight).saturating_mul(a as Weight)))
+    }
+    // Storage: DSAccountsModule AccountRegistry (r:1 w:0)
+    // Storage: DSMapsModule RootBoxes (r:1 w:0)
+    // Storage: DSMapsModule RootsToRemove (r:1 w:1)
+    fn root_remove() -> Weight {
+        (40_000_000 as Weight)
+            .saturating_add(T::DbWeight::get().reads(3 as Weight))
+            .saturating_add(T::DbWeight::get().writes(1 as Weight))
+    }
+    // Storage: DSAccountsModule AccountRegistry (r:1 w:0)
+    // Storage: DSMapsModule RedZones (r:1+a*z w:a)
+    // Storage: DSMapsModule RootsToRemove (r:1 w:0)
+    // Storage: DSMapsModule RootBoxes (r:1 w:0)
+    // Storage: DSMapsModule ZonesInArea (r:a w:a)
+    fn zone_remove(a: u32, z: u32, ) -> Weight {
+        (40_000_000 as Weight)
+            .saturating_add((20_000_000 as Weight).saturating_mul(a as Weight))
+            .saturating_add((1_500_000 as Weight).saturating_mul((a as Weight).saturating_mul(z as Weight)))
+            .saturating_add(T::DbWeight::get().reads(4 as Weight))
+            .saturating_add(T::DbWeight::get().reads((1 as Weight).saturating_mul(a as Weight)))
+            .saturating_add(T::DbWeight::get().reads((a as Weight).saturating_mul(z as Weight)))
+            .saturating_add(T::DbWeight::get().writes((2 as Weight).saturating_mul(a as Weight)))
+    }
+    // Storage: DSAccountsModule AccountRegistry (r:1 w:0)
+    // Storage: DSMapsModule RootsToRemove (r:1 w:0)
+    // Storage: DSMapsModule AreaData (r:2 w:1)
+    fn change_area_type() -> Weight {
+        (25_000_000 as Weight)
+            .saturating_add(T::DbWeight::get().reads(4 as Weight))
+            .saturating_add(T::DbWeight::get().writes(1 as Weight))
+    }
+    // Storage: DSAccountsModule AccountRegistry (r:1 w:0)
+    // Storage: DSMapsModule RootBoxes (r:1 w:0)
+    // Storage: DSMapsModule RootsToRemove (r:1 w:0)
+    // Storage: DSMapsModule NextRouteId (r:1 w:1)
+    // Storage: DSMapsModule ZonesInArea (r:a w:0)
+    // Storage: DSMapsModule RedZones (r:z w:0)
+    // Storage: DSMapsModule RoutesInArea (r:a w:a)
+    // Storage: DSMapsModule Routes (r:r w:1)
+    // Storage: DSMapsModule RoutesInRoot (r:0 w:1)
+    fn route_add(w: u32, a: u32, z: u32, r: u32, ) -> Weight {
+        (60_000_000 as Weight)
+            .saturating_add((20_000_000 as Weight).saturating_mul(w as Weight))
+            .saturating_add((3_000_000 as Weight).saturating_mul(a as Weight))
+            .saturating_add((2_500_000 as Weight).saturating_mul(z as Weight))
+            .saturating_add((15_000_000 as Weight).saturating_mul(r as Weight))
+            .saturating_add(T::DbWeight::get().reads(4 as Weight))
+            .saturating_add(T::DbWeight::get().reads((2 as Weight).saturating_mul(a as Weight)))
+            .saturating_add(T::DbWeight::get().reads((1 as Weight).saturating_mul(z as Weight)))
+            .saturating_add(T::DbWeight::get().reads((1 as Weight).saturating_mul(r as Weight)))
+            .saturating_add(T::DbWeight::get().writes(3 as Weight))
+            .saturating_add(T::DbWeight::get().writes((1 as Weight).saturating_mul(a as Weight)))
+    }
+    // Storage: DSMapsModule Routes (r:2 w:1)
+    // Storage: DSAccountsModule AccountRegistry (r:1 w:0)
+    // Storage: DSMapsModule RootsToRemove (r:1 w:0)
+    // Storage: DSMapsModule RootBoxes (r:1 w:0)
+    // Storage: DSMapsModule RoutesInArea (r:a w:a)
+    fn change_route_status(a: u32, ) -> Weight {
+        (50_000_000 as Weight)
+            .saturating_add((3_000_000 as Weight).saturating_mul(a as Weight))
+            .saturating_add(T::DbWeight::get().reads(5 as Weight))
+            .saturating_add(T::DbWeight::get().reads((1 as Weight).saturating_mul(a as Weight)))
+            .saturating_add(T::DbWeight::get().writes(1 as Weight))
+            .saturating_add(T::DbWeight::get().writes((1 as Weight).saturating_mul(a as Weight)))
+    }
+    // Storage: DSMapsModule NextPrunedBlock (r:1 w:1)
+    // Storage: DSMapsModule ZonesExpiry (r:1+e w:e)
+    // Storage: DSMapsModule RedZones (r:e+e*a*z w:e*a)
+    // Storage: DSMapsModule RootBoxes (r:1 w:0)
+    // Storage: DSMapsModule ZonesInArea (r:e*a w:e*a)
+    fn prune_zones(e: u32, a: u32, z: u32, ) -> Weight {
+        (5_000_000 as Weight)
+            .saturating_add((30_000_000 as Weight).saturating_mul(e as Weight))
+            .saturating_add((20_000_000 as Weight).saturating_mul((e as Weight).saturating_mul(a as Weight)))
+            .saturating_add((1_500_000 as Weight).saturating_mul((e as Weight).saturating_mul(a as Weight).saturating_mul(z as Weight)))
+            .saturating_add(T::DbWeight::get().reads(3 as Weight))
+            .saturating_add(T::DbWeight::get().reads((2 as Weight).saturating_mul(e as Weight)))
+            .saturating_add(T::DbWeight::get().reads((e as Weight).saturating_mul(a as Weight)))
+            .saturating_add(T::DbWeight::get().reads((e as Weight).saturating_mul(a as Weight).saturating_mul(z as Weight)))
+            .saturating_add(T::DbWeight::get().writes(1 as Weight))
+            .saturating_add(T::DbWeight::get().writes((1 as Weight).saturating_mul(e as Weight)))
+            .saturating_add(T::DbWeight::get().writes((2 as Weight).saturating_mul((e as Weight).saturating_mul(a as Weight))))
+    }
+    // Storage: DSMapsModule RootsToRemove (r:1 w:1)
+    // Storage: DSMapsModule ZonesInArea (r:a w:a)
+    // Storage: DSMapsModule RedZones (r:0 w:a*z)
+    // Storage: DSMapsModule AreaData (r:a w:a)
+    // Storage: DSMapsModule RoutesInArea (r:1 w:0)
+    // Storage: DSMapsModule RoutesInRoot (r:1 w:0)
+    // Storage: DSMapsModule RootBoxes (r:1 w:1)
+    fn clear_root(a: u32, z: u32, ) -> Weight {
+        (120_000_000 as Weight)
+            .saturating_add((20_000_000 as Weight).saturating_mul(a as Weight))
+            .saturating_add((1_500_000 as Weight).saturating_mul((a as Weight).saturating_mul(z as Weight)))
+            .saturating_add(T::DbWeight::get().reads(4 as Weight))
+            .saturating_add(T::DbWeight::get().reads((2 as Weight).saturating_mul(a as Weight)))
+            .saturating_add(T::DbWeight::get().writes(2 as Weight))
+            .saturating_add(T::DbWeight::get().writes((2 as Weight).saturating_mul(a as Weight)))
+            .saturating_add(T::DbWeight::get().writes((a as Weight).saturating_mul(z as Weight)))
+    }
+}
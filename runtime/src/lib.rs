@@ -15,6 +15,7 @@ use sp_api::impl_runtime_apis;
 use sp_consensus_aura::sr25519::AuthorityId as AuraId;
 use sp_core::{crypto::KeyTypeId, OpaqueMetadata};
 use pallet_ds_accounts::{prelude::IdentityMultiplierUpdater, AccountOf, ADMIN_ROLE, REGISTRAR_ROLE};
+use pallet_ds_maps::{AreaId, Point3D, RootId, RouteReport, Waypoint, ZoneOf};
 use pallet_transaction_payment::{CurrencyAdapter, FeeDetails};
 use sp_runtime::traits::{
     BlakeTwo256, Block as BlockT, IdentifyAccount, IdentityLookup, NumberFor, Verify,
@@ -297,7 +298,7 @@ impl pallet_ds_accounts::Trait for Runtime {
     type AdminRole = AdminRole;
     type AccountRole = u8;
     type Currency = Balances;
-    type WeightInfo = ();
+    type WeightInfo = pallet_ds_accounts::weights::SubstrateWeight<Runtime>;
     type MetaIPFS = Vec<u8>;
     type SerialNumber = Vec<u8>;    //guess, this should be UTF-8 encoded
 }
@@ -306,17 +307,56 @@ impl pallet_ds_accounts::Trait for Runtime {
 parameter_types! {
     pub const MaxHeight: u32 = 400;
     pub const MaxBuildingsInArea: u16 = 100;
+    pub const MaxAreasPerZone: u32 = 16;
+    pub const MaxWaypoints: u32 = 16;
+    pub const MaxPolygonVertices: u32 = 32;
+    pub const HorizontalSeparation: u32 = 100;
+    pub const VerticalSeparation: u32 = 30;
+    // One minute, as timestamps are in milliseconds
+    pub const TimeSeparation: u64 = 60_000;
+    pub const MaxRoutesInArea: u32 = 64;
+    pub const MaxAreasPerRoute: u32 = 1_000;
+    pub const MaxAreasClearedPerBlock: u32 = 100;
+    pub const MaxZonesPrunedPerBlock: u32 = 100;
+    pub const MaxRootsIndexedPerBlock: u32 = 20;
+    pub const MaxIndexKeysClearedPerBlock: u32 = 500;
+    // Cells of 0.01 degree, in pages of 0.32 x 0.5 degree
+    pub const BitmapCellLength: u32 = 1;
+    pub const BitmapCellWidth: u32 = 1;
+    pub const CellSizeDegree: u8 = 2;
+    pub const PageLength: u32 = 32;
+    pub const PageWidth: u32 = 50;
+    pub const MaxPagesAmountToExtract: u32 = 4;
 }
 
 /// Configure the DS maps pallet in pallets/ds-maps.
 impl pallet_ds_maps::Trait for Runtime {
     type Event = Event;
-    type WeightInfo = ();
+    type WeightInfo = pallet_ds_maps::weights::SubstrateWeight<Runtime>;
     type RawCoord = i32;
     type Coord = I10F22;
     type BigCoord = I42F22;
     type MaxBuildingsInArea = MaxBuildingsInArea;
     type MaxHeight = MaxHeight;
+    type MaxPolygonVertices = MaxPolygonVertices;
+    type MaxAreasPerZone = MaxAreasPerZone;
+    type MaxWaypoints = MaxWaypoints;
+    type HorizontalSeparation = HorizontalSeparation;
+    type VerticalSeparation = VerticalSeparation;
+    type TimeSeparation = TimeSeparation;
+    type MaxRoutesInArea = MaxRoutesInArea;
+    type MaxAreasPerRoute = MaxAreasPerRoute;
+    type MaxAreasClearedPerBlock = MaxAreasClearedPerBlock;
+    type MaxZonesPrunedPerBlock = MaxZonesPrunedPerBlock;
+    type MaxRootsIndexedPerBlock = MaxRootsIndexedPerBlock;
+    type MaxIndexKeysClearedPerBlock = MaxIndexKeysClearedPerBlock;
+    type SpatialIndex = pallet_ds_maps::index::QuadtreeIndex<Runtime>;
+    type BitmapCellLength = BitmapCellLength;
+    type BitmapCellWidth = BitmapCellWidth;
+    type CellSizeDegree = CellSizeDegree;
+    type PageLength = PageLength;
+    type PageWidth = PageWidth;
+    type MaxPagesAmountToExtract = MaxPagesAmountToExtract;
 }
 
 // Create the runtime by composing the FRAME pallets that were previously configured.
@@ -336,7 +376,7 @@ construct_runtime!(
         Sudo: pallet_sudo::{Module, Call, Config<T>, Storage, Event<T>},
         // Include the DS account management logic from ds-accounts pallet in the runtime
         DSAccountsModule: pallet_ds_accounts::{Module, Call, Storage, Config<T>, Event<T>},
-        DSMapsModule: pallet_ds_maps::{Module, Call, Storage, Event<T>},
+        DSMapsModule: pallet_ds_maps::{Module, Call, Storage, Config, Event<T>},
     }
 );
 
@@ -374,6 +414,27 @@ pub type Executive = frame_executive::Executive<
 >;
 
 pub type DSAccount = AccountOf<Runtime>;
+pub type DSZone = ZoneOf<Runtime>;
+
+sp_api::decl_runtime_apis! {
+    /// Read-only queries of the airspace, stored by ds-maps pallet.
+    /// Coordinates are passed raw, the same way as in raw_* calls of the pallet.
+    pub trait DsMapsApi {
+        /// Returns id of the root, which contains given point, or 0 if there is none
+        fn get_root_index(raw_point: [i32; 2]) -> RootId;
+        /// Returns all zones, stored in the area
+        fn get_area_zones(root_id: RootId, area_id: AreaId) -> Vec<DSZone>;
+        /// Returns all zones from areas, which given rect overlaps
+        fn get_rect_zones(root_id: RootId, raw_rect: [i32; 4]) -> Vec<DSZone>;
+        /// Returns type of the area, or None if area has no data yet
+        fn get_area_type(root_id: RootId, area_id: AreaId) -> Option<u8>;
+        /// Returns max area index of the root, or 0 if root doesn't exist
+        fn get_max_area(root_id: RootId) -> AreaId;
+        /// Runs all checks of route_add without adding the route, and reports every problem.
+        /// Each waypoint is given by it's location and arrival time.
+        fn validate_route(raw_waypoints: Vec<([i32; 3], u64)>, root_id: RootId) -> RouteReport;
+    }
+}
 
 impl_runtime_apis! {
     impl sp_api::Core<Block> for Runtime {
@@ -503,6 +564,40 @@ impl_runtime_apis! {
         }
     }
 
+    impl self::DsMapsApi<Block> for Runtime {
+        fn get_root_index(raw_point: [i32; 2]) -> RootId {
+            DSMapsModule::get_root_index(raw_point)
+        }
+
+        fn get_area_zones(root_id: RootId, area_id: AreaId) -> Vec<DSZone> {
+            DSMapsModule::get_area_zones(root_id, area_id)
+        }
+
+        fn get_rect_zones(root_id: RootId, raw_rect: [i32; 4]) -> Vec<DSZone> {
+            DSMapsModule::get_rect_zones(root_id, raw_rect)
+        }
+
+        fn get_area_type(root_id: RootId, area_id: AreaId) -> Option<u8> {
+            DSMapsModule::get_area_type(root_id, area_id)
+        }
+
+        fn get_max_area(root_id: RootId) -> AreaId {
+            DSMapsModule::get_max_area(root_id)
+        }
+
+        fn validate_route(raw_waypoints: Vec<([i32; 3], u64)>, root_id: RootId) -> RouteReport {
+            let waypoints: Vec<_> = raw_waypoints.into_iter()
+                .map(|(raw_location, arrival)| {
+                    let location = Point3D::new(I10F22::from_bits(raw_location[0]),
+                                                I10F22::from_bits(raw_location[1]),
+                                                I10F22::from_bits(raw_location[2]));
+                    Waypoint::new(location, arrival)
+                })
+                .collect();
+            DSMapsModule::validate_route(&waypoints, root_id)
+        }
+    }
+
     #[cfg(feature = "runtime-benchmarks")]
     impl frame_benchmarking::Benchmark<Block> for Runtime {
         fn dispatch_benchmark(
@@ -532,6 +627,8 @@ impl_runtime_apis! {
             add_benchmark!(params, batches, frame_system, SystemBench::<Runtime>);
             add_benchmark!(params, batches, pallet_balances, Balances);
             add_benchmark!(params, batches, pallet_timestamp, Timestamp);
+            add_benchmark!(params, batches, pallet_ds_accounts, DSAccountsModule);
+            add_benchmark!(params, batches, pallet_ds_maps, DSMapsModule);
 
             if batches.is_empty() { return Err("Benchmark not found for this pallet.".into()) }
             Ok(batches)
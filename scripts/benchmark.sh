@@ -0,0 +1,22 @@
+#!/usr/bin/env bash
+
+set -e
+
+echo "*** Regenerating weights of the pallets ***"
+
+cd $(dirname ${BASH_SOURCE[0]})/..
+
+cargo build --release -p node-dsky --features runtime-benchmarks
+
+for pallet in ds-maps ds-accounts; do
+    ./target/release/node-dsky benchmark \
+        --chain dev \
+        --execution wasm \
+        --wasm-execution compiled \
+        --pallet pallet_${pallet/-/_} \
+        --extrinsic '*' \
+        --steps 50 \
+        --repeat 20 \
+        --template .maintain/frame-weight-template.hbs \
+        --output pallets/$pallet/src/weights.rs
+done